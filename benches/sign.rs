@@ -0,0 +1,75 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tls_sig_api::{HmacBackend, MacBackend, TlsSigApiVer2};
+
+const MOCK_KEY: &str = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+const EXPIRE: Duration = Duration::from_secs(7200);
+
+fn gen_sign(c: &mut Criterion) {
+    let signer = TlsSigApiVer2::new(1400000000, MOCK_KEY);
+
+    c.bench_function("gen_sign", |b| {
+        b.iter(|| signer.gen_sign("10086", EXPIRE, None))
+    });
+    c.bench_function("gen_sign with userbuf", |b| {
+        b.iter(|| signer.gen_sign("10086", EXPIRE, Some("room:1024;role:admin")))
+    });
+
+    let identifiers: Vec<String> = (0..100).map(|i| format!("user-{}", i)).collect();
+    c.bench_function("gen_signs of 100", |b| {
+        b.iter(|| signer.gen_signs(identifiers.iter().map(String::as_str), EXPIRE, None))
+    });
+}
+
+fn hmac_sha256(c: &mut Criterion) {
+    let backend = HmacBackend::new(MOCK_KEY);
+    let content =
+        b"TLS.identifier:10086\nTLS.sdkappid:1400000000\nTLS.time:1570000000\nTLS.expire:7200\n";
+
+    c.bench_function("hmac_sha256", |b| b.iter(|| backend.hmac_sha256(content)));
+}
+
+/// signing while the key is rotated every 100µs and the other cores sign as
+/// well, which should cost about the same as signing alone.
+fn gen_sign_under_contention(c: &mut Criterion) {
+    let signer = Arc::new(TlsSigApiVer2::new(1400000000, MOCK_KEY));
+    let stop = Arc::new(AtomicBool::new(false));
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+
+    let mut workers = Vec::new();
+    {
+        let signer = Arc::clone(&signer);
+        let stop = Arc::clone(&stop);
+        workers.push(thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                signer.update_key(MOCK_KEY);
+                thread::sleep(Duration::from_micros(100));
+            }
+        }));
+    }
+    for _ in 1..cores {
+        let signer = Arc::clone(&signer);
+        let stop = Arc::clone(&stop);
+        workers.push(thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                signer.gen_sign("10010", EXPIRE, None);
+            }
+        }));
+    }
+
+    c.bench_function("gen_sign under contention", |b| {
+        b.iter(|| signer.gen_sign("10086", EXPIRE, None))
+    });
+
+    stop.store(true, Ordering::Relaxed);
+    for worker in workers {
+        worker.join().unwrap();
+    }
+}
+
+criterion_group!(benches, gen_sign, hmac_sha256, gen_sign_under_contention);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use std::time::SystemTime;
+
+/// Called on every sig issued, so that operators can keep an audit trail of
+/// which identities received credentials, with whatever logging or metrics
+/// stack they use.
+///
+/// It is implemented for closures taking the same arguments as [`on_sign`].
+///
+/// [`on_sign`]: #tymethod.on_sign
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::TlsSigApiVer2;
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+///
+/// let issued = Arc::new(Mutex::new(Vec::new()));
+/// let trail = Arc::clone(&issued);
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = TlsSigApiVer2::new(1400000000, mock_key).with_audit_hook(
+///     move |identifier: &str, sdkappid: u64, _expires_at| {
+///         trail.lock().unwrap().push(format!("{}@{}", identifier, sdkappid));
+///     },
+/// );
+///
+/// signer.gen_sign("10086", Duration::from_secs(7200), None);
+/// assert_eq!(*issued.lock().unwrap(), vec!["10086@1400000000"]);
+/// ```
+pub trait AuditHook: Send + Sync {
+    /// `identifier` has been issued a sig by `sdkappid`, valid until
+    /// `expires_at`.
+    fn on_sign(&self, identifier: &str, sdkappid: u64, expires_at: SystemTime);
+}
+
+impl<F> AuditHook for F
+where
+    F: Fn(&str, u64, SystemTime) + Send + Sync,
+{
+    fn on_sign(&self, identifier: &str, sdkappid: u64, expires_at: SystemTime) {
+        self(identifier, sdkappid, expires_at)
+    }
+}
@@ -0,0 +1,153 @@
+//! base64 with the standard alphabet, encoded and decoded with SIMD when the
+//! `simd-base64` feature is enabled, and the url-safe alphabet of the official
+//! SDKs on top of it.
+
+use crate::TlsSigError;
+
+/// The base64 alphabet sigs are encoded in, see
+/// [`TlsSigApiVer2::with_base64_alphabet`].
+///
+/// It is the only base64 configuration exposed, so the base64 crate used can
+/// change without breaking users.
+///
+/// [`TlsSigApiVer2::with_base64_alphabet`]: struct.TlsSigApiVer2.html#method.with_base64_alphabet
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::Base64Alphabet;
+///
+/// let encoded = Base64Alphabet::TencentUrlSafe.encode(&[0xfb, 0xff]);
+/// assert_eq!(encoded, "*-8_");
+/// assert_eq!(Base64Alphabet::TencentUrlSafe.decode(&encoded).unwrap(), vec![0xfb, 0xff]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Base64Alphabet {
+    /// `+`, `/` and `=` as in RFC 4648.
+    #[default]
+    Standard,
+    /// `*`, `-` and `_` instead, as the official SDKs do, so that sigs can be
+    /// put into urls without escaping. Decoding accepts the standard alphabet
+    /// too.
+    TencentUrlSafe,
+}
+
+impl Base64Alphabet {
+    pub fn encode(self, bytes: &[u8]) -> String {
+        let mut encoded = encode(bytes);
+        self.convert_tail(&mut encoded, 0);
+        encoded
+    }
+
+    /// decode `encoded`, returning [`TlsSigError::MalformedSig`] if it is not
+    /// valid base64.
+    ///
+    /// [`TlsSigError::MalformedSig`]: enum.TlsSigError.html#variant.MalformedSig
+    pub fn decode(self, encoded: &str) -> crate::Result<Vec<u8>> {
+        let decoded = match self {
+            Base64Alphabet::Standard => decode(encoded),
+            Base64Alphabet::TencentUrlSafe => decode(&from_url_safe(encoded)),
+        };
+
+        decoded.map_err(|e| TlsSigError::MalformedSig(format!("invalid base64: {}", e)))
+    }
+
+    /// convert the standard base64 of `encoded` from `start` to this alphabet.
+    pub(crate) fn convert_tail(self, encoded: &mut String, start: usize) {
+        if self == Base64Alphabet::TencentUrlSafe {
+            let url_safe = to_url_safe(&encoded[start..]);
+            encoded.truncate(start);
+            encoded.push_str(&url_safe);
+        }
+    }
+}
+
+fn to_url_safe(standard: &str) -> String {
+    standard
+        .chars()
+        .map(|c| match c {
+            '+' => '*',
+            '/' => '-',
+            '=' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+fn from_url_safe(url_safe: &str) -> String {
+    url_safe
+        .chars()
+        .map(|c| match c {
+            '*' => '+',
+            '-' => '/',
+            '_' => '=',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "simd-base64"))]
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::STANDARD)
+}
+
+#[cfg(feature = "simd-base64")]
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    base64_simd::STANDARD.encode_to_string(bytes)
+}
+
+/// encode `bytes` onto the end of `out`.
+#[cfg(feature = "simd-base64")]
+pub(crate) fn encode_append(bytes: &[u8], out: &mut String) {
+    base64_simd::STANDARD.encode_append(bytes, out);
+}
+
+/// decode `encoded`, or describe why it is not valid base64.
+#[cfg(not(feature = "simd-base64"))]
+pub(crate) fn decode(encoded: &str) -> Result<Vec<u8>, String> {
+    base64::decode_config(encoded, base64::STANDARD).map_err(|e| e.to_string())
+}
+
+/// decode `encoded`, or describe why it is not valid base64.
+#[cfg(feature = "simd-base64")]
+pub(crate) fn decode(encoded: &str) -> Result<Vec<u8>, String> {
+    // unpadded input is accepted too, like without SIMD
+    let engine = if encoded.len().is_multiple_of(4) {
+        base64_simd::STANDARD
+    } else {
+        base64_simd::STANDARD_NO_PAD
+    };
+
+    engine.decode_to_vec(encoded).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, encode, Base64Alphabet};
+    use crate::TlsSigError;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(encode(b"abc"), "YWJj");
+        assert_eq!(encode(&[0xfb, 0xff]), "+/8=");
+        assert_eq!(decode("+/8=").unwrap(), vec![0xfb, 0xff]);
+
+        assert_eq!(decode("+/8").unwrap(), vec![0xfb, 0xff]);
+        assert!(decode("not base64").is_err());
+    }
+
+    #[test]
+    fn test_base64_alphabet() {
+        let standard = Base64Alphabet::Standard;
+        let url_safe = Base64Alphabet::TencentUrlSafe;
+        assert_eq!(standard.encode(&[0xfb, 0xff]), "+/8=");
+        assert_eq!(url_safe.encode(&[0xfb, 0xff]), "*-8_");
+
+        assert_eq!(url_safe.decode("*-8_").unwrap(), vec![0xfb, 0xff]);
+        assert_eq!(url_safe.decode("+/8=").unwrap(), vec![0xfb, 0xff]);
+        assert!(matches!(
+            standard.decode("*-8_"),
+            Err(TlsSigError::MalformedSig(_))
+        ));
+    }
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use crate::{BatchBuffers, MacBackend, PendingSig, Result, TlsSigApiVer2, UserSig};
+
+/// Signs identifiers lazily as it is iterated, see
+/// [`TlsSigApiVer2::sign_iter`].
+///
+/// [`TlsSigApiVer2::sign_iter`]: struct.TlsSigApiVer2.html#method.sign_iter
+pub struct SignIter<'a, I> {
+    signer: &'a TlsSigApiVer2,
+    identifiers: I,
+    time: i64,
+    shared: PendingSig,
+    mac: Arc<Box<dyn MacBackend>>,
+    buffers: BatchBuffers,
+}
+
+impl<'a, I> SignIter<'a, I> {
+    pub(crate) fn new(
+        signer: &'a TlsSigApiVer2,
+        identifiers: I,
+        time: i64,
+        shared: PendingSig,
+        mac: Arc<Box<dyn MacBackend>>,
+    ) -> Self {
+        SignIter {
+            signer,
+            identifiers,
+            time,
+            shared,
+            mac,
+            buffers: BatchBuffers::default(),
+        }
+    }
+}
+
+impl<I> Iterator for SignIter<'_, I>
+where
+    I: Iterator,
+    I::Item: AsRef<str>,
+{
+    type Item = (I::Item, Result<UserSig>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let identifier = self.identifiers.next()?;
+        let sig = self.signer.sign_in_batch(
+            &**self.mac,
+            identifier.as_ref(),
+            self.time,
+            &self.shared,
+            &mut self.buffers,
+        );
+
+        Some((identifier, sig))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.identifiers.size_hint()
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
+
+use crate::{ExpireDuration, Result, SigInfo, TlsSigApiVer2, UserSig};
+
+/// A signer returning the sig issued before for the same identifier, expire
+/// and userbuf, as long as enough of its lifetime is left, for backends
+/// re-issuing sigs to the same hot users constantly.
+///
+/// At most `capacity` sigs are kept, evicting the least recently used. A sig
+/// is reused while at least 20% of its lifetime remains by default, see
+/// [`with_min_remaining`]. Freshness is timed by the clock of the signer.
+///
+/// Sigs signed before [`TlsSigApiVer2::update_key`] are never reused, so a
+/// leaked key stops being served right away. Keys rotated behind a
+/// [`KeyProvider`] can not be noticed, call [`clear`] after rotating them.
+///
+/// [`with_min_remaining`]: #method.with_min_remaining
+/// [`TlsSigApiVer2::update_key`]: struct.TlsSigApiVer2.html#method.update_key
+/// [`KeyProvider`]: trait.KeyProvider.html
+/// [`clear`]: #method.clear
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{SigCache, TlsSigApiVer2};
+/// use std::time::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let cache = SigCache::new(TlsSigApiVer2::new(0, mock_key), 10000);
+///
+/// let expire = Duration::from_secs(7200);
+/// let sig = cache.try_gen_sign("10086", expire, None).unwrap();
+/// assert_eq!(cache.try_gen_sign("10086", expire, None).unwrap(), sig);
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct SigCache {
+    signer: TlsSigApiVer2,
+    capacity: usize,
+    min_remaining: f64,
+    state: Mutex<State>,
+}
+
+/// the identifier, expire and SHA-256 of the userbuf a sig is issued for.
+pub(crate) type CacheKey = (String, i64, Option<[u8; 32]>);
+
+pub(crate) fn cache_key(identifier: &str, expire: i64, userbuf: Option<&str>) -> CacheKey {
+    (
+        identifier.to_string(),
+        expire,
+        userbuf.map(|buf| {
+            let mut digest = [0; 32];
+            digest.copy_from_slice(&Sha256::digest(buf.as_bytes()));
+            digest
+        }),
+    )
+}
+
+/// until when `info` has at least `min_remaining` of its lifetime left.
+pub(crate) fn fresh_until(info: &SigInfo, min_remaining: f64) -> SystemTime {
+    let lifetime = info
+        .expires_at
+        .duration_since(info.issued_at)
+        .unwrap_or_default();
+
+    info.expires_at
+        .checked_sub(lifetime.mul_f64(min_remaining))
+        .unwrap_or(info.issued_at)
+}
+
+/// whether `info` is still worth reusing at `now`.
+pub(crate) fn is_fresh(info: &SigInfo, min_remaining: f64, now: SystemTime) -> bool {
+    now < info.expires_at && now <= fresh_until(info, min_remaining)
+}
+
+#[derive(Debug, Default)]
+struct State {
+    entries: HashMap<CacheKey, Entry>,
+    /// keys by the tick they were last used at, oldest first.
+    recency: BTreeMap<u64, CacheKey>,
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    info: SigInfo,
+    /// the key version of the signer the sig is signed with.
+    key_version: u64,
+    last_used: u64,
+}
+
+impl SigCache {
+    /// keep at most `capacity` sigs, nothing is kept if it is zero.
+    pub fn new(signer: TlsSigApiVer2, capacity: usize) -> Self {
+        SigCache {
+            signer,
+            capacity,
+            min_remaining: 0.2,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// reuse sigs only while at least `fraction` of their lifetime remains,
+    /// 0.2 by default. It is clamped between 0 and 1.
+    pub fn with_min_remaining(mut self, fraction: f64) -> Self {
+        self.min_remaining = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// the wrapped signer, e.g. for verifying. Sigs issued through it
+    /// directly are not cached.
+    pub fn signer(&self) -> &TlsSigApiVer2 {
+        &self.signer
+    }
+
+    /// the number of sigs kept.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// drop every sig kept, e.g. after the key is rotated behind a key
+    /// provider.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.recency.clear();
+    }
+
+    /// drop the sigs kept for `identifier`, of any expire and userbuf, e.g.
+    /// after it is banned.
+    pub fn invalidate(&self, identifier: &str) {
+        let mut state = self.lock();
+        let State {
+            entries, recency, ..
+        } = &mut *state;
+        entries.retain(|key, entry| {
+            let keep = key.0 != identifier;
+            if !keep {
+                recency.remove(&entry.last_used);
+            }
+            keep
+        });
+    }
+
+    /// generate user sign like [`TlsSigApiVer2::try_gen_sign`], or return the
+    /// one cached if it is still fresh.
+    ///
+    /// [`TlsSigApiVer2::try_gen_sign`]: struct.TlsSigApiVer2.html#method.try_gen_sign
+    pub fn try_gen_sign<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<UserSig> {
+        self.gen_sign_info(identifier, expire, userbuf)
+            .map(|info| info.sig)
+    }
+
+    /// generate user sign with its issuance metadata like
+    /// [`TlsSigApiVer2::gen_sign_info`], or return the one cached if it is
+    /// still fresh.
+    ///
+    /// [`TlsSigApiVer2::gen_sign_info`]: struct.TlsSigApiVer2.html#method.gen_sign_info
+    pub fn gen_sign_info<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<SigInfo> {
+        let expire = expire.expire_secs();
+        let key = cache_key(identifier, expire, userbuf);
+        let key_version = self.signer.key_version();
+
+        if let Some(info) = self.lookup(&key, key_version) {
+            return Ok(info);
+        }
+
+        // signed unlocked, so that misses of other identifiers do not wait
+        let info = self.signer.sign_info(
+            identifier,
+            self.signer.curr_timestamp(),
+            expire,
+            userbuf.map(str::as_bytes),
+        )?;
+        self.insert(key, info.clone(), key_version);
+
+        Ok(info)
+    }
+
+    /// the cached sig of `key` if it is fresh and signed with the key of
+    /// `key_version`, dropping it if it is not.
+    fn lookup(&self, key: &CacheKey, key_version: u64) -> Option<SigInfo> {
+        let now = self.signer.clock.now();
+        let mut state = self.lock();
+
+        let tick = state.next_tick();
+        let State {
+            entries, recency, ..
+        } = &mut *state;
+        let entry = entries.get_mut(key)?;
+        recency.remove(&entry.last_used);
+
+        if entry.key_version != key_version || !is_fresh(&entry.info, self.min_remaining, now) {
+            entries.remove(key);
+            return None;
+        }
+
+        entry.last_used = tick;
+        recency.insert(tick, key.clone());
+        Some(entry.info.clone())
+    }
+
+    fn insert(&self, key: CacheKey, info: SigInfo, key_version: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut state = self.lock();
+        let tick = state.next_tick();
+        let State {
+            entries, recency, ..
+        } = &mut *state;
+
+        if let Some(previous) = entries.insert(
+            key.clone(),
+            Entry {
+                info,
+                key_version,
+                last_used: tick,
+            },
+        ) {
+            recency.remove(&previous.last_used);
+        }
+        recency.insert(tick, key);
+
+        while entries.len() > self.capacity {
+            match recency.pop_first() {
+                Some((_, oldest)) => entries.remove(&oldest),
+                None => break,
+            };
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl State {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SigCache;
+    use crate::clock::ManualClock;
+    use crate::TlsSigApiVer2;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const MOCK_KEY: &str = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    const EXPIRE: Duration = Duration::from_secs(1000);
+
+    fn cache(capacity: usize) -> (SigCache, ManualClock) {
+        let clock = ManualClock(Arc::new(AtomicU64::new(1569910200)));
+        let signer = TlsSigApiVer2::new(1400000000, MOCK_KEY).with_clock(clock.clone());
+
+        (SigCache::new(signer, capacity), clock)
+    }
+
+    #[test]
+    fn test_sig_cache_freshness() {
+        let (cache, clock) = cache(10);
+        let sig = cache.try_gen_sign("0", EXPIRE, Some("abc")).unwrap();
+
+        clock.0.fetch_add(800, Ordering::SeqCst);
+        assert_eq!(cache.try_gen_sign("0", EXPIRE, Some("abc")).unwrap(), sig);
+        // other expires and userbufs are other sigs
+        assert_ne!(cache.try_gen_sign("0", EXPIRE, None).unwrap(), sig);
+        assert_ne!(
+            cache
+                .try_gen_sign("0", Duration::from_secs(2000), Some("abc"))
+                .unwrap(),
+            sig
+        );
+
+        // less than 20% left
+        clock.0.fetch_add(1, Ordering::SeqCst);
+        let renewed = cache.try_gen_sign("0", EXPIRE, Some("abc")).unwrap();
+        assert_ne!(renewed, sig);
+        assert!(cache.signer().verify_sign("0", &renewed).is_ok());
+    }
+
+    #[test]
+    fn test_sig_cache_lru() {
+        let (cache, clock) = cache(2);
+        let first = cache.try_gen_sign("first", EXPIRE, None).unwrap();
+        cache.try_gen_sign("second", EXPIRE, None).unwrap();
+
+        // `first` is used last, so `second` is evicted
+        assert_eq!(cache.try_gen_sign("first", EXPIRE, None).unwrap(), first);
+        cache.try_gen_sign("third", EXPIRE, None).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(!cache
+            .lock()
+            .entries
+            .contains_key(&("second".to_string(), 1000, None)));
+
+        clock.0.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(cache.try_gen_sign("first", EXPIRE, None).unwrap(), first);
+        assert_eq!(cache.len(), 2);
+
+        let (disabled, _) = self::cache(0);
+        disabled.try_gen_sign("first", EXPIRE, None).unwrap();
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn test_sig_cache_key_rotation() {
+        let (cache, _) = cache(10);
+        let old = cache.try_gen_sign("0", EXPIRE, None).unwrap();
+
+        cache.signer().update_key("new key");
+        let new = cache.try_gen_sign("0", EXPIRE, None).unwrap();
+        assert_ne!(new, old);
+        assert!(cache.signer().verify_sign("0", &new).is_ok());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_sig_cache_invalidate() {
+        let (cache, _) = cache(10);
+        cache.try_gen_sign("0", EXPIRE, None).unwrap();
+        cache.try_gen_sign("0", EXPIRE, Some("abc")).unwrap();
+        cache.try_gen_sign("1", EXPIRE, None).unwrap();
+
+        cache.invalidate("0");
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.lock().recency.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert!(cache.lock().recency.is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::*;
+
+use crate::{ExpireDuration, Result, SharedSigner, SigInfo, UserSig};
+
+/// The sig of one identifier, renewed when it comes close to expiring, for
+/// long-lived bots and server admin identities.
+///
+/// [`current`] returns the sig as long as more than the renewal threshold is
+/// left, a fifth of the expire by default, see [`with_renew_before`], and
+/// signs a new one otherwise. If renewing fails while the sig is still valid,
+/// it is kept and the failure logged.
+///
+/// The sig is renewed as well once [`TlsSigApiVer2::update_key`] replaces the
+/// key, or after [`invalidate`], e.g. when the key is rotated behind a key
+/// provider. It is never kept on failures then, as the old key may have
+/// leaked.
+///
+/// [`current`]: #method.current
+/// [`with_renew_before`]: #method.with_renew_before
+/// [`TlsSigApiVer2::update_key`]: struct.TlsSigApiVer2.html#method.update_key
+/// [`invalidate`]: #method.invalidate
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{CachedSig, TlsSigApiVer2};
+/// use std::time::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = TlsSigApiVer2::new(1400000000, mock_key);
+///
+/// let admin = CachedSig::new(signer, "administrator", Duration::from_secs(7200), None)
+///     .unwrap()
+///     .with_renew_before(Duration::from_secs(600));
+/// let sig = admin.current().unwrap();
+/// assert!(admin.signer().verify_sign("administrator", &sig).is_ok());
+/// ```
+#[derive(Debug)]
+pub struct CachedSig {
+    signer: SharedSigner,
+    identifier: String,
+    expire: i64,
+    userbuf: Option<String>,
+    renew_before: Duration,
+    issued: Mutex<Issued>,
+}
+
+#[derive(Debug)]
+struct Issued {
+    info: SigInfo,
+    /// the key version of the signer the sig is signed with, `None` once it
+    /// is invalidated.
+    key_version: Option<u64>,
+}
+
+impl CachedSig {
+    /// sign `identifier` with `signer` right away, failing if it can not be
+    /// signed at all.
+    pub fn new<S: Into<SharedSigner>, E: ExpireDuration>(
+        signer: S,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<Self> {
+        let signer = signer.into();
+        let expire = expire.expire_secs();
+        let key_version = signer.key_version();
+        let info = signer.sign_info(
+            identifier,
+            signer.curr_timestamp(),
+            expire,
+            userbuf.map(str::as_bytes),
+        )?;
+
+        Ok(CachedSig {
+            signer,
+            identifier: identifier.to_string(),
+            expire,
+            userbuf: userbuf.map(str::to_string),
+            renew_before: Duration::from_secs(expire.max(0) as u64 / 5),
+            issued: Mutex::new(Issued {
+                info,
+                key_version: Some(key_version),
+            }),
+        })
+    }
+
+    /// renew the sig once at most `before` is left until it expires.
+    pub fn with_renew_before(mut self, before: Duration) -> Self {
+        self.renew_before = before;
+        self
+    }
+
+    /// the signer, e.g. for verifying.
+    pub fn signer(&self) -> &SharedSigner {
+        &self.signer
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// renew the sig on the next [`current`], e.g. after the key is rotated
+    /// behind a key provider.
+    ///
+    /// [`current`]: #method.current
+    pub fn invalidate(&self) {
+        self.lock().key_version = None;
+    }
+
+    /// the sig, renewed first if it is about to expire.
+    pub fn current(&self) -> Result<UserSig> {
+        self.current_info().map(|info| info.sig)
+    }
+
+    /// the sig with its issuance metadata, renewed first if it is about to
+    /// expire.
+    pub fn current_info(&self) -> Result<SigInfo> {
+        let now = self.signer.clock.now();
+        let key_version = self.signer.key_version();
+        let mut issued = self.lock();
+
+        let current_key = issued.key_version == Some(key_version);
+        let remaining = issued
+            .info
+            .expires_at
+            .duration_since(now)
+            .unwrap_or_default();
+        if current_key && remaining > self.renew_before {
+            return Ok(issued.info.clone());
+        }
+
+        match self.renew() {
+            Ok(info) => {
+                *issued = Issued {
+                    info,
+                    key_version: Some(key_version),
+                }
+            }
+            Err(e) if current_key && issued.info.expires_at > now => warn!(
+                "failed to renew the sig of {}, expiring in {:?}: {}",
+                self.identifier, remaining, e
+            ),
+            Err(e) => return Err(e),
+        }
+
+        Ok(issued.info.clone())
+    }
+
+    fn renew(&self) -> Result<SigInfo> {
+        let info = self.signer.sign_info(
+            &self.identifier,
+            self.signer.curr_timestamp(),
+            self.expire,
+            self.userbuf.as_deref().map(str::as_bytes),
+        )?;
+        debug!(
+            "renewed the sig of {}, expiring at {:?}",
+            self.identifier, info.expires_at
+        );
+
+        Ok(info)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Issued> {
+        self.issued.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CachedSig;
+    use crate::clock::ManualClock;
+    use crate::TlsSigApiVer2;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    const MOCK_KEY: &str = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+
+    #[test]
+    fn test_cached_sig_renewal() {
+        let clock = ManualClock(Arc::new(AtomicU64::new(1569910200)));
+        let signer = TlsSigApiVer2::new(1400000000, MOCK_KEY).with_clock(clock.clone());
+        let cached = CachedSig::new(signer, "bot", Duration::from_secs(1000), Some("abc")).unwrap();
+        let first = cached.current().unwrap();
+
+        // renewed once at most a fifth is left
+        clock.0.fetch_add(799, Ordering::SeqCst);
+        assert_eq!(cached.current().unwrap(), first);
+        clock.0.fetch_add(1, Ordering::SeqCst);
+        let renewed = cached.current_info().unwrap();
+        assert_ne!(renewed.sig, first);
+        assert_eq!(
+            renewed.issued_at,
+            UNIX_EPOCH + Duration::from_secs(1569911000)
+        );
+        assert_eq!(
+            renewed.sig.payload().unwrap().userbuf_bytes().unwrap(),
+            Some(b"abc".to_vec())
+        );
+        assert_eq!(cached.current().unwrap(), renewed.sig);
+    }
+
+    #[test]
+    fn test_cached_sig_invalidation() {
+        let clock = ManualClock(Arc::new(AtomicU64::new(1569910200)));
+        let signer = TlsSigApiVer2::new(1400000000, MOCK_KEY).with_clock(clock.clone());
+        let cached = CachedSig::new(signer, "bot", Duration::from_secs(1000), None).unwrap();
+        let first = cached.current().unwrap();
+
+        cached.signer().update_key("new key");
+        let rotated = cached.current().unwrap();
+        assert_ne!(rotated, first);
+        assert!(cached.signer().verify_sign("bot", &rotated).is_ok());
+
+        clock.0.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(cached.current().unwrap(), rotated);
+        cached.invalidate();
+        assert_ne!(cached.current().unwrap(), rotated);
+    }
+
+    #[test]
+    fn test_cached_sig_invalid_identifier() {
+        let signer = TlsSigApiVer2::new(1400000000, MOCK_KEY);
+        assert!(CachedSig::new(signer, "", Duration::from_secs(1000), None).is_err());
+    }
+}
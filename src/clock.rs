@@ -0,0 +1,42 @@
+use std::time::SystemTime;
+
+/// Source of current time for signing and verifying.
+///
+/// The signer uses [`SystemClock`] by default, implement this trait to inject
+/// a mock clock in tests or to compensate a known clock skew.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+}
+
+/// The clock of the operating system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that always reports the same time, mostly for tests.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub SystemTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.0
+    }
+}
+
+/// a clock that only moves when told to, for tests.
+#[cfg(test)]
+#[derive(Clone)]
+pub(crate) struct ManualClock(pub(crate) std::sync::Arc<std::sync::atomic::AtomicU64>);
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        let secs = self.0.load(std::sync::atomic::Ordering::SeqCst);
+        std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs)
+    }
+}
@@ -0,0 +1,22 @@
+//! Helpers shared by the request signatures of cloud APIs, i.e. AWS
+//! Signature Version 4 and Tencent Cloud API signature v3, which both derive
+//! their signing key by chained HMAC-SHA256.
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+/// HMAC-SHA256 of `data` with a key of any length.
+pub(crate) fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_varkey(key).expect("HMAC accepts keys of any length");
+    mac.input(data);
+    mac.result().code().to_vec()
+}
+
+/// lowercase hex of the SHA256 of `data`, as in canonical requests.
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,173 @@
+use std::fmt;
+use std::io::{self, Write};
+
+/// How hard the payload of a sig is compressed, see
+/// [`TlsSigApiVer2::with_compression`].
+///
+/// The official SDKs compress at the best level, but sigs of any level are
+/// decoded and verified the same. Faster levels cut signing latency for a few
+/// more bytes per sig.
+///
+/// [`TlsSigApiVer2::with_compression`]: struct.TlsSigApiVer2.html#method.with_compression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CompressionLevel {
+    Fast,
+    Default,
+    /// The level of the official SDKs.
+    #[default]
+    Best,
+}
+
+impl CompressionLevel {
+    /// the zlib level, from 1 to 9.
+    #[cfg(any(feature = "flate2", feature = "miniz-oxide-compressor"))]
+    fn zlib_level(self) -> u8 {
+        match self {
+            CompressionLevel::Fast => 1,
+            CompressionLevel::Default => 6,
+            CompressionLevel::Best => 9,
+        }
+    }
+}
+
+/// The zlib implementation compressing the payload of sigs.
+///
+/// It is implemented by [`Deflate`], [`Flate2`] and [`MinizOxide`] behind the
+/// cargo features `deflate`, `flate2` and `miniz-oxide-compressor`, so the
+/// one already in the dependency tree can be reused. Signers use the first one enabled in that order, see
+/// [`TlsSigApiVer2::with_compressor`] to pick another.
+///
+/// Only [`Deflate`] produces the very same bytes as the official SDKs, but
+/// sigs of any backend are decoded and verified the same.
+///
+/// [`Deflate`]: struct.Deflate.html
+/// [`Flate2`]: struct.Flate2.html
+/// [`MinizOxide`]: struct.MinizOxide.html
+/// [`TlsSigApiVer2::with_compressor`]: struct.TlsSigApiVer2.html#method.with_compressor
+pub trait Compressor: fmt::Debug + Send + Sync {
+    /// write the zlib stream of `raw` compressed at `level` to `out`.
+    fn compress(&self, raw: &[u8], level: CompressionLevel, out: &mut dyn Write) -> io::Result<()>;
+}
+
+/// The pure Rust [`deflate`](https://docs.rs/deflate) crate, the default.
+///
+/// Requires the `deflate` feature.
+#[cfg(feature = "deflate")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Deflate;
+
+#[cfg(feature = "deflate")]
+impl Compressor for Deflate {
+    fn compress(&self, raw: &[u8], level: CompressionLevel, out: &mut dyn Write) -> io::Result<()> {
+        let level = match level {
+            CompressionLevel::Fast => deflate::Compression::Fast,
+            CompressionLevel::Default => deflate::Compression::Default,
+            CompressionLevel::Best => deflate::Compression::Best,
+        };
+        let mut zlib = deflate::write::ZlibEncoder::new(out, level);
+        zlib.write_all(raw)?;
+        zlib.finish().map(|_| ())
+    }
+}
+
+/// The [`flate2`](https://docs.rs/flate2) crate, with whichever backend its
+/// own features select.
+///
+/// Requires the `flate2` feature.
+#[cfg(feature = "flate2")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Flate2;
+
+#[cfg(feature = "flate2")]
+impl Compressor for Flate2 {
+    fn compress(&self, raw: &[u8], level: CompressionLevel, out: &mut dyn Write) -> io::Result<()> {
+        let level = flate2::Compression::new(u32::from(level.zlib_level()));
+        let mut zlib = flate2::write::ZlibEncoder::new(out, level);
+        zlib.write_all(raw)?;
+        zlib.finish().map(|_| ())
+    }
+}
+
+/// The [`miniz_oxide`](https://docs.rs/miniz_oxide) crate, which decodes sigs
+/// anyway.
+///
+/// Requires the `miniz-oxide-compressor` feature.
+#[cfg(feature = "miniz-oxide-compressor")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinizOxide;
+
+#[cfg(feature = "miniz-oxide-compressor")]
+impl Compressor for MinizOxide {
+    fn compress(&self, raw: &[u8], level: CompressionLevel, out: &mut dyn Write) -> io::Result<()> {
+        out.write_all(&miniz_oxide::deflate::compress_to_vec_zlib(
+            raw,
+            level.zlib_level(),
+        ))
+    }
+}
+
+/// the compressor of new signers.
+pub(crate) fn default_compressor() -> Box<dyn Compressor> {
+    #[cfg(feature = "deflate")]
+    return Box::new(Deflate);
+    #[cfg(all(feature = "flate2", not(feature = "deflate")))]
+    return Box::new(Flate2);
+    #[cfg(all(
+        feature = "miniz-oxide-compressor",
+        not(any(feature = "deflate", feature = "flate2"))
+    ))]
+    return Box::new(MinizOxide);
+    // only the compile_error! below is reported then
+    #[cfg(not(any(
+        feature = "deflate",
+        feature = "flate2",
+        feature = "miniz-oxide-compressor"
+    )))]
+    unreachable!()
+}
+
+#[cfg(not(any(
+    feature = "deflate",
+    feature = "flate2",
+    feature = "miniz-oxide-compressor"
+)))]
+compile_error!(
+    "enable one of the `deflate`, `flate2` or `miniz-oxide-compressor` features to compress sigs"
+);
+
+#[cfg(test)]
+mod test {
+    use super::{default_compressor, CompressionLevel, Compressor};
+    use miniz_oxide::inflate::decompress_to_vec_zlib;
+
+    const RAW: &[u8] = br#"{"TLS.ver":"2.0","TLS.identifier":"0","TLS.sdkappid":1400000000}"#;
+
+    fn assert_round_trip<C: Compressor + ?Sized>(compressor: &C) {
+        for level in [
+            CompressionLevel::Fast,
+            CompressionLevel::Default,
+            CompressionLevel::Best,
+        ] {
+            let mut compressed = Vec::new();
+            compressor.compress(RAW, level, &mut compressed).unwrap();
+            assert_eq!(decompress_to_vec_zlib(&compressed).unwrap(), RAW);
+        }
+    }
+
+    #[test]
+    fn test_default_compressor() {
+        assert_round_trip(&*default_compressor());
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn test_flate2() {
+        assert_round_trip(&super::Flate2);
+    }
+
+    #[cfg(feature = "miniz-oxide-compressor")]
+    #[test]
+    fn test_miniz_oxide() {
+        assert_round_trip(&super::MinizOxide);
+    }
+}
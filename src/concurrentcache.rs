@@ -0,0 +1,318 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dashmap::DashMap;
+
+use crate::cache::{cache_key, fresh_until, CacheKey};
+use crate::{ExpireDuration, Result, SigInfo, TlsSigApiVer2, UserSig};
+
+/// A [`SigCache`] for multi-threaded servers, backed by a sharded
+/// [`DashMap`](https://docs.rs/dashmap) so that hundreds of handlers sharing
+/// it behind an `Arc` do not contend on one lock.
+///
+/// Each entry carries its own TTL: it is reused until less than 20% of the
+/// lifetime of its sig remains by default, see [`with_min_remaining`], and
+/// dropped afterwards. There is no LRU order to keep up to date on hits, so
+/// once `capacity` sigs are kept, expired ones are swept and new sigs are
+/// not cached until there is room again.
+///
+/// Like [`SigCache`], sigs signed before [`TlsSigApiVer2::update_key`] are
+/// never reused, and [`clear`] drops them after rotating a key provider.
+///
+/// Requires the `dashmap` feature.
+///
+/// [`SigCache`]: struct.SigCache.html
+/// [`with_min_remaining`]: #method.with_min_remaining
+/// [`TlsSigApiVer2::update_key`]: struct.TlsSigApiVer2.html#method.update_key
+/// [`clear`]: #method.clear
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{ConcurrentSigCache, TlsSigApiVer2};
+/// use std::sync::Arc;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let cache = Arc::new(ConcurrentSigCache::new(TlsSigApiVer2::new(0, mock_key), 10000));
+///
+/// let expire = Duration::from_secs(7200);
+/// let sig = cache.try_gen_sign("10086", expire, None).unwrap();
+/// let handler = {
+///     let cache = Arc::clone(&cache);
+///     thread::spawn(move || cache.try_gen_sign("10086", expire, None).unwrap())
+/// };
+/// assert_eq!(handler.join().unwrap(), sig);
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct ConcurrentSigCache {
+    signer: TlsSigApiVer2,
+    capacity: usize,
+    min_remaining: f64,
+    entries: DashMap<CacheKey, Entry>,
+    /// the unix second expired entries were last swept at.
+    last_swept: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    info: SigInfo,
+    fresh_until: SystemTime,
+    /// the key version of the signer the sig is signed with.
+    key_version: u64,
+}
+
+impl Entry {
+    fn is_fresh(&self, now: SystemTime, key_version: u64) -> bool {
+        self.key_version == key_version && now < self.info.expires_at && now <= self.fresh_until
+    }
+}
+
+impl ConcurrentSigCache {
+    /// keep at most `capacity` sigs, nothing is kept if it is zero.
+    pub fn new(signer: TlsSigApiVer2, capacity: usize) -> Self {
+        ConcurrentSigCache {
+            signer,
+            capacity,
+            min_remaining: 0.2,
+            entries: DashMap::new(),
+            last_swept: AtomicU64::new(0),
+        }
+    }
+
+    /// reuse sigs only while at least `fraction` of their lifetime remains,
+    /// 0.2 by default. It is clamped between 0 and 1.
+    pub fn with_min_remaining(mut self, fraction: f64) -> Self {
+        self.min_remaining = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// the wrapped signer, e.g. for verifying. Sigs issued through it
+    /// directly are not cached.
+    pub fn signer(&self) -> &TlsSigApiVer2 {
+        &self.signer
+    }
+
+    /// the number of sigs kept, including expired ones not swept yet.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// drop every sig kept, e.g. after the key is rotated behind a key
+    /// provider.
+    pub fn clear(&self) {
+        self.entries.clear();
+    }
+
+    /// drop the sigs kept for `identifier`, of any expire and userbuf, e.g.
+    /// after it is banned.
+    pub fn invalidate(&self, identifier: &str) {
+        self.entries.retain(|key, _| key.0 != identifier);
+    }
+
+    /// generate user sign like [`TlsSigApiVer2::try_gen_sign`], or return the
+    /// one cached if it is still fresh.
+    ///
+    /// [`TlsSigApiVer2::try_gen_sign`]: struct.TlsSigApiVer2.html#method.try_gen_sign
+    pub fn try_gen_sign<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<UserSig> {
+        self.gen_sign_info(identifier, expire, userbuf)
+            .map(|info| info.sig)
+    }
+
+    /// generate user sign with its issuance metadata like
+    /// [`TlsSigApiVer2::gen_sign_info`], or return the one cached if it is
+    /// still fresh.
+    ///
+    /// [`TlsSigApiVer2::gen_sign_info`]: struct.TlsSigApiVer2.html#method.gen_sign_info
+    pub fn gen_sign_info<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<SigInfo> {
+        let expire = expire.expire_secs();
+        let key = cache_key(identifier, expire, userbuf);
+
+        let now = self.signer.clock.now();
+        let key_version = self.signer.key_version();
+        if let Some(entry) = self.entries.get(&key) {
+            if entry.is_fresh(now, key_version) {
+                return Ok(entry.info.clone());
+            }
+        }
+
+        // concurrent misses of the same key may both sign, the last one kept
+        let info = self.signer.sign_info(
+            identifier,
+            self.signer.curr_timestamp(),
+            expire,
+            userbuf.map(str::as_bytes),
+        )?;
+        self.insert(key, info.clone(), now, key_version);
+
+        Ok(info)
+    }
+
+    fn insert(&self, key: CacheKey, info: SigInfo, now: SystemTime, key_version: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            self.sweep(now, key_version);
+            if self.entries.len() >= self.capacity {
+                return;
+            }
+        }
+
+        let fresh_until = fresh_until(&info, self.min_remaining);
+        self.entries.insert(
+            key,
+            Entry {
+                info,
+                fresh_until,
+                key_version,
+            },
+        );
+    }
+
+    /// drop the expired entries and those of an old key, at most once a second so that a cache full
+    /// of fresh sigs is not scanned on every miss.
+    fn sweep(&self, now: SystemTime, key_version: u64) {
+        let second = now
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let last = self.last_swept.load(Ordering::Relaxed);
+        if last >= second
+            || self
+                .last_swept
+                .compare_exchange(last, second, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return;
+        }
+
+        self.entries
+            .retain(|_, entry| entry.is_fresh(now, key_version));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ConcurrentSigCache;
+    use crate::clock::ManualClock;
+    use crate::TlsSigApiVer2;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    const MOCK_KEY: &str = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    const EXPIRE: Duration = Duration::from_secs(1000);
+
+    fn cache(capacity: usize) -> (ConcurrentSigCache, ManualClock) {
+        let clock = ManualClock(Arc::new(AtomicU64::new(1569910200)));
+        let signer = TlsSigApiVer2::new(1400000000, MOCK_KEY).with_clock(clock.clone());
+
+        (ConcurrentSigCache::new(signer, capacity), clock)
+    }
+
+    #[test]
+    fn test_concurrent_sig_cache_ttl() {
+        let (cache, clock) = cache(10);
+        let sig = cache.try_gen_sign("0", EXPIRE, Some("abc")).unwrap();
+        let long = cache
+            .try_gen_sign("0", Duration::from_secs(2000), Some("abc"))
+            .unwrap();
+        assert_ne!(long, sig);
+
+        clock.0.fetch_add(800, Ordering::SeqCst);
+        assert_eq!(cache.try_gen_sign("0", EXPIRE, Some("abc")).unwrap(), sig);
+
+        // the TTL of each entry follows the lifetime of its sig
+        clock.0.fetch_add(1, Ordering::SeqCst);
+        let renewed = cache.try_gen_sign("0", EXPIRE, Some("abc")).unwrap();
+        assert_ne!(renewed, sig);
+        assert!(cache.signer().verify_sign("0", &renewed).is_ok());
+        assert_eq!(
+            cache
+                .try_gen_sign("0", Duration::from_secs(2000), Some("abc"))
+                .unwrap(),
+            long
+        );
+    }
+
+    #[test]
+    fn test_concurrent_sig_cache_capacity() {
+        let (cache, clock) = cache(2);
+        let first = cache.try_gen_sign("first", EXPIRE, None).unwrap();
+        cache.try_gen_sign("second", EXPIRE, None).unwrap();
+
+        // full of fresh sigs, so `third` is not kept
+        let third = cache.try_gen_sign("third", EXPIRE, None).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.try_gen_sign("first", EXPIRE, None).unwrap(), first);
+
+        // expired ones make room again
+        clock.0.fetch_add(801, Ordering::SeqCst);
+        let renewed = cache.try_gen_sign("third", EXPIRE, None).unwrap();
+        assert_ne!(renewed, third);
+        assert_eq!(cache.len(), 1);
+
+        let (disabled, _) = self::cache(0);
+        disabled.try_gen_sign("first", EXPIRE, None).unwrap();
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn test_concurrent_sig_cache_shared() {
+        let (cache, _) = cache(100);
+        let cache = Arc::new(cache);
+
+        let handlers: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                thread::spawn(move || {
+                    for identifier in 0..10 {
+                        let identifier = identifier.to_string();
+                        let sig = cache.try_gen_sign(&identifier, EXPIRE, None).unwrap();
+                        assert!(cache.signer().verify_sign(&identifier, &sig).is_ok());
+                    }
+                })
+            })
+            .collect();
+        for handler in handlers {
+            handler.join().unwrap();
+        }
+
+        assert_eq!(cache.len(), 10);
+    }
+
+    #[test]
+    fn test_concurrent_sig_cache_invalidation() {
+        let (cache, _) = cache(10);
+        let old = cache.try_gen_sign("0", EXPIRE, None).unwrap();
+        cache.try_gen_sign("1", EXPIRE, None).unwrap();
+
+        cache.signer().update_key("new key");
+        let new = cache.try_gen_sign("0", EXPIRE, None).unwrap();
+        assert_ne!(new, old);
+        assert!(cache.signer().verify_sign("0", &new).is_ok());
+
+        cache.invalidate("0");
+        assert_eq!(cache.len(), 1);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
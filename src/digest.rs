@@ -0,0 +1,85 @@
+use std::fmt;
+
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::b64;
+
+/// The raw HMAC-SHA256 digest carried by a user sign.
+///
+/// It is only compared in constant time, so that comparing a forged digest
+/// with the recomputed one does not leak how many leading bytes match.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{SigDigest, TlsSigApiVer2};
+/// use std::time::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = TlsSigApiVer2::new(0, mock_key);
+/// let sig = signer.gen_sign("10086", Duration::from_secs(7200), None);
+///
+/// let digest = sig.payload().unwrap().digest().unwrap();
+/// assert_eq!(digest.as_bytes().len(), 32);
+/// assert_ne!(digest, SigDigest::from(vec![0; 32]));
+/// ```
+#[derive(Clone)]
+pub struct SigDigest(Vec<u8>);
+
+impl SigDigest {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for SigDigest {
+    fn from(bytes: Vec<u8>) -> Self {
+        SigDigest(bytes)
+    }
+}
+
+impl ConstantTimeEq for SigDigest {
+    fn ct_eq(&self, other: &SigDigest) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+/// Compared in constant time.
+impl PartialEq for SigDigest {
+    fn eq(&self, other: &SigDigest) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SigDigest {}
+
+/// Compared in constant time.
+impl PartialEq<[u8]> for SigDigest {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.0.ct_eq(other).into()
+    }
+}
+
+/// Printed as base64, as it is in the sig.
+impl fmt::Debug for SigDigest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SigDigest({})", b64::encode(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SigDigest;
+
+    #[test]
+    fn test_sig_digest_eq() {
+        let digest = SigDigest::from(vec![1, 2, 3]);
+
+        assert_eq!(digest, SigDigest::from(vec![1, 2, 3]));
+        assert_ne!(digest, SigDigest::from(vec![1, 2, 4]));
+        // different length never matches
+        assert_ne!(digest, SigDigest::from(vec![1, 2]));
+        assert!(digest == [1, 2, 3][..]);
+        assert_eq!(format!("{:?}", digest), "SigDigest(AQID)");
+    }
+}
@@ -0,0 +1,106 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::TimErrorCode;
+
+/// Errors that can occur when generating a user sign.
+#[derive(Debug)]
+pub enum TlsSigError {
+    /// The secret key can not be used to initialize the HMAC.
+    InvalidKey,
+    /// The expire (in seconds) can not be used to produce a valid sig.
+    InvalidExpire(i64),
+    /// The identifier breaks Tencent's rules.
+    InvalidIdentifier(&'static str),
+    /// The sdkappid is zero or not a number.
+    InvalidSdkAppId,
+    /// The env var is not set or not valid unicode.
+    MissingEnv(String),
+    /// The key provider fails to load the key.
+    KeyUnavailable(String),
+    /// The MAC backend fails to compute the HMAC.
+    MacUnavailable(String),
+    /// The sig store fails to load or store a sig.
+    StoreUnavailable(String),
+    /// The Tencent IM REST API can not be reached or answers garbage.
+    RestUnavailable(String),
+    /// The Tencent IM REST API answers with an HTTP error status.
+    RestHttpStatus(u16),
+    /// The Tencent IM REST API answers a request with a failure.
+    RestFailed { code: TimErrorCode, info: String },
+    /// The key id is not in the key ring.
+    UnknownKeyId(String),
+    /// The active key of the key ring can not be retired.
+    RetireActiveKey(String),
+    /// Too many sigs are issued to the identifier, retry after the duration.
+    RateLimited(std::time::Duration),
+    /// The sig would be too long to be decoded, e.g. its userbuf is too
+    /// large, with the length in bytes of the sig or of its json.
+    SigTooLong(usize),
+    /// The sig payload fails to be serialized.
+    Serialization(serde_json::Error),
+    /// The sig can not be decoded into a valid payload.
+    MalformedSig(String),
+    /// The sig is not issued to the expected identifier.
+    IdentifierMismatch,
+    /// The sig is not issued by the expected sdkappid.
+    SdkAppIdMismatch,
+    /// The HMAC in the sig does not match the recomputed one.
+    SignatureMismatch,
+    /// The sig has been expired.
+    Expired,
+    /// The sig is issued in the future.
+    NotYetValid,
+}
+
+pub type Result<T> = std::result::Result<T, TlsSigError>;
+
+impl fmt::Display for TlsSigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TlsSigError::InvalidKey => write!(f, "invalid secret key"),
+            TlsSigError::InvalidExpire(expire) => write!(f, "invalid expire: {}s", expire),
+            TlsSigError::InvalidIdentifier(reason) => write!(f, "invalid identifier: {}", reason),
+            TlsSigError::InvalidSdkAppId => write!(f, "invalid sdkappid"),
+            TlsSigError::MissingEnv(name) => write!(f, "env var {} is not set", name),
+            TlsSigError::KeyUnavailable(reason) => write!(f, "key unavailable: {}", reason),
+            TlsSigError::MacUnavailable(reason) => write!(f, "MAC unavailable: {}", reason),
+            TlsSigError::StoreUnavailable(reason) => write!(f, "sig store unavailable: {}", reason),
+            TlsSigError::RestUnavailable(reason) => write!(f, "REST API unavailable: {}", reason),
+            TlsSigError::RestHttpStatus(status) => write!(f, "REST API answered {}", status),
+            TlsSigError::RestFailed { code, info } => {
+                write!(f, "REST API failed with {}: {}", code, info)
+            }
+            TlsSigError::UnknownKeyId(id) => write!(f, "unknown key id {}", id),
+            TlsSigError::RetireActiveKey(id) => write!(f, "can not retire active key {}", id),
+            TlsSigError::RateLimited(retry_after) => write!(
+                f,
+                "too many sigs issued, retry after {}s",
+                retry_after.as_secs()
+            ),
+            TlsSigError::SigTooLong(len) => write!(f, "sig of {} bytes too long", len),
+            TlsSigError::Serialization(e) => write!(f, "failed to serialize sig: {}", e),
+            TlsSigError::MalformedSig(reason) => write!(f, "malformed sig: {}", reason),
+            TlsSigError::IdentifierMismatch => write!(f, "identifier mismatch"),
+            TlsSigError::SdkAppIdMismatch => write!(f, "sdkappid mismatch"),
+            TlsSigError::SignatureMismatch => write!(f, "signature mismatch"),
+            TlsSigError::Expired => write!(f, "sig expired"),
+            TlsSigError::NotYetValid => write!(f, "sig not yet valid"),
+        }
+    }
+}
+
+impl Error for TlsSigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TlsSigError::Serialization(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for TlsSigError {
+    fn from(e: serde_json::Error) -> Self {
+        TlsSigError::Serialization(e)
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt;
+
+use crate::timing::rfc3339;
+use crate::{decode_sign, Result, SigPayload, TlsSigApiVer2, TlsSigError};
+
+/// A human-readable breakdown of a user sign, like the one given by
+/// Tencent's online UserSig debugging tool.
+///
+/// Its `Display` output lists every field, one per line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigExplanation {
+    pub payload: SigPayload,
+    /// Issue time in RFC3339.
+    pub issued_at: String,
+    /// Expiry time in RFC3339.
+    pub expires_at: String,
+    /// The userbuf decoded as UTF-8, if it is valid UTF-8.
+    pub userbuf_text: Option<String>,
+    /// Whether the HMAC matches the given key, `None` if no key is given.
+    pub hmac_matches: Option<bool>,
+}
+
+/// explain what a user sign carries, checking its HMAC against `key` when
+/// given. The expiry is **not** checked against current time.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "chrono")] {
+/// use tls_sig_api::{explain, TlsSigApiVer2};
+/// use chrono::{Duration, TimeZone, Utc};
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = TlsSigApiVer2::new(1400000000, mock_key);
+/// let issued_at = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+/// let sig = signer.gen_sign_at("10086", issued_at, Duration::hours(2), None).unwrap();
+///
+/// let explanation = explain(&sig, Some(mock_key)).unwrap();
+/// assert_eq!(explanation.issued_at, "2019-10-01T06:10:00Z");
+/// assert_eq!(explanation.expires_at, "2019-10-01T08:10:00Z");
+/// assert_eq!(explanation.hmac_matches, Some(true));
+/// println!("{}", explanation);
+/// # }
+/// ```
+pub fn explain(sig: &str, key: Option<&str>) -> Result<SigExplanation> {
+    let payload = decode_sign(sig)?;
+
+    let userbuf_text = payload
+        .userbuf_bytes()?
+        .and_then(|buf| String::from_utf8(buf).ok());
+
+    let hmac_matches = match key {
+        Some(key) => {
+            let signer = TlsSigApiVer2::new(payload.sdkappid, key);
+            match signer.check_signature(&payload) {
+                Ok(()) => Some(true),
+                Err(TlsSigError::SignatureMismatch) => Some(false),
+                Err(e) => return Err(e),
+            }
+        }
+        None => None,
+    };
+
+    Ok(SigExplanation {
+        issued_at: rfc3339(payload.time),
+        expires_at: rfc3339(payload.time.saturating_add(payload.expire)),
+        userbuf_text,
+        hmac_matches,
+        payload,
+    })
+}
+
+impl fmt::Display for SigExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let payload = &self.payload;
+
+        writeln!(f, "TLS.ver:        {}", payload.ver)?;
+        writeln!(f, "TLS.identifier: {}", payload.identifier)?;
+        writeln!(f, "TLS.sdkappid:   {}", payload.sdkappid)?;
+        writeln!(f, "TLS.time:       {} ({})", payload.time, self.issued_at)?;
+        writeln!(
+            f,
+            "TLS.expire:     {} (expires at {})",
+            payload.expire, self.expires_at
+        )?;
+        match (&payload.userbuf, &self.userbuf_text) {
+            (Some(buf), Some(text)) => writeln!(f, "TLS.userbuf:    {} ({:?})", buf, text)?,
+            (Some(buf), None) => writeln!(f, "TLS.userbuf:    {}", buf)?,
+            (None, _) => writeln!(f, "TLS.userbuf:    (none)")?,
+        }
+        writeln!(f, "TLS.sig:        {}", payload.sig)?;
+
+        let hmac = match self.hmac_matches {
+            Some(true) => "matches",
+            Some(false) => "does NOT match",
+            None => "not checked",
+        };
+        write!(f, "HMAC:           {}", hmac)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::explain;
+
+    const MOCK_KEY: &str = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    // url-safe base64 sig produced by the python version
+    const MOCK_SIG_WITH_BUF: &str = "eJw9zEELwiAcBfDv4jmGs1lu0GkRUd0chMeWbv2zDdEZg*i7J5a92-s9eC-UnHj2VBZViGQYLWIHqcYJOoic0El9MQYkqvIC--Jd1GzAquCUUvLXCYZoq7LMMUnqnbKt78KvOB-u6Rr6AG299PTq3YP3bOvWR0HZcOO6sbMf7c5TLLXUtdgXwDbo-QEmHTZF";
+
+    #[test]
+    fn test_explain() {
+        let explanation = explain(MOCK_SIG_WITH_BUF, Some(MOCK_KEY)).unwrap();
+
+        assert_eq!(explanation.issued_at, "2019-10-01T06:10:00Z");
+        assert_eq!(explanation.expires_at, "2020-03-29T06:10:00Z");
+        assert_eq!(explanation.userbuf_text.as_deref(), Some("abc"));
+        assert_eq!(explanation.hmac_matches, Some(true));
+        assert_eq!(
+            explanation.to_string(),
+            "TLS.ver:        2.0\n\
+             TLS.identifier: 0\n\
+             TLS.sdkappid:   1400000000\n\
+             TLS.time:       1569910200 (2019-10-01T06:10:00Z)\n\
+             TLS.expire:     15552000 (expires at 2020-03-29T06:10:00Z)\n\
+             TLS.userbuf:    YWJj (\"abc\")\n\
+             TLS.sig:        bC3u5cuslSg8Ds7KY58mhSkTrxunrFu50dkdkCYH4i8=\n\
+             HMAC:           matches"
+        );
+    }
+
+    #[test]
+    fn test_explain_with_wrong_key() {
+        let explanation = explain(MOCK_SIG_WITH_BUF, Some("another key")).unwrap();
+        assert_eq!(explanation.hmac_matches, Some(false));
+
+        let explanation = explain(MOCK_SIG_WITH_BUF, None).unwrap();
+        assert_eq!(explanation.hmac_matches, None);
+    }
+}
@@ -0,0 +1,261 @@
+//! Identities a sig is bound to, the user identifier and the sdkappid.
+
+use std::borrow::Borrow;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use crate::{Result, TlsSigError};
+
+/// The longest identifier accepted by Tencent, in bytes.
+pub const MAX_IDENTIFIER_LEN: usize = 32;
+
+/// check an identifier against Tencent's rules: it must be non-empty, no
+/// longer than [`MAX_IDENTIFIER_LEN`] bytes and free of control characters.
+///
+/// [`MAX_IDENTIFIER_LEN`]: constant.MAX_IDENTIFIER_LEN.html
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::validate_identifier;
+///
+/// assert!(validate_identifier("10086").is_ok());
+/// assert!(validate_identifier("").is_err());
+/// assert!(validate_identifier("line\nbreak").is_err());
+/// ```
+pub fn validate_identifier(identifier: &str) -> Result<()> {
+    if identifier.is_empty() {
+        return Err(TlsSigError::InvalidIdentifier("empty identifier"));
+    }
+    if identifier.len() > MAX_IDENTIFIER_LEN {
+        return Err(TlsSigError::InvalidIdentifier(
+            "identifier longer than 32 bytes",
+        ));
+    }
+    if identifier.chars().any(char::is_control) {
+        return Err(TlsSigError::InvalidIdentifier(
+            "identifier contains control characters",
+        ));
+    }
+
+    Ok(())
+}
+
+/// A user identifier that passed [`validate_identifier`].
+///
+/// It derefs to `str`, so it can be passed wherever an identifier is taken.
+///
+/// [`validate_identifier`]: fn.validate_identifier.html
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{Identifier, TlsSigApiVer2};
+/// use std::convert::TryFrom;
+/// use std::time::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = TlsSigApiVer2::new(0, mock_key);
+///
+/// let identifier = Identifier::try_from("10086").unwrap();
+/// let sig = signer.gen_sign(&identifier, Duration::from_secs(7200), None);
+/// assert!(signer.verify_sign(&identifier, &sig).is_ok());
+///
+/// assert!(Identifier::try_from("").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Identifier(String);
+
+impl Identifier {
+    /// validate and wrap an identifier.
+    pub fn new<S: Into<String>>(identifier: S) -> Result<Self> {
+        let identifier = identifier.into();
+        validate_identifier(&identifier)?;
+        Ok(Identifier(identifier))
+    }
+
+    /// wrap an identifier without validating it, for identifiers that are
+    /// known to be valid or rely on lax rules.
+    pub fn new_unchecked<S: Into<String>>(identifier: S) -> Self {
+        Identifier(identifier.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for Identifier {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Identifier {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Identifier {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<&str> for Identifier {
+    type Error = TlsSigError;
+
+    fn try_from(identifier: &str) -> Result<Self> {
+        Identifier::new(identifier)
+    }
+}
+
+impl TryFrom<String> for Identifier {
+    type Error = TlsSigError;
+
+    fn try_from(identifier: String) -> Result<Self> {
+        Identifier::new(identifier)
+    }
+}
+
+impl FromStr for Identifier {
+    type Err = TlsSigError;
+
+    fn from_str(identifier: &str) -> Result<Self> {
+        Identifier::new(identifier)
+    }
+}
+
+impl From<Identifier> for String {
+    fn from(identifier: Identifier) -> String {
+        identifier.0
+    }
+}
+
+/// The sdkappid of an application, taken by the constructors of
+/// [`TlsSigApiVer2`] so that it can not be swapped with other numbers.
+///
+/// [`new`] and parsing reject zero, which is never assigned by Tencent, while
+/// `From<u64>` wraps any number as is, so that plain sdkappids are still
+/// accepted wherever a `SdkAppId` is taken.
+///
+/// [`TlsSigApiVer2`]: struct.TlsSigApiVer2.html
+/// [`new`]: #method.new
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{SdkAppId, TlsSigApiVer2};
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let sdkappid: SdkAppId = "1400000000".parse().unwrap();
+/// let signer = TlsSigApiVer2::new(sdkappid, mock_key);
+/// assert_eq!(signer.appid(), 1400000000);
+///
+/// assert!("0".parse::<SdkAppId>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SdkAppId(u64);
+
+impl SdkAppId {
+    /// validate and wrap a sdkappid.
+    pub fn new(sdkappid: u64) -> Result<Self> {
+        if sdkappid == 0 {
+            return Err(TlsSigError::InvalidSdkAppId);
+        }
+        Ok(SdkAppId(sdkappid))
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for SdkAppId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u64> for SdkAppId {
+    fn from(sdkappid: u64) -> Self {
+        SdkAppId(sdkappid)
+    }
+}
+
+impl FromStr for SdkAppId {
+    type Err = TlsSigError;
+
+    fn from_str(sdkappid: &str) -> Result<Self> {
+        sdkappid
+            .trim()
+            .parse()
+            .map_err(|_| TlsSigError::InvalidSdkAppId)
+            .and_then(SdkAppId::new)
+    }
+}
+
+impl From<SdkAppId> for u64 {
+    fn from(sdkappid: SdkAppId) -> u64 {
+        sdkappid.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{validate_identifier, Identifier, SdkAppId};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_validate_identifier() {
+        assert!(validate_identifier("admin").is_ok());
+        assert!(validate_identifier("用户_01").is_ok());
+        assert!(validate_identifier(&"a".repeat(32)).is_ok());
+
+        assert!(validate_identifier("").is_err());
+        assert!(validate_identifier(&"a".repeat(33)).is_err());
+        // 11 chars but 33 bytes
+        assert!(validate_identifier(&"用".repeat(11)).is_err());
+        assert!(validate_identifier("tab\tin").is_err());
+        assert!(validate_identifier("nul\0").is_err());
+    }
+
+    #[test]
+    fn test_identifier() {
+        let identifier = Identifier::try_from("admin").unwrap();
+        assert_eq!(identifier.as_str(), "admin");
+        assert_eq!(&*identifier, "admin");
+        assert_eq!(String::from(identifier), "admin".to_string());
+
+        assert!(Identifier::try_from("a".repeat(33)).is_err());
+        assert_eq!(Identifier::new_unchecked("").as_str(), "");
+    }
+
+    #[test]
+    fn test_sdkappid() {
+        assert_eq!(SdkAppId::new(1400000000).unwrap().get(), 1400000000);
+        assert_eq!(
+            " 1400000000 ".parse::<SdkAppId>().unwrap().get(),
+            1400000000
+        );
+        assert!(SdkAppId::new(0).is_err());
+        assert_eq!(SdkAppId::from(0).get(), 0);
+        assert!("abc".parse::<SdkAppId>().is_err());
+        assert!("-1".parse::<SdkAppId>().is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use std::env;
+
+use crate::{Key, KeyProvider, Result, TlsSigError};
+
+/// The env var [`EnvKeyProvider::new`] reads the key from.
+///
+/// [`EnvKeyProvider::new`]: struct.EnvKeyProvider.html#method.new
+pub const DEFAULT_KEY_VAR: &str = "TLS_SIG_KEY";
+/// The env var [`TlsSigApiVer2::from_env`] reads the sdkappid from.
+///
+/// [`TlsSigApiVer2::from_env`]: struct.TlsSigApiVer2.html#method.from_env
+pub const DEFAULT_APPID_VAR: &str = "TLS_SIG_APPID";
+
+/// A key provider reading the key from an env var.
+///
+/// The var is read once when the provider is created.
+#[derive(Debug, Clone)]
+pub struct EnvKeyProvider {
+    key: Key,
+}
+
+impl EnvKeyProvider {
+    /// read the key from [`DEFAULT_KEY_VAR`].
+    ///
+    /// [`DEFAULT_KEY_VAR`]: constant.DEFAULT_KEY_VAR.html
+    pub fn new() -> Result<Self> {
+        Self::from_var(DEFAULT_KEY_VAR)
+    }
+
+    /// read the key from the env var `name`.
+    pub fn from_var(name: &str) -> Result<Self> {
+        Ok(EnvKeyProvider {
+            key: Key::new(read_var(name)?),
+        })
+    }
+}
+
+impl KeyProvider for EnvKeyProvider {
+    fn current_key(&self) -> Key {
+        self.key.clone()
+    }
+}
+
+pub(crate) fn read_var(name: &str) -> Result<String> {
+    env::var(name).map_err(|_| TlsSigError::MissingEnv(name.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::EnvKeyProvider;
+    use crate::{KeyProvider, TlsSigError};
+    use std::env;
+
+    #[test]
+    fn test_env_key_provider() {
+        env::set_var("TEST_ENV_KEY_PROVIDER_KEY", "secret");
+
+        let provider = EnvKeyProvider::from_var("TEST_ENV_KEY_PROVIDER_KEY").unwrap();
+        assert_eq!(provider.current_key().expose(), "secret");
+    }
+
+    #[test]
+    fn test_env_key_provider_missing_var() {
+        assert!(matches!(
+            EnvKeyProvider::from_var("TEST_ENV_KEY_PROVIDER_MISSING"),
+            Err(TlsSigError::MissingEnv(ref name)) if name == "TEST_ENV_KEY_PROVIDER_MISSING"
+        ));
+    }
+}
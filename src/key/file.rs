@@ -0,0 +1,189 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use log::*;
+
+use crate::{Key, KeyProvider, Result, TlsSigError};
+
+/// A key provider reading the key from a file, e.g. a mounted Kubernetes
+/// secret. Surrounding whitespace, like the trailing newline, is trimmed.
+///
+/// The file is read when the provider is created and on every [`reload`].
+/// With the `notify` feature, [`watch`] reloads the key whenever the file
+/// changes. Clones share the same key, so a clone can be handed to the
+/// signer while the original is kept for reloading.
+///
+/// [`reload`]: #method.reload
+/// [`watch`]: #method.watch
+///
+/// # Examples
+///
+/// ```no_run
+/// use tls_sig_api::{FileKeyProvider, TlsSigApiVer2};
+///
+/// let provider = FileKeyProvider::new("/etc/secrets/tls-sig-key").unwrap();
+/// let signer = TlsSigApiVer2::from_key_provider(1400000000, provider.clone());
+///
+/// // after the secret is rotated
+/// provider.reload().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileKeyProvider {
+    path: PathBuf,
+    key: Arc<ArcSwap<Key>>,
+}
+
+impl FileKeyProvider {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let key = read_key(&path)?;
+
+        Ok(FileKeyProvider {
+            path,
+            key: Arc::new(ArcSwap::from_pointee(key)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// read the file again, keeping the current key if it fails.
+    pub fn reload(&self) -> Result<()> {
+        let key = read_key(&self.path)?;
+        self.key.store(Arc::new(key));
+        info!("key reloaded from {}", self.path.display());
+
+        Ok(())
+    }
+
+    /// watch the file and reload the key whenever it changes, until the
+    /// returned watcher is dropped.
+    ///
+    /// The parent directory is watched rather than the file itself, so that
+    /// the atomic symlink swap Kubernetes does on secret rotation is caught.
+    #[cfg(feature = "notify")]
+    pub fn watch(&self) -> Result<FileKeyWatcher> {
+        use notify::{RecursiveMode, Watcher};
+
+        let provider = self.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) if event.kind.is_access() => {}
+                Ok(_) => {
+                    if let Err(e) = provider.reload() {
+                        warn!(
+                            "failed to reload key from {}: {}",
+                            provider.path.display(),
+                            e
+                        );
+                    }
+                }
+                Err(e) => warn!("error watching {}: {}", provider.path.display(), e),
+            })
+            .map_err(|e| TlsSigError::KeyUnavailable(e.to_string()))?;
+
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| TlsSigError::KeyUnavailable(e.to_string()))?;
+
+        Ok(FileKeyWatcher { _watcher: watcher })
+    }
+}
+
+impl KeyProvider for FileKeyProvider {
+    fn current_key(&self) -> Key {
+        Key::clone(&self.key.load())
+    }
+}
+
+/// Keeps reloading a [`FileKeyProvider`] until dropped.
+///
+/// [`FileKeyProvider`]: struct.FileKeyProvider.html
+#[cfg(feature = "notify")]
+pub struct FileKeyWatcher {
+    _watcher: notify::RecommendedWatcher,
+}
+
+fn read_key(path: &Path) -> Result<Key> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        TlsSigError::KeyUnavailable(format!("failed to read {}: {}", path.display(), e))
+    })?;
+
+    let secret = content.trim();
+    if secret.is_empty() {
+        return Err(TlsSigError::KeyUnavailable(format!(
+            "{} is empty",
+            path.display()
+        )));
+    }
+
+    Ok(Key::new(secret))
+}
+
+#[cfg(test)]
+mod test {
+    use super::FileKeyProvider;
+    use crate::{KeyProvider, TlsSigError};
+    use std::fs;
+
+    fn temp_key_file(name: &str, content: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("tls_sig_api_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("key");
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_file_key_provider() {
+        let path = temp_key_file("file_key_provider", "old-secret\n");
+        let provider = FileKeyProvider::new(&path).unwrap();
+        let shared = provider.clone();
+        assert_eq!(shared.current_key().expose(), "old-secret");
+
+        fs::write(&path, "new-secret\n").unwrap();
+        provider.reload().unwrap();
+        assert_eq!(shared.current_key().expose(), "new-secret");
+
+        // a failed reload keeps the current key
+        fs::write(&path, "\n").unwrap();
+        assert!(matches!(
+            provider.reload(),
+            Err(TlsSigError::KeyUnavailable(_))
+        ));
+        assert_eq!(shared.current_key().expose(), "new-secret");
+    }
+
+    #[test]
+    fn test_file_key_provider_missing_file() {
+        assert!(matches!(
+            FileKeyProvider::new("/nonexistent/tls_sig_api/key"),
+            Err(TlsSigError::KeyUnavailable(_))
+        ));
+    }
+
+    #[cfg(feature = "notify")]
+    #[test]
+    fn test_file_key_watcher() {
+        use std::time::{Duration, Instant};
+
+        let path = temp_key_file("file_key_watcher", "old-secret");
+        let provider = FileKeyProvider::new(&path).unwrap();
+        let _watcher = provider.watch().unwrap();
+
+        fs::write(&path, "new-secret").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while provider.current_key().expose() != "new-secret" && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(provider.current_key().expose(), "new-secret");
+    }
+}
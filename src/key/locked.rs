@@ -0,0 +1,90 @@
+use std::ptr::{self, NonNull};
+
+use log::*;
+
+/// A string kept in memory locked out of swap and core dumps, in pages of
+/// its own between guard pages. It is wiped and unlocked when dropped.
+///
+/// Locking fails if it exceeds `RLIMIT_MEMLOCK`, the string is then kept in
+/// the guarded pages unlocked, see [`is_locked`].
+///
+/// [`is_locked`]: #method.is_locked
+pub(crate) struct LockedStr {
+    buf: Option<NonNull<[u8]>>,
+    locked: bool,
+}
+
+// the buffer is owned and never mutated until dropped
+unsafe impl Send for LockedStr {}
+unsafe impl Sync for LockedStr {}
+
+impl LockedStr {
+    pub(crate) fn new(secret: &str) -> Self {
+        if secret.is_empty() {
+            return LockedStr {
+                buf: None,
+                locked: true,
+            };
+        }
+
+        let len = secret.len();
+        // safety: the buffer is freed only once, when dropped
+        let buf = unsafe { memsec::malloc_sized(len) }.expect("failed to allocate key memory");
+        let start = buf.as_ptr() as *mut u8;
+        // safety: the buffer has exactly `len` bytes
+        let locked = unsafe {
+            ptr::copy_nonoverlapping(secret.as_ptr(), start, len);
+            // the allocation locks its pages too, but ignores failures
+            memsec::mlock(start, len)
+        };
+        if !locked {
+            warn!("failed to lock the key in memory, it may be swapped out, try raising RLIMIT_MEMLOCK");
+        }
+
+        LockedStr {
+            buf: Some(buf),
+            locked,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        match self.buf {
+            // safety: the buffer is a copy of a `str`, alive as long as self
+            Some(buf) => unsafe { std::str::from_utf8_unchecked(buf.as_ref()) },
+            None => "",
+        }
+    }
+
+    pub(crate) fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl Drop for LockedStr {
+    fn drop(&mut self) {
+        if let Some(buf) = self.buf.take() {
+            // safety: allocated by `memsec::malloc_sized`, which wipes and
+            // unlocks it when freeing
+            unsafe { memsec::free(buf) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LockedStr;
+
+    #[test]
+    fn test_locked_str() {
+        let secret =
+            LockedStr::new("5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e");
+        assert_eq!(
+            secret.as_str(),
+            "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e"
+        );
+
+        let empty = LockedStr::new("");
+        assert_eq!(empty.as_str(), "");
+        assert!(empty.is_locked());
+    }
+}
@@ -0,0 +1,261 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+use subtle::ConstantTimeEq;
+
+mod env;
+mod file;
+#[cfg(feature = "mlock")]
+mod locked;
+mod refresher;
+mod ring;
+mod rotation;
+#[cfg(feature = "tencent-ssm")]
+mod ssm;
+#[cfg(feature = "vault")]
+mod vault;
+
+pub(crate) use self::env::read_var;
+pub use self::env::{EnvKeyProvider, DEFAULT_APPID_VAR, DEFAULT_KEY_VAR};
+pub use self::file::FileKeyProvider;
+#[cfg(feature = "notify")]
+pub use self::file::FileKeyWatcher;
+pub use self::refresher::KeyRefresher;
+pub use self::ring::KeyRing;
+pub use self::rotation::{KeyRotation, RotationEvent, RotationSchedule};
+#[cfg(feature = "tencent-ssm")]
+pub use self::ssm::{TencentSsmConfig, TencentSsmKeyProvider};
+#[cfg(feature = "vault")]
+pub use self::vault::{VaultConfig, VaultKeyProvider};
+
+/// The secret key of an application, as shown in the Tencent console.
+///
+/// Its `Debug` output never reveals the secret. With the `zeroize` feature,
+/// the secret is wiped from memory when the key is dropped, e.g. when
+/// [`update_key`] replaces it.
+///
+/// [`update_key`]: struct.TlsSigApiVer2.html#method.update_key
+///
+/// With the `secrecy` feature, the secret is kept in a
+/// [`secrecy::SecretString`], and the key implements [`ExposeSecret`].
+///
+/// [`secrecy::SecretString`]: https://docs.rs/secrecy/0.10/secrecy/type.SecretString.html
+/// [`ExposeSecret`]: https://docs.rs/secrecy/0.10/secrecy/trait.ExposeSecret.html
+///
+/// With the `mlock` feature, the secret is kept in memory locked out of swap
+/// and core dumps, shared by clones of the key and wiped when the last one is
+/// dropped. It takes precedence over `secrecy` for storage.
+#[derive(Clone)]
+pub struct Key {
+    #[cfg(not(any(feature = "secrecy", feature = "mlock")))]
+    secret: String,
+    #[cfg(all(feature = "secrecy", not(feature = "mlock")))]
+    secret: secrecy::SecretString,
+    #[cfg(feature = "mlock")]
+    secret: std::sync::Arc<locked::LockedStr>,
+}
+
+impl Key {
+    #[cfg(not(feature = "mlock"))]
+    #[cfg_attr(not(feature = "secrecy"), allow(clippy::useless_conversion))]
+    pub fn new<S: Into<String>>(secret: S) -> Self {
+        Key {
+            secret: secret.into().into(),
+        }
+    }
+
+    #[cfg(feature = "mlock")]
+    pub fn new<S: Into<String>>(secret: S) -> Self {
+        let mut secret = secret.into();
+        let key = Key {
+            secret: std::sync::Arc::new(locked::LockedStr::new(&secret)),
+        };
+        zeroize::Zeroize::zeroize(&mut secret);
+
+        key
+    }
+
+    /// The secret itself, only expose it where the HMAC is computed.
+    #[cfg(not(any(feature = "secrecy", feature = "mlock")))]
+    pub fn expose(&self) -> &str {
+        &self.secret
+    }
+
+    /// The secret itself, only expose it where the HMAC is computed.
+    #[cfg(all(feature = "secrecy", not(feature = "mlock")))]
+    pub fn expose(&self) -> &str {
+        secrecy::ExposeSecret::expose_secret(&self.secret)
+    }
+
+    /// The secret itself, only expose it where the HMAC is computed.
+    #[cfg(feature = "mlock")]
+    pub fn expose(&self) -> &str {
+        self.secret.as_str()
+    }
+
+    /// whether the secret is locked in memory. Locking fails if the process
+    /// exceeds its `RLIMIT_MEMLOCK`, which is logged as a warning.
+    #[cfg(feature = "mlock")]
+    pub fn is_locked(&self) -> bool {
+        self.secret.is_locked()
+    }
+}
+
+/// Compared in constant time.
+impl PartialEq for Key {
+    fn eq(&self, other: &Key) -> bool {
+        self.expose()
+            .as_bytes()
+            .ct_eq(other.expose().as_bytes())
+            .into()
+    }
+}
+
+impl Eq for Key {}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Key(<redacted>)")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Key {
+    fn zeroize(&mut self) {
+        #[cfg(not(any(feature = "secrecy", feature = "mlock")))]
+        self.secret.zeroize();
+        // the secret box wipes the secret when dropped
+        #[cfg(all(feature = "secrecy", not(feature = "mlock")))]
+        drop(std::mem::take(&mut self.secret));
+        // the locked secret is wiped when its last clone is dropped
+        #[cfg(feature = "mlock")]
+        {
+            self.secret = std::sync::Arc::new(locked::LockedStr::new(""));
+        }
+    }
+}
+
+// the locked secret wipes itself
+#[cfg(all(feature = "zeroize", not(feature = "mlock")))]
+impl Drop for Key {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Key {}
+
+#[cfg(feature = "secrecy")]
+impl secrecy::ExposeSecret<str> for Key {
+    fn expose_secret(&self) -> &str {
+        self.expose()
+    }
+}
+
+#[cfg(all(feature = "secrecy", not(feature = "mlock")))]
+impl From<secrecy::SecretString> for Key {
+    fn from(secret: secrecy::SecretString) -> Self {
+        Key { secret }
+    }
+}
+
+#[cfg(all(feature = "secrecy", feature = "mlock"))]
+impl From<secrecy::SecretString> for Key {
+    fn from(secret: secrecy::SecretString) -> Self {
+        Key::new(secrecy::ExposeSecret::expose_secret(&secret))
+    }
+}
+
+impl From<&str> for Key {
+    fn from(secret: &str) -> Self {
+        Key::new(secret)
+    }
+}
+
+impl From<String> for Key {
+    fn from(secret: String) -> Self {
+        Key::new(secret)
+    }
+}
+
+/// Source of the secret key used by the signer, so that the key can be kept
+/// in env, files, or secret managers instead of the signer itself.
+///
+/// The key is fetched on every signing and verifying, so implementations
+/// should cache it rather than fetching it remotely each time.
+pub trait KeyProvider: Send + Sync {
+    fn current_key(&self) -> Key;
+
+    /// the async variant of [`current_key`], used by [`HmacBackend`] when
+    /// signing asynchronously. Providers fetching the key remotely on a miss
+    /// should override it to avoid blocking the executor, the default one
+    /// simply calls [`current_key`].
+    ///
+    /// [`current_key`]: #tymethod.current_key
+    /// [`HmacBackend`]: struct.HmacBackend.html
+    fn current_key_async(&self) -> KeyFuture<'_> {
+        let key = self.current_key();
+        Box::pin(async move { key })
+    }
+}
+
+/// The future returned by [`KeyProvider::current_key_async`].
+///
+/// [`KeyProvider::current_key_async`]: trait.KeyProvider.html#method.current_key_async
+pub type KeyFuture<'a> = Pin<Box<dyn Future<Output = Key> + Send + 'a>>;
+
+/// A key never changes by itself.
+impl KeyProvider for Key {
+    fn current_key(&self) -> Key {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Key, KeyProvider};
+
+    #[test]
+    fn test_key_debug_redacted() {
+        let key = Key::new("5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e");
+        assert_eq!(format!("{:?}", key), "Key(<redacted>)");
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_key_zeroize() {
+        use zeroize::Zeroize;
+
+        let mut key = Key::new("secret");
+        key.zeroize();
+        assert_eq!(key.expose(), "");
+    }
+
+    #[cfg(feature = "secrecy")]
+    #[test]
+    fn test_key_from_secret_string() {
+        use secrecy::{ExposeSecret, SecretString};
+
+        let key = Key::from(SecretString::from("secret"));
+        assert_eq!(key.expose_secret(), "secret");
+        assert_eq!(key, Key::new("secret"));
+    }
+
+    #[cfg(feature = "mlock")]
+    #[test]
+    fn test_key_mlock() {
+        let key = Key::new("secret");
+        let cloned = key.clone();
+        drop(key);
+        // clones share the locked secret
+        assert_eq!(cloned.expose(), "secret");
+    }
+
+    #[test]
+    fn test_static_key_provider() {
+        let key = Key::from("secret");
+        assert_eq!(key.current_key().expose(), "secret");
+    }
+}
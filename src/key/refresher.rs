@@ -0,0 +1,70 @@
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// the shortest interval between ticks, so that a zero interval, e.g. from a
+/// zero TTL, does not keep the thread spinning.
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Keeps a background refresh, like [`VaultKeyProvider::spawn_refresher`] or
+/// [`KeyRotation::spawn`], running until dropped.
+///
+/// [`VaultKeyProvider::spawn_refresher`]: struct.VaultKeyProvider.html#method.spawn_refresher
+/// [`KeyRotation::spawn`]: struct.KeyRotation.html#method.spawn
+#[derive(Debug)]
+pub struct KeyRefresher {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl KeyRefresher {
+    /// run `tick` every `interval`, at least a second, in a background
+    /// thread.
+    pub(crate) fn spawn<F: FnMut() + Send + 'static>(interval: Duration, mut tick: F) -> Self {
+        let interval = interval.max(MIN_INTERVAL);
+        let (stop, ticks) = mpsc::channel::<()>();
+
+        let handle = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = ticks.recv_timeout(interval) {
+                tick();
+            }
+        });
+
+        KeyRefresher {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for KeyRefresher {
+    fn drop(&mut self) {
+        // closing the channel wakes the thread up
+        self.stop.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::KeyRefresher;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_zero_interval_does_not_spin() {
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&ticks);
+        let refresher = KeyRefresher::spawn(Duration::from_secs(0), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        thread::sleep(Duration::from_millis(100));
+        drop(refresher);
+        assert_eq!(ticks.load(Ordering::SeqCst), 0);
+    }
+}
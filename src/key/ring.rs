@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use log::*;
+
+use crate::mac::VerifyFuture;
+use crate::{HmacBackend, Key, KeyProvider, MacBackend, Result, TlsSigError};
+
+/// A set of keys by key id, with one active key for signing and all of them
+/// accepted when verifying.
+///
+/// Sigs do not carry the key id, so verifying tries every key, in constant
+/// time. To rotate, [`insert`] the new key, [`promote`] it once it is
+/// registered in the Tencent console, and [`retire`] the old one once the
+/// sigs it signed have expired.
+///
+/// Clones share the same keys, so the ring can be given to the signer and
+/// still rotated afterwards.
+///
+/// [`insert`]: #method.insert
+/// [`promote`]: #method.promote
+/// [`retire`]: #method.retire
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{KeyRing, TlsSigApiVer2};
+/// use std::time::Duration;
+///
+/// let ring = KeyRing::new("2019-10", "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e");
+/// let signer = TlsSigApiVer2::from_mac_backend(0, ring.clone());
+/// let old_sig = signer.gen_sign("10086", Duration::from_secs(7200), None);
+///
+/// ring.insert("2020-04", "0f4c78e5bb2c8ab578ba8a8617a95e0ff0d4d8f1e5c9ebb1a73d0a51d1f6b4ea");
+/// ring.promote("2020-04").unwrap();
+/// assert_eq!(ring.active_id(), "2020-04");
+///
+/// // sigs by the old key are still accepted until it is retired
+/// assert!(signer.verify_sign("10086", &old_sig).is_ok());
+/// ring.retire("2019-10").unwrap();
+/// assert!(signer.verify_sign("10086", &old_sig).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeyRing {
+    inner: Arc<RwLock<Ring>>,
+}
+
+#[derive(Debug)]
+struct Ring {
+    active: String,
+    keys: BTreeMap<String, Key>,
+}
+
+impl KeyRing {
+    /// create a ring with `key` as the active key.
+    pub fn new<K: Into<Key>>(id: &str, key: K) -> Self {
+        let mut keys = BTreeMap::new();
+        keys.insert(id.to_string(), key.into());
+
+        KeyRing {
+            inner: Arc::new(RwLock::new(Ring {
+                active: id.to_string(),
+                keys,
+            })),
+        }
+    }
+
+    /// add a key for verifying, replacing the key of the same id if any.
+    pub fn insert<K: Into<Key>>(&self, id: &str, key: K) {
+        self.write().keys.insert(id.to_string(), key.into());
+    }
+
+    /// make the key of `id` the one used for signing.
+    pub fn promote(&self, id: &str) -> Result<()> {
+        let mut ring = self.write();
+        if !ring.keys.contains_key(id) {
+            return Err(TlsSigError::UnknownKeyId(id.to_string()));
+        }
+        ring.active = id.to_string();
+        info!("key {} promoted to active", id);
+
+        Ok(())
+    }
+
+    /// remove the key of `id`, so that sigs signed by it are no longer
+    /// accepted. The active key can not be retired, promote another one first.
+    pub fn retire(&self, id: &str) -> Result<()> {
+        let mut ring = self.write();
+        if ring.active == id {
+            return Err(TlsSigError::RetireActiveKey(id.to_string()));
+        }
+        if ring.keys.remove(id).is_none() {
+            return Err(TlsSigError::UnknownKeyId(id.to_string()));
+        }
+        info!("key {} retired", id);
+
+        Ok(())
+    }
+
+    pub fn active_id(&self) -> String {
+        self.read().active.clone()
+    }
+
+    /// ids of all keys, in order.
+    pub fn ids(&self) -> Vec<String> {
+        self.read().keys.keys().cloned().collect()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.read().keys.contains_key(id)
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Ring> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Ring> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The active key.
+impl KeyProvider for KeyRing {
+    fn current_key(&self) -> Key {
+        let ring = self.read();
+        ring.keys[&ring.active].clone()
+    }
+}
+
+/// Signs with the active key and verifies with all keys.
+impl MacBackend for KeyRing {
+    fn hmac_sha256(&self, content: &[u8]) -> Result<Vec<u8>> {
+        HmacBackend::from_key_provider(self.current_key()).hmac_sha256(content)
+    }
+
+    fn verify_hmac_sha256(&self, content: &[u8], digest: &[u8]) -> Result<()> {
+        let keys: Vec<Key> = self.read().keys.values().cloned().collect();
+
+        // no short-circuit, every key is tried
+        let mut matched = false;
+        for key in keys {
+            match HmacBackend::from_key_provider(key).verify_hmac_sha256(content, digest) {
+                Ok(()) => matched = true,
+                Err(TlsSigError::SignatureMismatch) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        if matched {
+            Ok(())
+        } else {
+            Err(TlsSigError::SignatureMismatch)
+        }
+    }
+
+    fn verify_hmac_sha256_async<'a>(
+        &'a self,
+        content: &'a [u8],
+        digest: &'a [u8],
+    ) -> VerifyFuture<'a> {
+        let result = self.verify_hmac_sha256(content, digest);
+        Box::pin(async move { result })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::KeyRing;
+    use crate::{KeyProvider, MacBackend, TlsSigError};
+
+    #[test]
+    fn test_key_ring_rotation() {
+        let ring = KeyRing::new("old", "old-secret");
+        ring.insert("new", "new-secret");
+        assert_eq!(ring.ids(), vec!["new", "old"]);
+        assert_eq!(ring.current_key().expose(), "old-secret");
+
+        let old_digest = ring.hmac_sha256(b"content").unwrap();
+        ring.promote("new").unwrap();
+        assert_eq!(ring.current_key().expose(), "new-secret");
+        assert_ne!(ring.hmac_sha256(b"content").unwrap(), old_digest);
+        assert!(ring.verify_hmac_sha256(b"content", &old_digest).is_ok());
+
+        ring.retire("old").unwrap();
+        assert!(!ring.contains("old"));
+        assert!(matches!(
+            ring.verify_hmac_sha256(b"content", &old_digest),
+            Err(TlsSigError::SignatureMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_key_ring_errors() {
+        let ring = KeyRing::new("active", "secret");
+
+        assert!(matches!(
+            ring.promote("missing"),
+            Err(TlsSigError::UnknownKeyId(_))
+        ));
+        assert!(matches!(
+            ring.retire("missing"),
+            Err(TlsSigError::UnknownKeyId(_))
+        ));
+        assert!(matches!(
+            ring.retire("active"),
+            Err(TlsSigError::RetireActiveKey(_))
+        ));
+        assert_eq!(ring.active_id(), "active");
+    }
+}
@@ -0,0 +1,292 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use log::*;
+
+use crate::timing::UnixTime;
+use crate::{ExpireDuration, KeyProvider, KeyRefresher, KeyRing};
+
+/// When [`KeyRotation`] rotates.
+///
+/// [`KeyRotation`]: struct.KeyRotation.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RotationSchedule {
+    /// Rotate every period, starting one period after the rotation is created.
+    Every(Duration),
+    /// Rotate at each of the given times.
+    At(Vec<SystemTime>),
+}
+
+/// What [`KeyRotation`] did on a tick, passed to the listener set by
+/// [`on_event`].
+///
+/// [`KeyRotation`]: struct.KeyRotation.html
+/// [`on_event`]: struct.KeyRotation.html#method.on_event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RotationEvent {
+    /// The key from the provider is promoted to active as `id`, and the
+    /// `previous` one is kept for verifying until its grace period ends.
+    Rotated { id: String, previous: String },
+    /// A rotation is due, but the provider still returns the active key.
+    Unchanged,
+    /// The key of `id` is retired after its grace period.
+    Retired { id: String },
+}
+
+/// Rotates the active key of a [`KeyRing`] on a schedule.
+///
+/// On every scheduled time, the key from the provider is inserted into the
+/// ring and promoted to active. The previous key stays in the ring for
+/// `grace`, so sigs it signed are still accepted, then it is retired. The
+/// grace period is best set to the longest expire of the sigs issued.
+///
+/// Call [`tick`] periodically, or [`spawn`] a thread doing so.
+///
+/// [`KeyRing`]: struct.KeyRing.html
+/// [`tick`]: #method.tick
+/// [`spawn`]: #method.spawn
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{
+///     FileKeyProvider, KeyProvider, KeyRing, KeyRotation, RotationEvent, RotationSchedule, TlsSigApiVer2,
+/// };
+/// use std::time::Duration;
+///
+/// # let dir = std::env::temp_dir().join("tls_sig_api_rotation_doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # let path = dir.join("key");
+/// # std::fs::write(&path, "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e").unwrap();
+/// let provider = FileKeyProvider::new(&path).unwrap();
+/// let ring = KeyRing::new("initial", provider.current_key());
+/// let signer = TlsSigApiVer2::from_mac_backend(1400000000, ring.clone());
+///
+/// let rotation = KeyRotation::new(
+///     ring,
+///     provider,
+///     RotationSchedule::Every(Duration::from_secs(30 * 24 * 3600)),
+///     Duration::from_secs(7 * 24 * 3600),
+/// )
+/// .on_event(|event: &RotationEvent| println!("key rotation: {:?}", event));
+///
+/// let _handle = rotation.spawn(Duration::from_secs(60));
+/// ```
+pub struct KeyRotation<P> {
+    ring: KeyRing,
+    provider: P,
+    grace: i64,
+    listener: Option<Box<Listener>>,
+    state: Mutex<State>,
+}
+
+type Listener = dyn Fn(&RotationEvent) + Send + Sync;
+
+struct State {
+    schedule: RotationSchedule,
+    /// `None` until the first tick, then `Some(None)` if nothing is left to
+    /// rotate.
+    next: Option<Option<i64>>,
+    retirements: Vec<(i64, String)>,
+}
+
+impl<P: KeyProvider + 'static> KeyRotation<P> {
+    /// rotate keys of `ring` to the key of `provider` on `schedule`, retiring
+    /// previous keys after `grace`. The schedule starts on the first tick,
+    /// times already passed by then are skipped.
+    pub fn new(ring: KeyRing, provider: P, schedule: RotationSchedule, grace: Duration) -> Self {
+        KeyRotation {
+            ring,
+            provider,
+            grace: grace.expire_secs(),
+            listener: None,
+            state: Mutex::new(State {
+                schedule,
+                next: None,
+                retirements: Vec::new(),
+            }),
+        }
+    }
+
+    /// call `listener` with every event, e.g. to log it.
+    pub fn on_event<F: Fn(&RotationEvent) + Send + Sync + 'static>(mut self, listener: F) -> Self {
+        self.listener = Some(Box::new(listener));
+        self
+    }
+
+    /// the ring being rotated.
+    pub fn ring(&self) -> &KeyRing {
+        &self.ring
+    }
+
+    /// rotate and retire keys that are due at `now`.
+    pub fn tick<T: UnixTime>(&self, now: T) -> Vec<RotationEvent> {
+        let now = now.unix_timestamp();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut events = Vec::new();
+
+        let next = match state.next {
+            Some(next) => next,
+            None => {
+                let next = next_rotation(&state.schedule, now - 1);
+                state.next = Some(next);
+                next
+            }
+        };
+
+        if next.is_some_and(|next| next <= now) {
+            let key = self.provider.current_key();
+            if key == self.ring.current_key() {
+                events.push(RotationEvent::Unchanged);
+            } else {
+                let previous = self.ring.active_id();
+                let id = unique_id(&self.ring, &format!("rotated-{}", now));
+                self.ring.insert(&id, key);
+                self.ring
+                    .promote(&id)
+                    .expect("the key is inserted right before");
+
+                state
+                    .retirements
+                    .push((now.saturating_add(self.grace), previous.clone()));
+                events.push(RotationEvent::Rotated { id, previous });
+            }
+            state.next = Some(next_rotation(&state.schedule, now));
+        }
+
+        let (due, pending) = state
+            .retirements
+            .drain(..)
+            .partition::<Vec<_>, _>(|(at, _)| *at <= now);
+        state.retirements = pending;
+        for (_, id) in due {
+            match self.ring.retire(&id) {
+                Ok(()) => events.push(RotationEvent::Retired { id }),
+                // promoted again or retired by hand in the meantime
+                Err(e) => debug!("skip retiring key {}: {}", id, e),
+            }
+        }
+
+        if let Some(listener) = &self.listener {
+            events.iter().for_each(listener);
+        }
+
+        events
+    }
+
+    /// [`tick`] every `interval`, at least a second, in a background thread,
+    /// until the returned handle is dropped.
+    ///
+    /// [`tick`]: #method.tick
+    pub fn spawn(self, interval: Duration) -> KeyRefresher {
+        let rotation = Arc::new(self);
+        KeyRefresher::spawn(interval, move || {
+            rotation.tick(SystemTime::now());
+        })
+    }
+}
+
+fn next_rotation(schedule: &RotationSchedule, now: i64) -> Option<i64> {
+    match schedule {
+        RotationSchedule::Every(period) => Some(now.saturating_add(period.expire_secs().max(1))),
+        RotationSchedule::At(times) => times
+            .iter()
+            .map(UnixTime::unix_timestamp)
+            .filter(|at| *at > now)
+            .min(),
+    }
+}
+
+/// `base`, suffixed if the ring already has it.
+fn unique_id(ring: &KeyRing, base: &str) -> String {
+    let mut id = base.to_string();
+    let mut n = 1;
+    while ring.contains(&id) {
+        n += 1;
+        id = format!("{}-{}", base, n);
+    }
+    id
+}
+
+#[cfg(test)]
+mod test {
+    use super::{KeyRotation, RotationEvent, RotationSchedule};
+    use crate::{Key, KeyProvider, KeyRing};
+    use arc_swap::ArcSwap;
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    /// a provider whose key is set by the test.
+    #[derive(Clone)]
+    struct MockProvider(Arc<ArcSwap<Key>>);
+
+    impl KeyProvider for MockProvider {
+        fn current_key(&self) -> Key {
+            Key::clone(&self.0.load())
+        }
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let provider = MockProvider(Arc::new(ArcSwap::from_pointee(Key::new("first"))));
+        let ring = KeyRing::new("first", "first");
+        let rotation = KeyRotation::new(
+            ring.clone(),
+            provider.clone(),
+            RotationSchedule::At(vec![at(1000), at(2000)]),
+            Duration::from_secs(300),
+        );
+        assert!(rotation.tick(at(999)).is_empty());
+        assert_eq!(rotation.tick(at(1000)), vec![RotationEvent::Unchanged]);
+
+        provider.0.store(Arc::new(Key::new("second")));
+        assert!(rotation.tick(at(1999)).is_empty());
+        assert_eq!(
+            rotation.tick(at(2000)),
+            vec![RotationEvent::Rotated {
+                id: "rotated-2000".to_string(),
+                previous: "first".to_string(),
+            }]
+        );
+        assert_eq!(ring.current_key().expose(), "second");
+        assert!(ring.contains("first"));
+
+        assert!(rotation.tick(at(2299)).is_empty());
+        assert_eq!(
+            rotation.tick(at(2300)),
+            vec![RotationEvent::Retired {
+                id: "first".to_string()
+            }]
+        );
+        assert_eq!(ring.ids(), vec!["rotated-2000"]);
+    }
+
+    #[test]
+    fn test_key_rotation_listener() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let provider = MockProvider(Arc::new(ArcSwap::from_pointee(Key::new("second"))));
+        let count = Arc::new(AtomicUsize::new(0));
+        let listened = Arc::clone(&count);
+        let rotation = KeyRotation::new(
+            KeyRing::new("first", "first"),
+            provider,
+            RotationSchedule::Every(Duration::from_secs(60)),
+            Duration::from_secs(0),
+        )
+        .on_event(move |_: &RotationEvent| {
+            listened.fetch_add(1, Ordering::SeqCst);
+        });
+
+        assert!(rotation.tick(at(1000)).is_empty());
+        // rotated and retired at once without grace period
+        let events = rotation.tick(at(1060));
+        assert_eq!(events.len(), 2);
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(rotation.ring().current_key().expose(), "second");
+    }
+}
@@ -0,0 +1,499 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use arc_swap::ArcSwap;
+use log::*;
+use serde_json::{json, Value};
+
+use crate::cloudsign::{hex, hmac, sha256_hex};
+use crate::key::{read_var, KeyRefresher};
+use crate::timing::{rfc3339, UnixTime};
+use crate::{Key, KeyProvider, Result, TlsSigError};
+
+const SERVICE: &str = "ssm";
+const ACTION: &str = "GetSecretValue";
+const VERSION: &str = "2019-09-23";
+const CONTENT_TYPE: &str = "application/json; charset=utf-8";
+
+/// Where and how to read the key from Tencent Cloud Secrets Manager.
+///
+/// The API credentials only need the `ssm:GetSecretValue` permission on the
+/// secret.
+#[derive(Clone)]
+pub struct TencentSsmConfig {
+    region: String,
+    secret_id: String,
+    secret_key: String,
+    token: Option<String>,
+    secret_name: String,
+    version_id: Option<String>,
+    field: Option<String>,
+    endpoint: String,
+    ttl: Duration,
+    timeout: Duration,
+}
+
+impl TencentSsmConfig {
+    pub fn new(region: &str, secret_id: &str, secret_key: &str, secret_name: &str) -> Self {
+        TencentSsmConfig {
+            region: region.to_string(),
+            secret_id: secret_id.to_string(),
+            secret_key: secret_key.to_string(),
+            token: None,
+            secret_name: secret_name.to_string(),
+            version_id: None,
+            field: None,
+            endpoint: "https://ssm.tencentcloudapi.com/".to_string(),
+            ttl: Duration::from_secs(300),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// read the region and API credentials from the env vars of the Tencent
+    /// Cloud SDKs: `TENCENTCLOUD_REGION`, `TENCENTCLOUD_SECRET_ID`,
+    /// `TENCENTCLOUD_SECRET_KEY` and the optional `TENCENTCLOUD_SESSION_TOKEN`.
+    pub fn from_env(secret_name: &str) -> Result<Self> {
+        let config = TencentSsmConfig::new(
+            &read_var("TENCENTCLOUD_REGION")?,
+            &read_var("TENCENTCLOUD_SECRET_ID")?,
+            &read_var("TENCENTCLOUD_SECRET_KEY")?,
+            secret_name,
+        );
+
+        Ok(match read_var("TENCENTCLOUD_SESSION_TOKEN") {
+            Ok(token) => config.with_session_token(&token),
+            Err(_) => config,
+        })
+    }
+
+    /// the session token of temporary credentials.
+    pub fn with_session_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// read the given version of the secret instead of the current one.
+    pub fn with_version_id(mut self, version_id: &str) -> Self {
+        self.version_id = Some(version_id.to_string());
+        self
+    }
+
+    /// read the key from `field` of a JSON object secret, instead of taking
+    /// the whole secret as the key.
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.field = Some(field.to_string());
+        self
+    }
+
+    /// send requests to `endpoint` instead of the public one, e.g.
+    /// `https://ssm.internal.tencentcloudapi.com/` inside Tencent Cloud.
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+
+    /// how long the fetched key is used before fetching it again, 5 minutes
+    /// by default.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// timeout of each request to Secrets Manager, 10 seconds by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// the signed headers and body of a `GetSecretValue` request.
+    fn request(&self, timestamp: i64) -> Result<(Vec<(&'static str, String)>, String)> {
+        let mut body = json!({ "SecretName": self.secret_name });
+        if let Some(version_id) = &self.version_id {
+            body["VersionId"] = json!(version_id);
+        }
+        let body = body.to_string();
+
+        let url = reqwest::Url::parse(&self.endpoint)
+            .map_err(|e| TlsSigError::KeyUnavailable(format!("invalid endpoint: {}", e)))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => {
+                return Err(TlsSigError::KeyUnavailable(
+                    "invalid endpoint: no host".to_string(),
+                ))
+            }
+        };
+
+        let authorization = tc3_authorization(
+            &self.secret_id,
+            &self.secret_key,
+            SERVICE,
+            timestamp,
+            &host,
+            body.as_bytes(),
+        );
+
+        let mut headers = vec![
+            ("authorization", authorization),
+            ("content-type", CONTENT_TYPE.to_string()),
+            ("x-tc-action", ACTION.to_string()),
+            ("x-tc-region", self.region.clone()),
+            ("x-tc-timestamp", timestamp.to_string()),
+            ("x-tc-version", VERSION.to_string()),
+        ];
+        if let Some(token) = &self.token {
+            headers.push(("x-tc-token", token.clone()));
+        }
+
+        Ok((headers, body))
+    }
+}
+
+impl std::fmt::Debug for TencentSsmConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("TencentSsmConfig")
+            .field("region", &self.region)
+            .field("secret_id", &self.secret_id)
+            .field("secret_key", &"<redacted>")
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("secret_name", &self.secret_name)
+            .field("version_id", &self.version_id)
+            .field("field", &self.field)
+            .field("endpoint", &self.endpoint)
+            .field("ttl", &self.ttl)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// A key provider reading the key from a Tencent Cloud Secrets Manager
+/// secret, so the key never appears in env vars or config files.
+///
+/// The key is fetched when the provider is created and cached for the TTL of
+/// the config. Once it is stale, the next signing starts fetching it again in
+/// a background thread and keeps using the stale key meanwhile, or if that
+/// fails, so signing never waits for Secrets Manager and is safe inside an
+/// async runtime. Start a [`spawn_refresher`] to fetch it before it is stale
+/// instead. Clones share the same key.
+///
+/// Requires the `tencent-ssm` feature.
+///
+/// [`spawn_refresher`]: #method.spawn_refresher
+///
+/// # Examples
+///
+/// ```no_run
+/// use tls_sig_api::{TencentSsmConfig, TencentSsmKeyProvider, TlsSigApiVer2};
+///
+/// let config = TencentSsmConfig::from_env("tls-sig-key").unwrap();
+/// let provider = TencentSsmKeyProvider::new(config).unwrap();
+/// let _refresher = provider.spawn_refresher();
+///
+/// let signer = TlsSigApiVer2::from_key_provider(1400000000, provider);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TencentSsmKeyProvider {
+    config: Arc<TencentSsmConfig>,
+    client: reqwest::blocking::Client,
+    cached: Arc<ArcSwap<Cached>>,
+    refreshing: Arc<AtomicBool>,
+}
+
+#[derive(Debug)]
+struct Cached {
+    key: Key,
+    fetched_at: Instant,
+}
+
+impl TencentSsmKeyProvider {
+    pub fn new(config: TencentSsmConfig) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e| TlsSigError::KeyUnavailable(e.to_string()))?;
+        let key = fetch_key(&client, &config)?;
+
+        Ok(TencentSsmKeyProvider {
+            config: Arc::new(config),
+            client,
+            cached: Arc::new(ArcSwap::from_pointee(Cached {
+                key,
+                fetched_at: Instant::now(),
+            })),
+            refreshing: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// fetch the key from Secrets Manager again, keeping the current key if
+    /// it fails.
+    pub fn refresh(&self) -> Result<()> {
+        let key = fetch_key(&self.client, &self.config)?;
+        self.cached.store(Arc::new(Cached {
+            key,
+            fetched_at: Instant::now(),
+        }));
+        info!(
+            "key refreshed from tencent cloud secret {}",
+            self.config.secret_name
+        );
+
+        Ok(())
+    }
+
+    /// refresh the key every TTL, at least a second, in a background thread,
+    /// until the returned refresher is dropped. Failed refreshes are logged and retried on the
+    /// next tick.
+    pub fn spawn_refresher(&self) -> KeyRefresher {
+        let provider = self.clone();
+
+        KeyRefresher::spawn(self.config.ttl, move || provider.refresh_logged())
+    }
+
+    fn refresh_logged(&self) {
+        if let Err(e) = self.refresh() {
+            warn!(
+                "failed to refresh key from tencent cloud secret {}: {}",
+                self.config.secret_name, e
+            );
+        }
+    }
+
+    /// fetch the stale key again in a background thread, one at a time, as
+    /// `reqwest::blocking` can neither block the caller nor run inside an
+    /// async runtime.
+    fn refresh_in_background(&self) {
+        if self
+            .refreshing
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return;
+        }
+
+        let provider = self.clone();
+        thread::spawn(move || {
+            provider.refresh_logged();
+            // retry failed refreshes after another TTL, not on every sign
+            let current = provider.cached.load();
+            if current.fetched_at.elapsed() >= provider.config.ttl {
+                provider.cached.store(Arc::new(Cached {
+                    key: current.key.clone(),
+                    fetched_at: Instant::now(),
+                }));
+            }
+            provider.refreshing.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+impl KeyProvider for TencentSsmKeyProvider {
+    fn current_key(&self) -> Key {
+        let cached = self.cached.load();
+        if cached.fetched_at.elapsed() < self.config.ttl {
+            return cached.key.clone();
+        }
+
+        self.refresh_in_background();
+        cached.key.clone()
+    }
+}
+
+fn fetch_key(client: &reqwest::blocking::Client, config: &TencentSsmConfig) -> Result<Key> {
+    let unavailable = |e: reqwest::Error| {
+        TlsSigError::KeyUnavailable(format!(
+            "failed to read tencent cloud secret {}: {}",
+            config.secret_name, e
+        ))
+    };
+
+    let (headers, body) = config.request(SystemTime::now().unix_timestamp())?;
+    let mut req = client.post(&config.endpoint).body(body);
+    for (name, value) in headers {
+        req = req.header(name, value);
+    }
+    let body: Value = req
+        .send()
+        .and_then(|resp| resp.error_for_status())
+        .and_then(|resp| resp.json())
+        .map_err(unavailable)?;
+
+    extract_key(&body, config.field.as_deref()).map_err(|reason| {
+        TlsSigError::KeyUnavailable(format!(
+            "failed to read tencent cloud secret {}: {}",
+            config.secret_name, reason
+        ))
+    })
+}
+
+/// pick the key out of a `GetSecretValue` response, either the whole
+/// `SecretString` or its `field`.
+fn extract_key(body: &Value, field: Option<&str>) -> std::result::Result<Key, String> {
+    let response = &body["Response"];
+    if let Some(error) = response.get("Error") {
+        return Err(format!(
+            "{} {}",
+            error["Code"].as_str().unwrap_or("unknown error"),
+            error["Message"].as_str().unwrap_or("")
+        ));
+    }
+
+    let secret = response["SecretString"]
+        .as_str()
+        .filter(|s| !s.is_empty())
+        .ok_or("the secret has no SecretString")?;
+    let key = match field {
+        None => secret.to_string(),
+        Some(field) => serde_json::from_str::<Value>(secret)
+            .ok()
+            .and_then(|value| value[field].as_str().map(str::to_string))
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| format!("the secret has no string field {}", field))?,
+    };
+
+    Ok(Key::new(key))
+}
+
+/// the `Authorization` header of Tencent Cloud API signature v3, signing the
+/// `content-type` and `host` headers of a JSON `POST /` request.
+fn tc3_authorization(
+    secret_id: &str,
+    secret_key: &str,
+    service: &str,
+    timestamp: i64,
+    host: &str,
+    body: &[u8],
+) -> String {
+    let signed_headers = "content-type;host";
+    let canonical_request = format!(
+        "POST\n/\n\ncontent-type:{}\nhost:{}\n\n{}\n{}",
+        CONTENT_TYPE,
+        host,
+        signed_headers,
+        sha256_hex(body)
+    );
+
+    let date = &rfc3339(timestamp)[..10];
+    let scope = format!("{}/{}/tc3_request", date, service);
+    let string_to_sign = format!(
+        "TC3-HMAC-SHA256\n{}\n{}\n{}",
+        timestamp,
+        scope,
+        sha256_hex(canonical_request.as_bytes())
+    );
+
+    let secret = format!("TC3{}", secret_key);
+    let signing_key = [date, service, "tc3_request"]
+        .iter()
+        .fold(secret.into_bytes(), |key, part| hmac(&key, part.as_bytes()));
+    let signature = hex(&hmac(&signing_key, string_to_sign.as_bytes()));
+
+    format!(
+        "TC3-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        secret_id, scope, signed_headers, signature
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{extract_key, tc3_authorization, TencentSsmConfig, TencentSsmKeyProvider};
+    use crate::KeyProvider;
+    use serde_json::json;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_tc3_authorization() {
+        // the example from Tencent Cloud API signature v3 docs
+        let body = r#"{"Limit": 1, "Filters": [{"Values": ["\u672a\u547d\u540d"], "Name": "instance-name"}]}"#;
+        assert_eq!(
+            tc3_authorization(
+                "AKIDz8krbsJ5yKBZQpn74WFkmLPx3EXAMPLE",
+                "Gu5t9xGARNpq86cd98joQYCN3EXAMPLE",
+                "cvm",
+                1551113065,
+                "cvm.tencentcloudapi.com",
+                body.as_bytes(),
+            ),
+            "TC3-HMAC-SHA256 Credential=AKIDz8krbsJ5yKBZQpn74WFkmLPx3EXAMPLE/2019-02-25/cvm/tc3_request, \
+             SignedHeaders=content-type;host, \
+             Signature=72e494ea809ad7a8c8f7a4507b9bddcbaa8e581f516e8da2f66e2c5a96525168"
+        );
+    }
+
+    #[test]
+    fn test_extract_key() {
+        let plain = json!({ "Response": { "SecretName": "tls-sig", "SecretString": "secret" } });
+        let object =
+            json!({ "Response": { "SecretString": r#"{"key":"secret"}"#, "SecretBinary": "" } });
+        let error = json!({ "Response": { "Error": { "Code": "ResourceNotFound", "Message": "no such secret" } } });
+
+        assert_eq!(extract_key(&plain, None).unwrap().expose(), "secret");
+        assert_eq!(
+            extract_key(&object, Some("key")).unwrap().expose(),
+            "secret"
+        );
+        assert!(extract_key(&object, Some("other")).is_err());
+        assert!(extract_key(&error, None)
+            .unwrap_err()
+            .contains("ResourceNotFound"));
+    }
+
+    #[test]
+    fn test_tencent_ssm_key_provider() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}/", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 4096];
+            let n = stream.read(&mut buf).unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+            assert!(request.starts_with("post / "));
+            assert!(request.contains("x-tc-action: getsecretvalue"));
+            assert!(request.contains("x-tc-region: ap-guangzhou"));
+            assert!(request.contains("authorization: tc3-hmac-sha256 credential=akid/"));
+
+            let body = r#"{"Response":{"SecretName":"tls-sig","SecretString":"ssm-secret","RequestId":"1"}}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+
+        let config = TencentSsmConfig::new("ap-guangzhou", "AKID", "secret key", "tls-sig")
+            .with_endpoint(&endpoint)
+            .with_ttl(Duration::from_secs(0));
+        let provider = TencentSsmKeyProvider::new(config).unwrap();
+        server.join().unwrap();
+
+        // the key is stale at once, and the server is gone, so the stale key
+        // is kept
+        assert_eq!(provider.current_key().expose(), "ssm-secret");
+        assert!(provider.refresh().is_err());
+        assert_eq!(provider.current_key().expose(), "ssm-secret");
+
+        // refreshing never blocks, so it is fine inside an async runtime
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let key = runtime.block_on(async { provider.current_key() });
+        assert_eq!(key.expose(), "ssm-secret");
+    }
+
+    #[test]
+    fn test_tencent_ssm_config_debug_redacted() {
+        let config = TencentSsmConfig::new("ap-guangzhou", "AKID", "secret key", "tls-sig")
+            .with_session_token("session token");
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("secret key"));
+        assert!(!debug.contains("session token"));
+    }
+}
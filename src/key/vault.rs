@@ -0,0 +1,251 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use log::*;
+use serde_json::Value;
+
+use crate::key::{read_var, KeyRefresher};
+use crate::{Key, KeyProvider, Result, TlsSigError};
+
+/// Where and how to read the key from Vault.
+///
+/// `path` is the API path after `/v1/`, e.g. `secret/data/tls-sig` for a KV
+/// version 2 engine mounted at `secret`, or `secret/tls-sig` for version 1.
+#[derive(Clone)]
+pub struct VaultConfig {
+    addr: String,
+    token: String,
+    path: String,
+    field: String,
+    timeout: Duration,
+}
+
+impl VaultConfig {
+    pub fn new(addr: &str, token: &str, path: &str) -> Self {
+        VaultConfig {
+            addr: addr.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            path: path.trim_matches('/').to_string(),
+            field: "key".to_string(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// read the address and token from `VAULT_ADDR` and `VAULT_TOKEN`, as the
+    /// Vault CLI does.
+    pub fn from_env(path: &str) -> Result<Self> {
+        Ok(VaultConfig::new(
+            &read_var("VAULT_ADDR")?,
+            &read_var("VAULT_TOKEN")?,
+            path,
+        ))
+    }
+
+    /// the field of the secret holding the key, `key` by default.
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.field = field.to_string();
+        self
+    }
+
+    /// timeout of each request to Vault, 10 seconds by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn url(&self) -> String {
+        format!("{}/v1/{}", self.addr, self.path)
+    }
+}
+
+impl std::fmt::Debug for VaultConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("VaultConfig")
+            .field("addr", &self.addr)
+            .field("token", &"<redacted>")
+            .field("path", &self.path)
+            .field("field", &self.field)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// A key provider reading the key from a HashiCorp Vault KV secret.
+///
+/// The key is fetched when the provider is created and cached afterwards, so
+/// signing never waits for Vault. Call [`refresh`] or start a
+/// [`spawn_refresher`] to pick up a rotated key. Clones share the same key.
+///
+/// Requires the `vault` feature.
+///
+/// [`refresh`]: #method.refresh
+/// [`spawn_refresher`]: #method.spawn_refresher
+///
+/// # Examples
+///
+/// ```no_run
+/// use tls_sig_api::{TlsSigApiVer2, VaultConfig, VaultKeyProvider};
+/// use std::time::Duration;
+///
+/// let config = VaultConfig::from_env("secret/data/tls-sig").unwrap();
+/// let provider = VaultKeyProvider::new(config).unwrap();
+/// let _refresher = provider.spawn_refresher(Duration::from_secs(300));
+///
+/// let signer = TlsSigApiVer2::from_key_provider(1400000000, provider);
+/// ```
+#[derive(Debug, Clone)]
+pub struct VaultKeyProvider {
+    config: Arc<VaultConfig>,
+    client: reqwest::blocking::Client,
+    key: Arc<ArcSwap<Key>>,
+}
+
+impl VaultKeyProvider {
+    pub fn new(config: VaultConfig) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e| TlsSigError::KeyUnavailable(e.to_string()))?;
+        let key = fetch_key(&client, &config)?;
+
+        Ok(VaultKeyProvider {
+            config: Arc::new(config),
+            client,
+            key: Arc::new(ArcSwap::from_pointee(key)),
+        })
+    }
+
+    /// fetch the key from Vault again, keeping the current key if it fails.
+    pub fn refresh(&self) -> Result<()> {
+        let key = fetch_key(&self.client, &self.config)?;
+        self.key.store(Arc::new(key));
+        info!("key refreshed from vault path {}", self.config.path);
+
+        Ok(())
+    }
+
+    /// refresh the key every `interval`, at least a second, in a background
+    /// thread, until the returned refresher is dropped. Failed refreshes are logged and retried
+    /// on the next tick.
+    pub fn spawn_refresher(&self, interval: Duration) -> KeyRefresher {
+        let provider = self.clone();
+
+        KeyRefresher::spawn(interval, move || {
+            if let Err(e) = provider.refresh() {
+                warn!(
+                    "failed to refresh key from vault path {}: {}",
+                    provider.config.path, e
+                );
+            }
+        })
+    }
+}
+
+// only the cached key is read, so the default `current_key_async` never
+// blocks the executor
+impl KeyProvider for VaultKeyProvider {
+    fn current_key(&self) -> Key {
+        Key::clone(&self.key.load())
+    }
+}
+
+fn fetch_key(client: &reqwest::blocking::Client, config: &VaultConfig) -> Result<Key> {
+    let unavailable = |e: reqwest::Error| {
+        TlsSigError::KeyUnavailable(format!("failed to read vault path {}: {}", config.path, e))
+    };
+
+    let body: Value = client
+        .get(config.url())
+        .header("X-Vault-Token", &config.token)
+        .send()
+        .and_then(|resp| resp.error_for_status())
+        .and_then(|resp| resp.json())
+        .map_err(unavailable)?;
+
+    extract_key(&body, &config.field).ok_or_else(|| {
+        TlsSigError::KeyUnavailable(format!(
+            "vault path {} has no string field {}",
+            config.path, config.field
+        ))
+    })
+}
+
+/// pick the key out of a KV response, either `{"data": {"data": {..}}}` from
+/// version 2 or `{"data": {..}}` from version 1.
+fn extract_key(body: &Value, field: &str) -> Option<Key> {
+    let data = body.get("data")?;
+    let secret = match data.get("data") {
+        Some(inner) if inner.is_object() => inner,
+        _ => data,
+    };
+
+    secret
+        .get(field)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .map(Key::new)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{extract_key, VaultConfig, VaultKeyProvider};
+    use crate::{KeyProvider, TlsSigError};
+    use serde_json::json;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn test_extract_key() {
+        let v2 = json!({ "data": { "data": { "key": "secret" }, "metadata": { "version": 3 } } });
+        let v1 = json!({ "data": { "key": "secret" } });
+
+        assert_eq!(extract_key(&v2, "key").unwrap().expose(), "secret");
+        assert_eq!(extract_key(&v1, "key").unwrap().expose(), "secret");
+        assert!(extract_key(&v1, "other").is_none());
+        assert!(extract_key(&json!({ "errors": [] }), "key").is_none());
+    }
+
+    #[test]
+    fn test_vault_key_provider() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 4096];
+            let n = stream.read(&mut buf).unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+            assert!(request.starts_with("get /v1/secret/data/tls-sig "));
+            assert!(request.contains("x-vault-token: s.token"));
+
+            let body = r#"{"data":{"data":{"key":"vault-secret"}}}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+
+        let config = VaultConfig::new(&addr, "s.token", "/secret/data/tls-sig");
+        let provider = VaultKeyProvider::new(config).unwrap();
+        assert_eq!(provider.current_key().expose(), "vault-secret");
+        server.join().unwrap();
+
+        // the server is gone, so the refresh fails but the key is kept
+        assert!(matches!(
+            provider.refresh(),
+            Err(TlsSigError::KeyUnavailable(_))
+        ));
+        assert_eq!(provider.current_key().expose(), "vault-secret");
+    }
+
+    #[test]
+    fn test_vault_config_debug_redacted() {
+        let config = VaultConfig::new("http://127.0.0.1:8200", "s.token", "secret/tls-sig");
+        assert!(!format!("{:?}", config).contains("s.token"));
+    }
+}
@@ -1,136 +1,1590 @@
-use chrono::{DateTime, Duration, Utc};
-use deflate::{deflate_bytes_zlib_conf, Compression};
+use arc_swap::{ArcSwap, Guard};
 use log::*;
-use sha2::Sha256;
-use hmac::{Hmac, Mac};
-use serde_json::json;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+mod audit;
+mod b64;
+mod batch;
+mod cache;
+mod cachedsig;
+mod clock;
+#[cfg(any(feature = "aws-kms", feature = "tencent-ssm"))]
+mod cloudsign;
+mod compression;
+#[cfg(feature = "dashmap")]
+mod concurrentcache;
+mod digest;
+mod error;
+mod explain;
+mod identifier;
+mod key;
+mod mac;
+mod payload;
+mod policy;
+mod ratelimit;
+#[cfg(any(feature = "rest", feature = "rest-blocking"))]
+mod rest;
+mod shared;
+#[cfg(feature = "async")]
+mod sigrefresher;
+mod store;
+mod timcode;
+mod timing;
+mod usersig;
+mod verifier;
+
+pub use audit::AuditHook;
+pub use b64::Base64Alphabet;
+pub use batch::SignIter;
+pub use cache::SigCache;
+pub use cachedsig::CachedSig;
+pub use clock::{Clock, FixedClock, SystemClock};
+#[cfg(feature = "deflate")]
+pub use compression::Deflate;
+#[cfg(feature = "flate2")]
+pub use compression::Flate2;
+#[cfg(feature = "miniz-oxide-compressor")]
+pub use compression::MinizOxide;
+pub use compression::{CompressionLevel, Compressor};
+#[cfg(feature = "dashmap")]
+pub use concurrentcache::ConcurrentSigCache;
+pub use digest::SigDigest;
+pub use error::{Result, TlsSigError};
+pub use explain::{explain, SigExplanation};
+pub use identifier::{validate_identifier, Identifier, SdkAppId, MAX_IDENTIFIER_LEN};
+#[cfg(feature = "notify")]
+pub use key::FileKeyWatcher;
+pub use key::{
+    EnvKeyProvider, FileKeyProvider, Key, KeyFuture, KeyProvider, KeyRefresher, KeyRing,
+    KeyRotation, RotationEvent, RotationSchedule, DEFAULT_APPID_VAR, DEFAULT_KEY_VAR,
+};
+#[cfg(feature = "tencent-ssm")]
+pub use key::{TencentSsmConfig, TencentSsmKeyProvider};
+#[cfg(feature = "vault")]
+pub use key::{VaultConfig, VaultKeyProvider};
+#[cfg(feature = "aws-kms")]
+pub use mac::AwsKmsMac;
+pub use mac::{HmacBackend, MacBackend, MacFuture, VerifyFuture};
+use payload::RawPayload;
+pub use payload::{decode_debug_sign, decode_sign, SigPayload};
+pub use policy::{ExpirePolicy, MAX_EXPIRE_SECS};
+pub use ratelimit::RateLimitedSigner;
+#[cfg(feature = "rest-blocking")]
+pub use rest::BlockingHttpTransport;
+#[cfg(any(feature = "rest", feature = "rest-blocking"))]
+pub use rest::{
+    AccountCheckItem, AccountDeleteItem, AccountError, AccountImportReport, AccountStatus,
+    AddFriendItem, AddGroupMemberItem, AddGroupMemberStatus, AppInfo, ApplyJoinOption,
+    BatchSendMsg, BatchSendMsgResult, BlacklistCheckItem, BlacklistItem, BlacklistPage,
+    BlacklistQuery, BlacklistRelation, ChatType, CreateGroup, CreateTopic, CustomElem,
+    DestroyTopicItem, FaceElem, FileElem, Friend, FriendCheckItem, FriendPage, FriendQuery,
+    FriendRelation, FriendResultItem, FriendType, FriendUpdate, GroupAttr, GroupInfo, GroupMember,
+    GroupMemberPage, GroupMemberQuery, GroupRole, GroupType, HistoryFile, HttpOptions, HttpRequest,
+    HttpResponse, ImPush, ImageElem, ImageInfo, ImportGroupMember, ImportGroupMsg,
+    ImportGroupMsgItem, ImportMsg, LocationElem, Middleware, ModifyGroupBaseInfo,
+    ModifyGroupMemberInfo, ModifyTopic, MsgElement, MsgPriority, MultiAccountImportResult,
+    NoSpeaking, OfflinePushInfo, OnlineState, OnlineStatus, OnlineStatusResult, PlatformStatus,
+    ProfileItem, ProfileTag, PushAttrs, PushCondition, PushReport, PushTags, RecentContact,
+    RecentContactPage, RecentContactQuery, RetryPolicy, RoamMsg, RoamMsgPage, RoamMsgQuery,
+    SendGroupMsg, SendGroupMsgResult, SendMsg, SendMsgResult, Session, ShuttedMember, SnsItem,
+    SoundElem, TextElem, TimResponse, TopicInfo, UserProfile, VideoFileElem, DEFAULT_ENDPOINT,
+};
+#[cfg(feature = "rest")]
+pub use rest::{GroupMemberStream, RecentContactStream, RoamMsgStream, TimClient};
+#[cfg(feature = "rest-blocking")]
+pub use rest::{GroupMembers, RecentContacts, RoamMsgs, TimClientBlocking};
+#[cfg(feature = "rest")]
+pub use rest::{HttpTransport, TransportFuture};
+pub use shared::SharedSigner;
+#[cfg(feature = "async")]
+pub use sigrefresher::{RefreshedSigs, SigRefresher};
+#[cfg(feature = "redis")]
+pub use store::RedisSigStore;
+pub use store::{MemorySigStore, SigStore, StoredSigner};
+pub use timcode::TimErrorCode;
+pub use timing::{ExpireDuration, UnixTime};
+pub use usersig::{SigInfo, UserSig};
+pub use verifier::MultiKeyVerifier;
 
 pub struct TlsSigApiVer2 {
-    sdkappid: u64,
+    sdkappid: SdkAppId,
     tls_ver: &'static str,
-    secret: String,
+    mac: ArcSwap<Box<dyn MacBackend>>,
+    /// bumped whenever the key is replaced, so that caches drop the sigs of
+    /// the old key.
+    key_version: AtomicU64,
+    clock: Box<dyn Clock>,
+    expire_policy: ExpirePolicy,
+    validate_identifier: bool,
+    leeway: i64,
+    compression: CompressionLevel,
+    compressor: Box<dyn Compressor>,
+    alphabet: Base64Alphabet,
+    audit_hook: Option<Box<dyn AuditHook>>,
+}
+
+/// The key, or MAC backend, is never printed.
+impl fmt::Debug for TlsSigApiVer2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TlsSigApiVer2")
+            .field("sdkappid", &self.sdkappid.get())
+            .field("tls_ver", &self.tls_ver)
+            .field("key", &"<redacted>")
+            .field("expire_policy", &self.expire_policy)
+            .field("validate_identifier", &self.validate_identifier)
+            .field("leeway", &self.leeway)
+            .field("compression", &self.compression)
+            .field("compressor", &self.compressor)
+            .field("alphabet", &self.alphabet)
+            .field("audit_hook", &self.audit_hook.is_some())
+            .finish()
+    }
 }
 
-impl TlsSigApiVer2 {
-    pub fn new(sdkappid: u64, key: &str) -> Self {
-        TlsSigApiVer2 {
-            sdkappid,
-            tls_ver: "2.0",
-            secret: key.to_string(),
+impl TlsSigApiVer2 {
+    pub fn new<A: Into<SdkAppId>>(sdkappid: A, key: &str) -> Self {
+        Self::from_key_provider(sdkappid, Key::new(key))
+    }
+
+    /// create a signer like [`new`], but catching configuration mistakes
+    /// early: a zero sdkappid or an empty key is rejected, and a key that is
+    /// not the 64-character hex secret shown in the Tencent console is logged
+    /// as a warning, as it is likely mis-pasted.
+    ///
+    /// [`new`]: #method.new
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{TlsSigApiVer2, TlsSigError};
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// assert!(TlsSigApiVer2::new_checked(1400000000, mock_key).is_ok());
+    ///
+    /// assert!(matches!(
+    ///     TlsSigApiVer2::new_checked(1400000000, ""),
+    ///     Err(TlsSigError::InvalidKey)
+    /// ));
+    /// ```
+    pub fn new_checked<A: Into<SdkAppId>>(sdkappid: A, key: &str) -> Result<Self> {
+        let sdkappid = SdkAppId::new(sdkappid.into().get())?;
+
+        if key.trim().is_empty() {
+            return Err(TlsSigError::InvalidKey);
+        }
+        if !is_console_key(key) {
+            warn!(
+                "the key of sdkappid {} is not a 64-character hex secret, \
+                 check it is copied from the Tencent console as is",
+                sdkappid
+            );
+        }
+
+        Ok(Self::new(sdkappid, key))
+    }
+
+    /// create a signer whose key is fetched from `key_provider` on every
+    /// signing and verifying.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{Key, KeyProvider, TlsSigApiVer2};
+    /// use std::time::Duration;
+    ///
+    /// struct MyVault;
+    ///
+    /// impl KeyProvider for MyVault {
+    ///     fn current_key(&self) -> Key {
+    ///         Key::new("5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e")
+    ///     }
+    /// }
+    ///
+    /// let signer = TlsSigApiVer2::from_key_provider(0, MyVault);
+    /// let sig = signer.gen_sign("10086", Duration::from_secs(7200), None);
+    /// assert!(signer.verify_sign("10086", &sig).is_ok());
+    /// ```
+    pub fn from_key_provider<A: Into<SdkAppId>, P: KeyProvider + 'static>(
+        sdkappid: A,
+        key_provider: P,
+    ) -> Self {
+        Self::from_mac_backend(sdkappid, HmacBackend::from_key_provider(key_provider))
+    }
+
+    /// create a signer whose HMAC is computed by `backend`, e.g. a cloud KMS
+    /// holding the key, so that the signer never sees the raw secret.
+    ///
+    /// See [`MacBackend`] for an example.
+    ///
+    /// [`MacBackend`]: trait.MacBackend.html
+    pub fn from_mac_backend<A: Into<SdkAppId>, B: MacBackend + 'static>(
+        sdkappid: A,
+        backend: B,
+    ) -> Self {
+        TlsSigApiVer2 {
+            sdkappid: sdkappid.into(),
+            tls_ver: "2.0",
+            mac: ArcSwap::from_pointee(Box::new(backend)),
+            key_version: AtomicU64::new(0),
+            clock: Box::new(SystemClock),
+            expire_policy: ExpirePolicy::default(),
+            validate_identifier: true,
+            leeway: 0,
+            compression: CompressionLevel::default(),
+            compressor: compression::default_compressor(),
+            alphabet: Base64Alphabet::default(),
+            audit_hook: None,
+        }
+    }
+
+    /// create a signer from env, reading the sdkappid from
+    /// [`DEFAULT_APPID_VAR`] and the key from [`DEFAULT_KEY_VAR`].
+    ///
+    /// [`DEFAULT_APPID_VAR`]: constant.DEFAULT_APPID_VAR.html
+    /// [`DEFAULT_KEY_VAR`]: constant.DEFAULT_KEY_VAR.html
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tls_sig_api::TlsSigApiVer2;
+    ///
+    /// // with TLS_SIG_APPID and TLS_SIG_KEY set
+    /// let signer = TlsSigApiVer2::from_env().expect("signer not configured");
+    /// ```
+    pub fn from_env() -> Result<Self> {
+        Self::from_env_vars(DEFAULT_APPID_VAR, DEFAULT_KEY_VAR)
+    }
+
+    /// create a signer from env like [`from_env`], but with custom var names.
+    ///
+    /// [`from_env`]: #method.from_env
+    pub fn from_env_vars(appid_var: &str, key_var: &str) -> Result<Self> {
+        let sdkappid: SdkAppId = key::read_var(appid_var)?.parse()?;
+        let key_provider = EnvKeyProvider::from_var(key_var)?;
+
+        Ok(Self::from_key_provider(sdkappid, key_provider))
+    }
+
+    /// replace the clock used to get current time when signing and verifying.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "chrono")] {
+    /// use tls_sig_api::{decode_sign, FixedClock, TlsSigApiVer2};
+    /// use chrono::{Duration, TimeZone, Utc};
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let mock_now = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+    /// let signer = TlsSigApiVer2::new(0, mock_key).with_clock(FixedClock(mock_now.into()));
+    ///
+    /// let sig = signer.gen_sign("10086", Duration::hours(2), None);
+    /// assert_eq!(decode_sign(&sig).unwrap().time, mock_now.timestamp());
+    /// # }
+    /// ```
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// set how expires out of the allowed range are treated, they are signed
+    /// as is by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{decode_sign, ExpirePolicy, TlsSigApiVer2, MAX_EXPIRE_SECS};
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key).with_expire_policy(ExpirePolicy::clamp_to_max());
+    ///
+    /// let sig = signer.gen_sign("10086", Duration::from_secs(365 * 24 * 3600), None);
+    /// assert_eq!(decode_sign(&sig).unwrap().expire, MAX_EXPIRE_SECS as i64);
+    /// ```
+    pub fn with_expire_policy(mut self, policy: ExpirePolicy) -> Self {
+        self.expire_policy = policy;
+        self
+    }
+
+    /// enable or disable the identifier validation done before signing, see
+    /// [`validate_identifier`] for the rules. It is enabled by default, only
+    /// disable it if you rely on identifiers Tencent does not document.
+    ///
+    /// [`validate_identifier`]: fn.validate_identifier.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::TlsSigApiVer2;
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    /// assert!(signer.try_gen_sign("", Duration::from_secs(7200), None).is_err());
+    ///
+    /// let signer = signer.with_identifier_validation(false);
+    /// assert!(signer.try_gen_sign("", Duration::from_secs(7200), None).is_ok());
+    /// ```
+    pub fn with_identifier_validation(mut self, enabled: bool) -> Self {
+        self.validate_identifier = enabled;
+        self
+    }
+
+    /// set the clock skew tolerated when verifying sigs, in both directions:
+    /// sigs issued up to `leeway` in the future, and sigs expired no longer
+    /// than `leeway` ago are still accepted. No leeway by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{FixedClock, TlsSigApiVer2};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let now = SystemTime::now();
+    /// // a peer whose clock runs two minutes ahead
+    /// let peer = TlsSigApiVer2::new(0, mock_key)
+    ///     .with_clock(FixedClock(now + Duration::from_secs(120)));
+    /// let sig = peer.gen_sign("10086", Duration::from_secs(7200), None);
+    ///
+    /// let verifier = TlsSigApiVer2::new(0, mock_key).with_clock(FixedClock(now));
+    /// assert!(verifier.verify_sign("10086", &sig).is_err());
+    ///
+    /// let verifier = verifier.with_leeway(Duration::from_secs(300));
+    /// assert!(verifier.verify_sign("10086", &sig).is_ok());
+    /// ```
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway.expire_secs();
+        self
+    }
+
+    /// set how hard sigs are compressed, [`CompressionLevel::Best`] like the
+    /// official SDKs by default.
+    ///
+    /// [`CompressionLevel::Best`]: enum.CompressionLevel.html#variant.Best
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{CompressionLevel, TlsSigApiVer2};
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key).with_compression(CompressionLevel::Fast);
+    ///
+    /// let sig = signer.gen_sign("10086", Duration::from_secs(7200), None);
+    /// assert!(signer.verify_sign("10086", &sig).is_ok());
+    /// ```
+    pub fn with_compression(mut self, level: CompressionLevel) -> Self {
+        self.compression = level;
+        self
+    }
+
+    /// compress sigs with `compressor` instead of the one selected by cargo
+    /// features, see [`Compressor`].
+    ///
+    /// [`Compressor`]: trait.Compressor.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{Compressor, CompressionLevel, TlsSigApiVer2};
+    /// use std::io::{self, Write};
+    ///
+    /// /// stores the payload uncompressed, in zlib's framing.
+    /// #[derive(Debug)]
+    /// struct Stored;
+    ///
+    /// impl Compressor for Stored {
+    ///     fn compress(&self, raw: &[u8], _: CompressionLevel, out: &mut dyn Write) -> io::Result<()> {
+    ///         let len = raw.len() as u16;
+    ///         out.write_all(&[0x78, 0x01, 0x01])?;
+    ///         out.write_all(&len.to_le_bytes())?;
+    ///         out.write_all(&(!len).to_le_bytes())?;
+    ///         out.write_all(raw)?;
+    ///
+    ///         let (mut a, mut b) = (1u32, 0u32);
+    ///         for &byte in raw {
+    ///             a = (a + u32::from(byte)) % 65521;
+    ///             b = (b + a) % 65521;
+    ///         }
+    ///         out.write_all(&((b << 16) | a).to_be_bytes())
+    ///     }
+    /// }
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key).with_compressor(Stored);
+    ///
+    /// let sig = signer.gen_sign("10086", std::time::Duration::from_secs(7200), None);
+    /// assert!(signer.verify_sign("10086", &sig).is_ok());
+    /// ```
+    pub fn with_compressor<C: Compressor + 'static>(mut self, compressor: C) -> Self {
+        self.compressor = Box::new(compressor);
+        self
+    }
+
+    /// encode sigs in `alphabet`, the standard one by default. Sigs of either
+    /// alphabet are verified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{Base64Alphabet, TlsSigApiVer2};
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer =
+    ///     TlsSigApiVer2::new(0, mock_key).with_base64_alphabet(Base64Alphabet::TencentUrlSafe);
+    ///
+    /// let sig = signer.gen_sign("10086", Duration::from_secs(7200), None);
+    /// assert!(!sig.contains(|c| c == '+' || c == '/' || c == '='));
+    /// ```
+    pub fn with_base64_alphabet(mut self, alphabet: Base64Alphabet) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+
+    /// call `hook` on every sig issued, see [`AuditHook`].
+    ///
+    /// [`AuditHook`]: trait.AuditHook.html
+    pub fn with_audit_hook<H: AuditHook + 'static>(mut self, hook: H) -> Self {
+        self.audit_hook = Some(Box::new(hook));
+        self
+    }
+
+    pub fn appid(&self) -> u64 {
+        self.sdkappid.get()
+    }
+
+    /// In case that the key is leaked, we want to update the key at runtime.
+    ///
+    /// The key provider, or MAC backend, is replaced by the given key. Only
+    /// `&self` is needed, so a signer shared between threads can be rotated
+    /// while it keeps signing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::TlsSigApiVer2;
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// let signer = Arc::new(TlsSigApiVer2::new(0, "leaked key"));
+    ///
+    /// let rotator = Arc::clone(&signer);
+    /// std::thread::spawn(move || {
+    ///     rotator.update_key("5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e");
+    /// })
+    /// .join()
+    /// .unwrap();
+    ///
+    /// let sig = signer.gen_sign("10086", Duration::from_secs(7200), None);
+    /// assert!(signer.verify_sign("10086", &sig).is_ok());
+    /// ```
+    pub fn update_key(&self, key: &str) {
+        self.update_mac_backend(HmacBackend::new(key));
+    }
+
+    /// replace the MAC backend at runtime, like [`update_key`].
+    ///
+    /// [`update_key`]: #method.update_key
+    pub fn update_mac_backend<B: MacBackend + 'static>(&self, backend: B) {
+        self.mac.store(Arc::new(Box::new(backend)));
+        self.key_version.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// how many times the key has been replaced by [`update_key`] or
+    /// [`update_mac_backend`]. Read it before signing, so that a sig tagged
+    /// with it is never signed by an older key.
+    ///
+    /// [`update_key`]: #method.update_key
+    /// [`update_mac_backend`]: #method.update_mac_backend
+    pub(crate) fn key_version(&self) -> u64 {
+        self.key_version.load(Ordering::SeqCst)
+    }
+
+    /// the current MAC backend, loaded without locking so that signing never
+    /// waits for a rotation, nor the other way round.
+    fn mac(&self) -> Guard<Arc<Box<dyn MacBackend>>> {
+        self.mac.load()
+    }
+
+    /// generate user sign with timestamp. Note that the SDK only accept
+    /// timestamps **in seconds**.
+    ///
+    /// `expire` can be either a [`chrono::Duration`] or a
+    /// [`std::time::Duration`], see [`ExpireDuration`].
+    ///
+    /// [`ExpireDuration`]: trait.ExpireDuration.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sig can not be generated, e.g. `expire` is zero or
+    /// negative, see [`try_gen_sign`] for a non-panicking version.
+    ///
+    /// [`try_gen_sign`]: #method.try_gen_sign
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "chrono")] {
+    /// use tls_sig_api::TlsSigApiVer2;
+    /// use chrono::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// let identifier = "10086";
+    /// let expire = Duration::hours(2);
+    /// let userbuf = "This' really a good crate!";
+    ///
+    /// let digest = signer.gen_sign(identifier, expire, Some(userbuf));
+    /// println!("{}", digest);
+    ///
+    /// // or without chrono
+    /// let expire = std::time::Duration::from_secs(7200);
+    /// let digest = signer.gen_sign(identifier, expire, Some(userbuf));
+    /// println!("{}", digest);
+    /// # }
+    /// ```
+    pub fn gen_sign<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> UserSig {
+        self.try_gen_sign(identifier, expire, userbuf)
+            .expect("Failed to generate sig")
+    }
+
+    /// generate user sign like [`gen_sign`], but return an error instead of
+    /// panicking when the sig can not be generated.
+    ///
+    /// Like every signing method, it returns [`TlsSigError::InvalidExpire`]
+    /// for zero or negative expires, since such a sig is expired already, and
+    /// [`TlsSigError::SigTooLong`] for a userbuf so large that the sig would
+    /// be refused when decoded.
+    ///
+    /// [`TlsSigError::InvalidExpire`]: enum.TlsSigError.html#variant.InvalidExpire
+    /// [`TlsSigError::SigTooLong`]: enum.TlsSigError.html#variant.SigTooLong
+    /// [`gen_sign`]: #method.gen_sign
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "chrono")] {
+    /// use tls_sig_api::TlsSigApiVer2;
+    /// use chrono::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// match signer.try_gen_sign("10086", Duration::hours(2), None) {
+    ///     Ok(digest) => println!("{}", digest),
+    ///     Err(e) => eprintln!("failed to sign: {}", e),
+    /// }
+    /// # }
+    /// ```
+    pub fn try_gen_sign<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<UserSig> {
+        self.sign(
+            identifier,
+            self.curr_timestamp(),
+            expire.expire_secs(),
+            userbuf.map(str::as_bytes),
+        )
+    }
+
+    /// generate user sign like [`try_gen_sign`], but append it to `out`
+    /// instead of allocating a string for it, e.g. to write it right into a
+    /// response being built. `out` is left untouched on errors.
+    ///
+    /// [`try_gen_sign`]: #method.try_gen_sign
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::TlsSigApiVer2;
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// let mut body = String::with_capacity(512);
+    /// body.push_str(r#"{"userSig":""#);
+    /// signer.gen_sign_into("10086", Duration::from_secs(7200), None, &mut body).unwrap();
+    /// body.push_str(r#""}"#);
+    ///
+    /// let sig = &body[12..body.len() - 2];
+    /// assert!(signer.verify_sign("10086", sig).is_ok());
+    /// ```
+    pub fn gen_sign_into<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+        out: &mut String,
+    ) -> Result<()> {
+        let time = self.curr_timestamp();
+        let pending = self.prepare(
+            identifier,
+            time,
+            expire.expire_secs(),
+            userbuf.map(str::as_bytes),
+        )?;
+        let digest = self.mac().hmac_sha256(pending.content.as_bytes())?;
+
+        self.assemble_into(identifier, time, pending, &digest, out)
+            .map(|_| ())
+    }
+
+    /// generate user signs for all `identifiers` at once, e.g. to provision
+    /// accounts in bulk, returning the sig of each identifier. The sigs share
+    /// the same issue time, and buffers are reused across them.
+    ///
+    /// Duplicated identifiers are signed once. If any identifier fails to be
+    /// signed, the error is returned and no sig at all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::TlsSigApiVer2;
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// let sigs = signer
+    ///     .gen_signs(vec!["10086", "10010"], Duration::from_secs(7200), None)
+    ///     .unwrap();
+    /// assert_eq!(sigs.len(), 2);
+    /// assert!(signer.verify_sign("10010", &sigs["10010"]).is_ok());
+    /// ```
+    pub fn gen_signs<'a, I: IntoIterator<Item = &'a str>, E: ExpireDuration>(
+        &self,
+        identifiers: I,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<HashMap<String, UserSig>> {
+        let time = self.curr_timestamp();
+        let shared = self.prepare_batch(expire.expire_secs(), userbuf.map(str::as_bytes))?;
+        let mac = self.mac();
+        let mut buffers = BatchBuffers::default();
+
+        let identifiers = identifiers.into_iter();
+        let mut sigs = HashMap::with_capacity(identifiers.size_hint().0);
+        for identifier in identifiers {
+            if sigs.contains_key(identifier) {
+                continue;
+            }
+            let sig = self.sign_in_batch(&***mac, identifier, time, &shared, &mut buffers)?;
+            sigs.insert(identifier.to_string(), sig);
+        }
+
+        Ok(sigs)
+    }
+
+    /// generate user signs like [`gen_signs`], but across the threads of the
+    /// rayon pool, for huge batches like millions of test accounts.
+    ///
+    /// Requires the `rayon` feature.
+    ///
+    /// [`gen_signs`]: #method.gen_signs
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rayon::prelude::*;
+    /// use tls_sig_api::TlsSigApiVer2;
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// let identifiers: Vec<String> = (0..1000).map(|i| format!("test-{}", i)).collect();
+    /// let sigs = signer
+    ///     .par_gen_signs(identifiers.par_iter().map(String::as_str), Duration::from_secs(7200), None)
+    ///     .unwrap();
+    /// assert_eq!(sigs.len(), 1000);
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_gen_signs<'a, I, E>(
+        &self,
+        identifiers: I,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<HashMap<String, UserSig>>
+    where
+        I: rayon::iter::IntoParallelIterator<Item = &'a str>,
+        E: ExpireDuration,
+    {
+        use rayon::prelude::*;
+        use std::collections::HashSet;
+
+        let time = self.curr_timestamp();
+        let shared = self.prepare_batch(expire.expire_secs(), userbuf.map(str::as_bytes))?;
+        let mac = self.mac();
+        let mac: &dyn MacBackend = &***mac;
+
+        // deduplicated first, so that each identifier is signed once
+        let identifiers: HashSet<&str> = identifiers.into_par_iter().collect();
+        identifiers
+            .into_par_iter()
+            .map_init(BatchBuffers::default, |buffers, identifier| {
+                self.sign_in_batch(mac, identifier, time, &shared, buffers)
+                    .map(|sig| (identifier.to_string(), sig))
+            })
+            .collect()
+    }
+
+    /// generate user signs for `identifiers` lazily like [`gen_signs`],
+    /// yielding each identifier with its sig, so that huge streams, e.g. read
+    /// from a file, are signed without collecting them into memory.
+    ///
+    /// The expire is checked up front, other errors are yielded with the
+    /// identifier failing. Identifiers are not deduplicated.
+    ///
+    /// [`gen_signs`]: #method.gen_signs
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::TlsSigApiVer2;
+    /// use std::io::{BufRead, Cursor};
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// let lines = Cursor::new("10086\n10010\n").lines().map(Result::unwrap);
+    /// for (identifier, sig) in signer.sign_iter(lines, Duration::from_secs(7200), None).unwrap() {
+    ///     let sig = sig.unwrap();
+    ///     assert!(signer.verify_sign(&identifier, &sig).is_ok());
+    /// }
+    /// ```
+    pub fn sign_iter<I, E>(
+        &self,
+        identifiers: I,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<SignIter<'_, I::IntoIter>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+        E: ExpireDuration,
+    {
+        let time = self.curr_timestamp();
+        let shared = self.prepare_batch(expire.expire_secs(), userbuf.map(str::as_bytes))?;
+
+        Ok(SignIter::new(
+            self,
+            identifiers.into_iter(),
+            time,
+            shared,
+            self.mac.load_full(),
+        ))
+    }
+
+    /// sign `identifier` as part of a batch issued at `time`, sharing the
+    /// expire and userbuf of `shared` and reusing `buffers`.
+    fn sign_in_batch(
+        &self,
+        mac: &dyn MacBackend,
+        identifier: &str,
+        time: i64,
+        shared: &PendingSig,
+        buffers: &mut BatchBuffers,
+    ) -> Result<UserSig> {
+        if self.validate_identifier {
+            validate_identifier(identifier)?;
+        }
+
+        buffers.content.clear();
+        self.signing_content_into(
+            identifier,
+            time,
+            shared.expire,
+            shared.base64_buf.as_deref(),
+            &mut buffers.content,
+        );
+        let digest = mac.hmac_sha256(buffers.content.as_bytes())?;
+
+        buffers.raw_sig.clear();
+        self.raw_sig_into(identifier, time, shared, &digest, &mut buffers.raw_sig)?;
+        let mut sig = String::new();
+        self.encode_into(&buffers.raw_sig, &mut sig)?;
+
+        self.issued(identifier, time, shared.expire);
+        Ok(UserSig::new_unchecked(sig))
+    }
+
+    /// generate user sign that expires after `expire_secs` seconds, handy
+    /// when the expire comes from plain config values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{decode_sign, TlsSigApiVer2};
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// let ttl: u64 = "604800".parse().unwrap();
+    /// let sig = signer.gen_sign_secs("10086", ttl, None).unwrap();
+    /// assert_eq!(decode_sign(&sig).unwrap().expire, 604800);
+    /// ```
+    pub fn gen_sign_secs(
+        &self,
+        identifier: &str,
+        expire_secs: u64,
+        userbuf: Option<&str>,
+    ) -> Result<UserSig> {
+        self.try_gen_sign(identifier, Duration::from_secs(expire_secs), userbuf)
+    }
+
+    /// generate user sign as if it is issued at `issued_at`, which makes the
+    /// output deterministic and thus handy for tests.
+    ///
+    /// `issued_at` can be either a [`chrono::DateTime`] or a
+    /// [`std::time::SystemTime`], see [`UnixTime`].
+    ///
+    /// [`UnixTime`]: trait.UnixTime.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "chrono")] {
+    /// use tls_sig_api::TlsSigApiVer2;
+    /// use chrono::{Duration, TimeZone, Utc};
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// let issued_at = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+    /// let sig = signer.gen_sign_at("10086", issued_at, Duration::hours(2), None).unwrap();
+    /// assert_eq!(
+    ///     sig,
+    ///     signer.gen_sign_at("10086", issued_at, Duration::hours(2), None).unwrap()
+    /// );
+    /// # }
+    /// ```
+    pub fn gen_sign_at<T: UnixTime, E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        issued_at: T,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<UserSig> {
+        self.sign(
+            identifier,
+            issued_at.unix_timestamp(),
+            expire.expire_secs(),
+            userbuf.map(str::as_bytes),
+        )
+    }
+
+    /// generate user sign with a binary userbuf, such as the privilege map
+    /// used by TRTC and GME. The raw bytes are base64-encoded into the sig,
+    /// which fails with [`TlsSigError::SigTooLong`] if they make it too long
+    /// to be decoded.
+    ///
+    /// [`TlsSigError::SigTooLong`]: enum.TlsSigError.html#variant.SigTooLong
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{decode_sign, TlsSigApiVer2};
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// let userbuf = [0x01, 0x00, 0xff, 0x7f];
+    /// let sig = signer
+    ///     .gen_sign_with_userbuf_bytes("10086", Duration::from_secs(7200), &userbuf)
+    ///     .unwrap();
+    /// let payload = decode_sign(&sig).unwrap();
+    /// assert_eq!(payload.userbuf_bytes().unwrap(), Some(userbuf.to_vec()));
+    /// ```
+    pub fn gen_sign_with_userbuf_bytes<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: &[u8],
+    ) -> Result<UserSig> {
+        self.sign(
+            identifier,
+            self.curr_timestamp(),
+            expire.expire_secs(),
+            Some(userbuf),
+        )
+    }
+
+    /// generate user sign with any serializable value as userbuf, which is
+    /// embedded as its JSON representation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{decode_sign, TlsSigApiVer2};
+    /// use serde_json::json;
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// let userbuf = json!({ "room": 1234, "role": "anchor" });
+    /// let sig = signer
+    ///     .gen_sign_with_json_userbuf("10086", Duration::from_secs(7200), &userbuf)
+    ///     .unwrap();
+    /// let payload = decode_sign(&sig).unwrap();
+    /// assert_eq!(
+    ///     payload.userbuf_bytes().unwrap(),
+    ///     Some(br#"{"room":1234,"role":"anchor"}"#.to_vec())
+    /// );
+    /// ```
+    pub fn gen_sign_with_json_userbuf<E: ExpireDuration, T: Serialize + ?Sized>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: &T,
+    ) -> Result<UserSig> {
+        let userbuf = serde_json::to_vec(userbuf)?;
+        self.gen_sign_with_userbuf_bytes(identifier, expire, &userbuf)
+    }
+
+    /// generate user sign that expires at `expires_at`, e.g. a maintenance
+    /// boundary, instead of after a relative duration.
+    ///
+    /// Returns [`TlsSigError::InvalidExpire`] if `expires_at` is not later
+    /// than current time.
+    ///
+    /// [`TlsSigError::InvalidExpire`]: enum.TlsSigError.html#variant.InvalidExpire
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "chrono")] {
+    /// use tls_sig_api::{decode_sign, FixedClock, TlsSigApiVer2};
+    /// use chrono::{TimeZone, Utc};
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let mock_now = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+    /// let signer = TlsSigApiVer2::new(0, mock_key).with_clock(FixedClock(mock_now.into()));
+    ///
+    /// let expires_at = Utc.with_ymd_and_hms(2019, 10, 8, 0, 0, 0).unwrap();
+    /// let sig = signer.gen_sign_until("10086", expires_at, None).unwrap();
+    /// let payload = decode_sign(&sig).unwrap();
+    /// assert_eq!(payload.time + payload.expire, expires_at.timestamp());
+    /// # }
+    /// ```
+    pub fn gen_sign_until<T: UnixTime>(
+        &self,
+        identifier: &str,
+        expires_at: T,
+        userbuf: Option<&str>,
+    ) -> Result<UserSig> {
+        let time = self.curr_timestamp();
+        let expire = expires_at.unix_timestamp().saturating_sub(time);
+
+        self.sign(identifier, time, expire, userbuf.map(str::as_bytes))
+    }
+
+    /// generate user sign like [`try_gen_sign`], along with when it is issued
+    /// and when it expires, as they are actually signed after applying the
+    /// expire policy.
+    ///
+    /// [`try_gen_sign`]: #method.try_gen_sign
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::TlsSigApiVer2;
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// let info = signer.gen_sign_info("10086", Duration::from_secs(7200), None).unwrap();
+    /// assert_eq!(info.identifier, "10086");
+    /// assert_eq!(
+    ///     info.expires_at.duration_since(info.issued_at).unwrap(),
+    ///     Duration::from_secs(7200)
+    /// );
+    /// ```
+    pub fn gen_sign_info<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<SigInfo> {
+        self.sign_info(
+            identifier,
+            self.curr_timestamp(),
+            expire.expire_secs(),
+            userbuf.map(str::as_bytes),
+        )
+    }
+
+    /// generate the payload of a user sign like [`try_gen_sign`], but only
+    /// base64-encoded without zlib, to inspect what goes into a sig, e.g. in
+    /// interop tests. Decode it with [`decode_debug_sign`].
+    ///
+    /// **Not for production**: neither Tencent Cloud nor [`verify_sign`]
+    /// accept it, so the audit hook is not called for it either.
+    ///
+    /// [`try_gen_sign`]: #method.try_gen_sign
+    /// [`verify_sign`]: #method.verify_sign
+    /// [`decode_debug_sign`]: fn.decode_debug_sign.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{decode_debug_sign, TlsSigApiVer2};
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(1400000000, mock_key);
+    ///
+    /// let debug_sig = signer.gen_debug_sign("10086", Duration::from_secs(7200), None).unwrap();
+    /// let payload = decode_debug_sign(&debug_sig).unwrap();
+    /// assert_eq!(payload.identifier, "10086");
+    /// assert!(signer.verify_sign("10086", &debug_sig).is_err());
+    /// ```
+    pub fn gen_debug_sign<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<String> {
+        let time = self.curr_timestamp();
+        let pending = self.prepare(
+            identifier,
+            time,
+            expire.expire_secs(),
+            userbuf.map(str::as_bytes),
+        )?;
+        let digest = self.mac().hmac_sha256(pending.content.as_bytes())?;
+        let raw_sig = self.raw_sig(identifier, time, &pending, &digest)?;
+
+        Ok(self.alphabet.encode(&raw_sig))
+    }
+
+    /// generate user sign like [`try_gen_sign`], but with the HMAC computed
+    /// by [`MacBackend::hmac_sha256_async`], so that a remote backend does not
+    /// block the executor.
+    ///
+    /// [`try_gen_sign`]: #method.try_gen_sign
+    /// [`MacBackend::hmac_sha256_async`]: trait.MacBackend.html#method.hmac_sha256_async
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::TlsSigApiVer2;
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// futures::executor::block_on(async {
+    ///     let sig = signer
+    ///         .try_gen_sign_async("10086", Duration::from_secs(7200), None)
+    ///         .await
+    ///         .unwrap();
+    ///     assert!(signer.verify_sign_async("10086", &sig).await.is_ok());
+    /// });
+    /// ```
+    pub async fn try_gen_sign_async<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<UserSig> {
+        let time = self.curr_timestamp();
+        let pending = self.prepare(
+            identifier,
+            time,
+            expire.expire_secs(),
+            userbuf.map(str::as_bytes),
+        )?;
+        let digest = self
+            .mac
+            .load_full()
+            .hmac_sha256_async(pending.content.as_bytes())
+            .await?;
+
+        self.assemble(identifier, time, pending, &digest)
+            .map(|info| info.sig)
+    }
+
+    fn curr_timestamp(&self) -> i64 {
+        // Always use current time for production sign.
+        let curr_time = self.clock.now();
+        debug!(
+            "current time: {:?}, timestamp in seconds: {}",
+            curr_time,
+            curr_time.unix_timestamp()
+        );
+
+        curr_time.unix_timestamp()
+    }
+
+    fn sign(
+        &self,
+        identifier: &str,
+        time: i64,
+        expire: i64,
+        userbuf: Option<&[u8]>,
+    ) -> Result<UserSig> {
+        self.sign_info(identifier, time, expire, userbuf)
+            .map(|info| info.sig)
+    }
+
+    fn sign_info(
+        &self,
+        identifier: &str,
+        time: i64,
+        expire: i64,
+        userbuf: Option<&[u8]>,
+    ) -> Result<SigInfo> {
+        let pending = self.prepare(identifier, time, expire, userbuf)?;
+        let digest = self.mac().hmac_sha256(pending.content.as_bytes())?;
+
+        self.assemble(identifier, time, pending, &digest)
+    }
+
+    /// validate the request and build the content to be signed.
+    fn prepare(
+        &self,
+        identifier: &str,
+        time: i64,
+        expire: i64,
+        userbuf: Option<&[u8]>,
+    ) -> Result<PendingSig> {
+        if self.validate_identifier {
+            validate_identifier(identifier)?;
+        }
+
+        let mut pending = self.prepare_batch(expire, userbuf)?;
+        pending.content = self.signing_content(
+            identifier,
+            time,
+            pending.expire,
+            pending.base64_buf.as_deref(),
+        );
+
+        Ok(pending)
+    }
+
+    /// validate the expire and encode the userbuf, shared by all sigs of a
+    /// batch. The content is left empty.
+    fn prepare_batch(&self, expire: i64, userbuf: Option<&[u8]>) -> Result<PendingSig> {
+        // A sig that is already expired when issued is always a mistake.
+        if expire <= 0 {
+            return Err(TlsSigError::InvalidExpire(expire));
+        }
+        let expire = self.expire_policy.apply(expire)?;
+
+        Ok(PendingSig {
+            expire,
+            base64_buf: userbuf.map(b64::encode),
+            content: String::new(),
+        })
+    }
+
+    /// put the digest into the payload, then compress and encode it.
+    fn assemble(
+        &self,
+        identifier: &str,
+        time: i64,
+        pending: PendingSig,
+        digest: &[u8],
+    ) -> Result<SigInfo> {
+        let mut sig = String::new();
+        let expires_at = self.assemble_into(identifier, time, pending, digest, &mut sig)?;
+
+        Ok(SigInfo {
+            sig: UserSig::new_unchecked(sig),
+            issued_at: timing::system_time(time),
+            expires_at,
+            identifier: identifier.to_string(),
+            sdkappid: self.sdkappid.get(),
+        })
+    }
+
+    /// like [`assemble`], but append the sig to `out`, returning when it
+    /// expires. `out` is left untouched on errors.
+    ///
+    /// [`assemble`]: #method.assemble
+    fn assemble_into(
+        &self,
+        identifier: &str,
+        time: i64,
+        pending: PendingSig,
+        digest: &[u8],
+        out: &mut String,
+    ) -> Result<SystemTime> {
+        let raw_sig = self.raw_sig(identifier, time, &pending, digest)?;
+        self.encode_into(&raw_sig, out)?;
+
+        Ok(self.issued(identifier, time, pending.expire))
+    }
+
+    /// compress and encode the raw sig json onto `out`, refusing a sig too
+    /// long to be decoded. `out` is left untouched on errors.
+    fn encode_into(&self, raw_sig: &[u8], out: &mut String) -> Result<()> {
+        let start = out.len();
+        compress_and_encode(
+            &*self.compressor,
+            raw_sig,
+            self.compression,
+            self.alphabet,
+            out,
+        );
+
+        let len = out.len() - start;
+        if len > payload::MAX_SIG_LEN {
+            out.truncate(start);
+            return Err(TlsSigError::SigTooLong(len));
+        }
+
+        Ok(())
+    }
+
+    /// a sig of `expire` seconds has been issued to `identifier` at `time`,
+    /// tell the audit hook and return when it expires.
+    fn issued(&self, identifier: &str, time: i64, expire: i64) -> SystemTime {
+        let expires_at = timing::system_time(time.saturating_add(expire));
+        if let Some(hook) = &self.audit_hook {
+            hook.on_sign(identifier, self.sdkappid.get(), expires_at);
+        }
+
+        expires_at
+    }
+
+    /// the raw sig json, with the digest put into the payload.
+    fn raw_sig(
+        &self,
+        identifier: &str,
+        time: i64,
+        pending: &PendingSig,
+        digest: &[u8],
+    ) -> Result<Vec<u8>> {
+        let mut raw_sig = Vec::with_capacity(RAW_SIG_CAPACITY + identifier.len());
+        self.raw_sig_into(identifier, time, pending, digest, &mut raw_sig)?;
+
+        Ok(raw_sig)
+    }
+
+    /// like [`raw_sig`], but write the json to `raw_sig`.
+    ///
+    /// [`raw_sig`]: #method.raw_sig
+    fn raw_sig_into(
+        &self,
+        identifier: &str,
+        time: i64,
+        pending: &PendingSig,
+        digest: &[u8],
+        raw_sig: &mut Vec<u8>,
+    ) -> Result<()> {
+        let digest = b64::encode(digest);
+        let dict = RawPayload {
+            ver: self.tls_ver,
+            identifier,
+            sdkappid: self.sdkappid.get(),
+            expire: pending.expire,
+            time,
+            userbuf: pending.base64_buf.as_deref(),
+            sig: &digest,
+        };
+
+        serde_json::to_writer(&mut *raw_sig, &dict)?;
+        debug!("raw sig json: {}", String::from_utf8_lossy(raw_sig));
+        if raw_sig.len() > payload::MAX_SIG_JSON_LEN {
+            return Err(TlsSigError::SigTooLong(raw_sig.len()));
         }
+
+        Ok(())
     }
 
-    pub fn appid(&self) -> u64 {
-        self.sdkappid
+    #[cfg(all(test, feature = "chrono"))]
+    fn hmac_sha256(
+        &self,
+        identifier: &str,
+        time: i64,
+        expire: i64,
+        base64_buf: Option<String>,
+    ) -> Result<String> {
+        let content = self.signing_content(identifier, time, expire, base64_buf.as_deref());
+        let digest = self.mac().hmac_sha256(content.as_bytes())?;
+
+        Ok(b64::encode(&digest))
     }
 
-    /// In case that the key is leaked, we want to update the key at runtime.
-    pub fn update_key(&mut self, key: &str) {
-        self.secret = key.to_string();
+    /// the content to be signed, shared by signing and verifying.
+    fn signing_content(
+        &self,
+        identifier: &str,
+        time: i64,
+        expire: i64,
+        base64_buf: Option<&str>,
+    ) -> String {
+        // sized up front, so the content is allocated once
+        let mut raw_content_to_be_signed = String::with_capacity(
+            SIGNING_CONTENT_CAPACITY + identifier.len() + base64_buf.map_or(0, str::len),
+        );
+        self.signing_content_into(
+            identifier,
+            time,
+            expire,
+            base64_buf,
+            &mut raw_content_to_be_signed,
+        );
+
+        raw_content_to_be_signed
     }
 
-    /// generate user sign with timestamp. Note that the SDK only accept
-    /// timestamps **in seconds**.
-    /// 
+    /// like [`signing_content`], but append the content to
+    /// `raw_content_to_be_signed`.
+    ///
+    /// [`signing_content`]: #method.signing_content
+    fn signing_content_into(
+        &self,
+        identifier: &str,
+        time: i64,
+        expire: i64,
+        base64_buf: Option<&str>,
+        raw_content_to_be_signed: &mut String,
+    ) {
+        use std::fmt::Write;
+
+        // writing into a string never fails
+        let _ = write!(
+            raw_content_to_be_signed,
+            "TLS.identifier:{}\nTLS.sdkappid:{}\nTLS.time:{}\nTLS.expire:{}\n",
+            identifier, self.sdkappid, time, expire,
+        );
+
+        if let Some(buf) = base64_buf {
+            raw_content_to_be_signed.push_str("TLS.userbuf:");
+            raw_content_to_be_signed.push_str(buf);
+            raw_content_to_be_signed.push('\n');
+        }
+
+        debug!("raw_content_to_be_signed: {}", raw_content_to_be_signed);
+    }
+
+    /// check the HMAC carried by `payload` in constant time.
+    pub(crate) fn check_signature(&self, payload: &SigPayload) -> Result<()> {
+        let content = self.signing_content(
+            &payload.identifier,
+            payload.time,
+            payload.expire,
+            payload.userbuf.as_deref(),
+        );
+
+        self.mac()
+            .verify_hmac_sha256(content.as_bytes(), payload.digest()?.as_bytes())
+    }
+
+    /// verify a user sign issued to `identifier`, checking both the HMAC and
+    /// the validity period against current time of the signer's clock, with
+    /// the [leeway] configured.
+    ///
+    /// [leeway]: #method.with_leeway
+    ///
+    /// The HMAC is compared in constant time, see [`SigDigest`], so it is safe
+    /// to verify sigs from untrusted clients on hot authentication paths.
+    ///
+    /// [`SigDigest`]: struct.SigDigest.html
+    ///
+    /// Both the standard base64 alphabet and the url-safe one used by the
+    /// official SDKs (`*`, `-` and `_`) are accepted.
+    ///
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "chrono")] {
     /// use tls_sig_api::TlsSigApiVer2;
     /// use chrono::Duration;
     ///
     /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
     /// let signer = TlsSigApiVer2::new(0, mock_key);
     ///
-    /// let identifier = "10086";
-    /// let expire = Duration::hours(2);
-    /// let userbuf = "This' really a good crate!";
-    ///
-    /// let digest = signer.gen_sign(identifier, expire, Some(userbuf));
-    /// println!("{}", digest);
+    /// let sig = signer.gen_sign("10086", Duration::hours(2), None);
+    /// assert!(signer.verify_sign("10086", &sig).is_ok());
+    /// assert!(signer.verify_sign("10010", &sig).is_err());
+    /// # }
     /// ```
-    pub fn gen_sign(&self, identifier: &str, expire: Duration, userbuf: Option<&str>) -> String {
-        // Always use current time for production sign.
-        let curr_time = Utc::now();
-        debug!(
-            "current time: {}, timestamp in seconds: {}",
-            curr_time,
-            curr_time.timestamp()
+    pub fn verify_sign(&self, identifier: &str, sig: &str) -> Result<()> {
+        self.verify_sign_with_time(identifier, sig, self.clock.now())
+    }
+
+    /// verify a user sign like [`verify_sign`], but with the HMAC computed by
+    /// [`MacBackend::hmac_sha256_async`].
+    ///
+    /// [`verify_sign`]: #method.verify_sign
+    /// [`MacBackend::hmac_sha256_async`]: trait.MacBackend.html#method.hmac_sha256_async
+    pub async fn verify_sign_async(&self, identifier: &str, sig: &str) -> Result<()> {
+        let payload = self.decode_claims(identifier, sig)?;
+
+        let content = self.signing_content(
+            &payload.identifier,
+            payload.time,
+            payload.expire,
+            payload.userbuf.as_deref(),
         );
+        self.mac
+            .load_full()
+            .verify_hmac_sha256_async(content.as_bytes(), payload.digest()?.as_bytes())
+            .await?;
 
-        self.gen_sign_with_time(identifier, curr_time, expire, userbuf)
+        self.check_validity(&payload, self.clock.now())
     }
 
-    fn gen_sign_with_time(
+    fn verify_sign_with_time<T: UnixTime>(
         &self,
         identifier: &str,
-        dt: DateTime<Utc>,
-        expire: Duration,
-        userbuf: Option<&str>,
-    ) -> String {
-        let mut dict = json!({
-            "TLS.ver": self.tls_ver,
-            "TLS.identifier": identifier.to_string(),
-            "TLS.sdkappid": self.sdkappid,
-            "TLS.expire": expire.num_seconds(),
-            "TLS.time": dt.timestamp()
-        });
+        sig: &str,
+        now: T,
+    ) -> Result<()> {
+        let payload = self.decode_claims(identifier, sig)?;
+        self.check_signature(&payload)?;
+
+        self.check_validity(&payload, now)
+    }
 
-        let base64_buf = userbuf.map(|buf| base64::encode_config(buf.as_bytes(), base64::STANDARD));
+    /// decode the sig, checking that it is issued to `identifier` by this
+    /// sdkappid.
+    fn decode_claims(&self, identifier: &str, sig: &str) -> Result<SigPayload> {
+        let payload = decode_sign(sig)?;
 
-        if let Some(buf) = base64_buf.clone() {
-            dict["TLS.userbuf"] = json!(buf);
+        if payload.identifier != identifier {
+            return Err(TlsSigError::IdentifierMismatch);
+        }
+        if payload.sdkappid != self.sdkappid.get() {
+            return Err(TlsSigError::SdkAppIdMismatch);
         }
 
-        dict["TLS.sig"] = json!(self.hmac_sha256(identifier, dt, expire, base64_buf));
-        debug!("raw sig json: {}", dict);
+        Ok(payload)
+    }
 
-        let sig_compressed =
-            deflate_bytes_zlib_conf(dict.to_string().as_bytes(), Compression::Best);
-        debug!("compressed sig: {:?}", &sig_compressed);
+    fn check_validity<T: UnixTime>(&self, payload: &SigPayload, now: T) -> Result<()> {
+        let now = now.unix_timestamp();
+        if now.saturating_add(self.leeway) < payload.time {
+            return Err(TlsSigError::NotYetValid);
+        }
+        if payload.is_expired(now.saturating_sub(self.leeway)) {
+            return Err(TlsSigError::Expired);
+        }
 
-        base64::encode_config(&sig_compressed, base64::STANDARD)
+        Ok(())
     }
+}
 
-    fn hmac_sha256(
-        &self,
-        identifier: &str,
-        curr_time: DateTime<Utc>,
-        expire: Duration,
-        base64_buf: Option<String>,
-    ) -> String {
-        let mut raw_content_to_be_signed = format!(
-            "TLS.identifier:{}\nTLS.sdkappid:{}\nTLS.time:{}\nTLS.expire:{}\n",
-            identifier,
-            self.sdkappid,
-            curr_time.timestamp(),
-            expire.num_seconds(),
-        )
-        .to_string();
+/// whether `key` looks like the secret shown in the Tencent console.
+fn is_console_key(key: &str) -> bool {
+    key.len() == 64 && key.bytes().all(|b| b.is_ascii_hexdigit())
+}
 
-        if let Some(buf) = base64_buf {
-            raw_content_to_be_signed.push_str(&format!("TLS.userbuf:{}\n", buf));
-        }
+/// compress the raw sig json at `level` and base64-encode it in `alphabet` onto `out`, streaming the
+/// compressed bytes into the encoder rather than collecting them first.
+fn compress_and_encode(
+    compressor: &dyn Compressor,
+    raw_sig: &[u8],
+    level: CompressionLevel,
+    alphabet: Base64Alphabet,
+    out: &mut String,
+) {
+    // base64 takes 4 bytes for every 3, and the json compresses to about half
+    out.reserve(raw_sig.len() * 2 / 3 + 4);
+    let start = out.len();
 
-        debug!("raw_content_to_be_signed: {}", raw_content_to_be_signed);
+    #[cfg(not(feature = "simd-base64"))]
+    {
+        let mut writer = StringWriter(out);
+        let mut base64 = base64::write::EncoderWriter::new(&mut writer, base64::STANDARD);
+        compressor
+            .compress(raw_sig, level, &mut base64)
+            .and_then(|()| base64.finish())
+            .expect("writing base64 into a string never fails");
+    }
+    // SIMD encodes whole buffers, so the compressed bytes are collected first
+    #[cfg(feature = "simd-base64")]
+    {
+        let mut compressed = Vec::new();
+        compressor
+            .compress(raw_sig, level, &mut compressed)
+            .expect("writing into a vec never fails");
+        b64::encode_append(&compressed, out);
+    }
+    alphabet.convert_tail(out, start);
+
+    debug!("encoded sig: {}", &out[start..]);
+}
+
+/// appends the bytes written, which must be UTF-8 like base64 is, to a
+/// string.
+#[cfg(not(feature = "simd-base64"))]
+struct StringWriter<'a>(&'a mut String);
 
-        let mut mac = Hmac::<Sha256>::new_varkey(self.secret.as_bytes())
-            .expect("HMAC can take key of any size");
-        mac.input(raw_content_to_be_signed.as_bytes());
-        let digest = mac.result().code();
+#[cfg(not(feature = "simd-base64"))]
+impl std::io::Write for StringWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let s = std::str::from_utf8(buf)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        self.0.push_str(s);
+
+        Ok(buf.len())
+    }
 
-        base64::encode_config(digest.as_ref(), base64::STANDARD)
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
 }
 
-#[cfg(test)]
+/// room for the labels and numbers of the signing content, besides the
+/// identifier and userbuf.
+const SIGNING_CONTENT_CAPACITY: usize = 128;
+
+/// room for the sig json besides the identifier, with a short userbuf.
+const RAW_SIG_CAPACITY: usize = 256;
+
+/// A sig whose content is ready to be signed.
+struct PendingSig {
+    expire: i64,
+    base64_buf: Option<String>,
+    content: String,
+}
+
+/// buffers reused across the sigs of a batch.
+#[derive(Default)]
+struct BatchBuffers {
+    content: String,
+    raw_sig: Vec<u8>,
+}
+
+// the times and expires of these tests are written with chrono
+#[cfg(all(test, feature = "chrono"))]
 mod test {
-    use super::TlsSigApiVer2;
+    use super::{
+        decode_debug_sign, decode_sign, CompressionLevel, FixedClock, TlsSigApiVer2, TlsSigError,
+    };
     use chrono::{Duration, TimeZone, Utc};
 
     const MOCK_APPID: u64 = 1400000000;
-    const MOCK_KEY: &'static str =
-        "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
-    const MOCK_USERBUF: &'static str = "abc";
+    const MOCK_KEY: &str = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    const MOCK_USERBUF: &str = "abc";
 
     fn log_init() {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -142,13 +1596,258 @@ mod test {
         assert_eq!(signer.appid(), MOCK_APPID);
     }
 
+    #[test]
+    fn test_new_checked() {
+        assert!(TlsSigApiVer2::new_checked(MOCK_APPID, MOCK_KEY).is_ok());
+        // only warned
+        assert!(TlsSigApiVer2::new_checked(MOCK_APPID, "not a console key").is_ok());
+
+        assert!(matches!(
+            TlsSigApiVer2::new_checked(MOCK_APPID, " \n"),
+            Err(TlsSigError::InvalidKey)
+        ));
+        assert!(matches!(
+            TlsSigApiVer2::new_checked(0, MOCK_KEY),
+            Err(TlsSigError::InvalidSdkAppId)
+        ));
+    }
+
+    #[test]
+    fn test_is_console_key() {
+        assert!(super::is_console_key(MOCK_KEY));
+        assert!(!super::is_console_key(&MOCK_KEY[1..]));
+        assert!(!super::is_console_key(&MOCK_KEY.replace('5', "g")));
+    }
+
+    #[test]
+    fn test_audit_hook() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let hook_count = Arc::clone(&count);
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY)
+            .with_clock(FixedClock(std::time::UNIX_EPOCH))
+            .with_audit_hook(move |identifier: &str, sdkappid, expires_at| {
+                assert_eq!(identifier, "0");
+                assert_eq!(sdkappid, MOCK_APPID);
+                assert_eq!(
+                    expires_at,
+                    std::time::UNIX_EPOCH + std::time::Duration::from_secs(7200)
+                );
+                hook_count.fetch_add(1, Ordering::SeqCst);
+            });
+
+        signer.gen_sign("0", Duration::hours(2), None);
+        futures::executor::block_on(signer.try_gen_sign_async("0", Duration::hours(2), None))
+            .unwrap();
+        // failed issuance is not audited
+        assert!(signer.try_gen_sign("0", Duration::zero(), None).is_err());
+
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_gen_sign_into() {
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let signer =
+            TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).with_clock(FixedClock(mock_curr_time.into()));
+
+        let mut out = "sig=".to_string();
+        signer
+            .gen_sign_into("0", Duration::days(180), Some(MOCK_USERBUF), &mut out)
+            .unwrap();
+        let sig = signer.gen_sign("0", Duration::days(180), Some(MOCK_USERBUF));
+        assert_eq!(out, format!("sig={}", sig));
+
+        // untouched on errors
+        assert!(signer
+            .gen_sign_into("0", Duration::zero(), None, &mut out)
+            .is_err());
+        assert_eq!(out, format!("sig={}", sig));
+    }
+
+    #[test]
+    fn test_gen_signs() {
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let signer =
+            TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).with_clock(FixedClock(mock_curr_time.into()));
+
+        let sigs = signer
+            .gen_signs(vec!["0", "1", "0"], Duration::days(180), Some(MOCK_USERBUF))
+            .unwrap();
+        assert_eq!(sigs.len(), 2);
+        for identifier in &["0", "1"] {
+            assert_eq!(
+                sigs[*identifier],
+                signer.gen_sign(identifier, Duration::days(180), Some(MOCK_USERBUF))
+            );
+        }
+
+        assert!(signer
+            .gen_signs(Vec::new(), Duration::days(180), None)
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            signer.gen_signs(vec!["0", ""], Duration::days(180), None),
+            Err(TlsSigError::InvalidIdentifier(_))
+        ));
+        assert!(matches!(
+            signer.gen_signs(vec!["0"], Duration::zero(), None),
+            Err(TlsSigError::InvalidExpire(0))
+        ));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_gen_signs() {
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let signer =
+            TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).with_clock(FixedClock(mock_curr_time.into()));
+
+        let identifiers: Vec<String> = (0..100).map(|i| format!("test-{}", i % 50)).collect();
+        let identifiers: Vec<&str> = identifiers.iter().map(String::as_str).collect();
+        let sigs = signer
+            .par_gen_signs(identifiers.clone(), Duration::days(180), Some(MOCK_USERBUF))
+            .unwrap();
+        assert_eq!(
+            sigs,
+            signer
+                .gen_signs(identifiers, Duration::days(180), Some(MOCK_USERBUF))
+                .unwrap()
+        );
+
+        assert!(matches!(
+            signer.par_gen_signs(vec!["0", ""], Duration::days(180), None),
+            Err(TlsSigError::InvalidIdentifier(_))
+        ));
+    }
+
+    #[test]
+    fn test_sign_iter() {
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let signer =
+            TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).with_clock(FixedClock(mock_curr_time.into()));
+
+        let identifiers = vec!["0".to_string(), String::new(), "1".to_string()];
+        let mut sigs = signer
+            .sign_iter(identifiers, Duration::days(180), Some(MOCK_USERBUF))
+            .unwrap();
+        assert_eq!(sigs.size_hint(), (3, Some(3)));
+
+        let (identifier, sig) = sigs.next().unwrap();
+        assert_eq!(identifier, "0");
+        assert_eq!(
+            sig.unwrap(),
+            signer.gen_sign("0", Duration::days(180), Some(MOCK_USERBUF))
+        );
+        assert!(matches!(
+            sigs.next(),
+            Some((_, Err(TlsSigError::InvalidIdentifier(_))))
+        ));
+        assert!(sigs.next().unwrap().1.is_ok());
+        assert!(sigs.next().is_none());
+
+        assert!(matches!(
+            signer.sign_iter(vec!["0"], Duration::zero(), None),
+            Err(TlsSigError::InvalidExpire(0))
+        ));
+    }
+
+    #[test]
+    fn test_gen_debug_sign() {
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let signer =
+            TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).with_clock(FixedClock(mock_curr_time.into()));
+
+        let sig = signer.gen_sign("0", Duration::days(180), Some(MOCK_USERBUF));
+        let debug_sig = signer
+            .gen_debug_sign("0", Duration::days(180), Some(MOCK_USERBUF))
+            .unwrap();
+        assert_eq!(
+            decode_debug_sign(&debug_sig).unwrap(),
+            decode_sign(sig.as_str()).unwrap()
+        );
+
+        assert!(decode_sign(&debug_sig).is_err());
+        assert!(decode_debug_sign(sig.as_str()).is_err());
+        assert!(signer.verify_sign("0", &debug_sig).is_err());
+    }
+
+    #[test]
+    fn test_compression_level() {
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let best =
+            TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).with_clock(FixedClock(mock_curr_time.into()));
+        let best_sig = best.gen_sign("0", Duration::days(180), Some(MOCK_USERBUF));
+
+        for level in [CompressionLevel::Fast, CompressionLevel::Default] {
+            let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY)
+                .with_clock(FixedClock(mock_curr_time.into()))
+                .with_compression(level);
+            let sig = signer.gen_sign("0", Duration::days(180), Some(MOCK_USERBUF));
+            assert!(best.verify_sign("0", &sig).is_ok());
+            assert!(signer.verify_sign("0", &best_sig).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_debug_redacted() {
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
+        let debug = format!("{:?}", signer);
+
+        assert!(debug.contains("sdkappid: 1400000000"));
+        assert!(debug.contains("tls_ver: \"2.0\""));
+        assert!(debug.contains("key: \"<redacted>\""));
+        assert!(!debug.contains(MOCK_KEY));
+    }
+
+    #[test]
+    fn test_from_env_vars() {
+        std::env::set_var("TEST_FROM_ENV_VARS_APPID", MOCK_APPID.to_string());
+        std::env::set_var("TEST_FROM_ENV_VARS_KEY", MOCK_KEY);
+
+        let signer =
+            TlsSigApiVer2::from_env_vars("TEST_FROM_ENV_VARS_APPID", "TEST_FROM_ENV_VARS_KEY")
+                .unwrap();
+        assert_eq!(signer.appid(), MOCK_APPID);
+        let sig = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).gen_sign("0", Duration::hours(2), None);
+        assert!(signer.verify_sign("0", &sig).is_ok());
+
+        assert!(matches!(
+            TlsSigApiVer2::from_env_vars("TEST_FROM_ENV_VARS_KEY", "TEST_FROM_ENV_VARS_KEY"),
+            Err(TlsSigError::InvalidSdkAppId)
+        ));
+    }
+
     #[test]
     fn test_update_key() {
-        let mut signer = TlsSigApiVer2::new(MOCK_APPID, "");
-        assert_eq!(signer.secret, "".to_string());
+        let signer = TlsSigApiVer2::new(MOCK_APPID, "");
+        let sig = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).gen_sign("0", Duration::hours(2), None);
+        assert!(matches!(
+            signer.verify_sign("0", &sig),
+            Err(TlsSigError::SignatureMismatch)
+        ));
 
         signer.update_key(MOCK_KEY);
-        assert_eq!(signer.secret, MOCK_KEY.to_string());
+        assert!(signer.verify_sign("0", &sig).is_ok());
+    }
+
+    #[test]
+    fn test_update_key_while_signing() {
+        let signer = TlsSigApiVer2::new(MOCK_APPID, "old key");
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for _ in 0..100 {
+                    signer.gen_sign("0", Duration::hours(2), None);
+                }
+            });
+            s.spawn(|| signer.update_key(MOCK_KEY));
+        });
+
+        let sig = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).gen_sign("0", Duration::hours(2), None);
+        assert!(signer.verify_sign("0", &sig).is_ok());
     }
 
     #[test]
@@ -157,7 +1856,11 @@ mod test {
 
         // the great moment of the 70th anniversary of the founding of new China!
         // timestamp_millis = 1569910200000
-        let mock_curr_time = Utc.ymd(2019, 10, 1).and_hms(6, 10, 0);
+        let mock_curr_time = Utc
+            .with_ymd_and_hms(2019, 10, 1, 6, 10, 0)
+            .unwrap()
+            .timestamp();
+        let mock_expire = Duration::days(180).num_seconds();
         let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
         let mock_base64_buf =
             Some(MOCK_USERBUF).map(|buf| base64::encode_config(buf.as_bytes(), base64::STANDARD));
@@ -167,15 +1870,278 @@ mod test {
         let mock_sig_with_buf = "bC3u5cuslSg8Ds7KY58mhSkTrxunrFu50dkdkCYH4i8=";
 
         assert_eq!(
-            &signer.hmac_sha256("0", mock_curr_time, Duration::days(180), None),
+            &signer
+                .hmac_sha256("0", mock_curr_time, mock_expire, None)
+                .unwrap(),
             mock_sig
         );
         assert_eq!(
-            &signer.hmac_sha256("0", mock_curr_time, Duration::days(180), mock_base64_buf),
+            &signer
+                .hmac_sha256("0", mock_curr_time, mock_expire, mock_base64_buf)
+                .unwrap(),
             mock_sig_with_buf
         );
     }
 
+    #[test]
+    fn test_try_gen_sign() {
+        log_init();
+
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
+        assert!(signer
+            .try_gen_sign("0", Duration::days(180), Some(MOCK_USERBUF))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_verify_sign() {
+        log_init();
+
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
+        let sig = signer
+            .gen_sign_at("0", mock_curr_time, Duration::days(180), Some(MOCK_USERBUF))
+            .unwrap();
+
+        assert!(signer
+            .verify_sign_with_time("0", &sig, mock_curr_time + Duration::days(1))
+            .is_ok());
+        assert!(matches!(
+            signer.verify_sign_with_time("1", &sig, mock_curr_time),
+            Err(TlsSigError::IdentifierMismatch)
+        ));
+        assert!(matches!(
+            signer.verify_sign_with_time("0", &sig, mock_curr_time + Duration::days(181)),
+            Err(TlsSigError::Expired)
+        ));
+        assert!(matches!(
+            TlsSigApiVer2::new(MOCK_APPID + 1, MOCK_KEY).verify_sign_with_time(
+                "0",
+                &sig,
+                mock_curr_time
+            ),
+            Err(TlsSigError::SdkAppIdMismatch)
+        ));
+        assert!(matches!(
+            TlsSigApiVer2::new(MOCK_APPID, "another key").verify_sign_with_time(
+                "0",
+                &sig,
+                mock_curr_time
+            ),
+            Err(TlsSigError::SignatureMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_reject_non_positive_expire() {
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
+
+        assert!(matches!(
+            signer.try_gen_sign("0", Duration::seconds(-5), None),
+            Err(TlsSigError::InvalidExpire(-5))
+        ));
+        assert!(matches!(
+            signer.gen_sign_secs("0", 0, None),
+            Err(TlsSigError::InvalidExpire(0))
+        ));
+        assert!(matches!(
+            signer.gen_sign_with_userbuf_bytes("0", Duration::zero(), b"abc"),
+            Err(TlsSigError::InvalidExpire(0))
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_gen_sign_panics_on_negative_expire() {
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
+        signer.gen_sign("0", Duration::seconds(-5), None);
+    }
+
+    #[test]
+    fn test_identifier_validation() {
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
+        let long_identifier = "a".repeat(33);
+
+        assert!(matches!(
+            signer.try_gen_sign(&long_identifier, Duration::days(1), None),
+            Err(TlsSigError::InvalidIdentifier(_))
+        ));
+
+        let lax_signer = signer.with_identifier_validation(false);
+        let sig = lax_signer
+            .try_gen_sign(&long_identifier, Duration::days(1), None)
+            .unwrap();
+        assert!(lax_signer.verify_sign(&long_identifier, &sig).is_ok());
+    }
+
+    #[test]
+    fn test_gen_sign_until_past() {
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let signer =
+            TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).with_clock(FixedClock(mock_curr_time.into()));
+
+        assert!(matches!(
+            signer.gen_sign_until("0", mock_curr_time, None),
+            Err(TlsSigError::InvalidExpire(0))
+        ));
+        assert!(matches!(
+            signer.gen_sign_until("0", mock_curr_time - Duration::seconds(5), None),
+            Err(TlsSigError::InvalidExpire(-5))
+        ));
+    }
+
+    #[test]
+    fn test_verify_binary_userbuf() {
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
+        let sig = signer
+            .gen_sign_with_userbuf_bytes("0", Duration::days(1), &[0, 159, 146, 150])
+            .unwrap();
+
+        assert!(signer.verify_sign("0", &sig).is_ok());
+    }
+
+    #[test]
+    fn test_sig_length_limit_round_trip() {
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
+        // incompressible bytes, so the sig grows with every byte of userbuf
+        let mut seed = 1u32;
+        let userbuf: Vec<u8> = (0..4000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                (seed >> 16) as u8
+            })
+            .collect();
+
+        let (mut longest, mut refused) = (0, false);
+        for len in 2800..3300 {
+            match signer.gen_sign_with_userbuf_bytes("0", Duration::days(1), &userbuf[..len]) {
+                Ok(sig) => {
+                    assert!(signer.verify_sign("0", &sig).is_ok());
+                    assert!(sig.parse::<super::UserSig>().is_ok());
+                    longest = longest.max(sig.len());
+                }
+                Err(TlsSigError::SigTooLong(len)) => {
+                    assert!(len > super::payload::MAX_SIG_LEN);
+                    refused = true;
+                }
+                Err(e) => panic!("unexpected error: {}", e),
+            }
+        }
+
+        // both sides of the limit are hit
+        assert!(refused);
+        assert!(longest > super::payload::MAX_SIG_LEN - 8);
+        assert!(longest <= super::payload::MAX_SIG_LEN);
+    }
+
+    #[test]
+    fn test_verify_sign_with_clock() {
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let signer =
+            TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).with_clock(FixedClock(mock_curr_time.into()));
+        let sig = signer.gen_sign("0", Duration::days(1), None);
+
+        assert!(signer.verify_sign("0", &sig).is_ok());
+
+        let later = signer.with_clock(FixedClock((mock_curr_time + Duration::days(2)).into()));
+        assert!(matches!(
+            later.verify_sign("0", &sig),
+            Err(TlsSigError::Expired)
+        ));
+    }
+
+    #[test]
+    fn test_verify_sign_with_leeway() {
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY)
+            .with_leeway(std::time::Duration::from_secs(300));
+        let sig = signer
+            .gen_sign_at("0", mock_curr_time, Duration::hours(1), None)
+            .unwrap();
+
+        let early = mock_curr_time - Duration::seconds(300);
+        let too_early = mock_curr_time - Duration::seconds(301);
+        let late = mock_curr_time + Duration::seconds(3600 + 300);
+        let too_late = mock_curr_time + Duration::seconds(3600 + 301);
+
+        assert!(signer.verify_sign_with_time("0", &sig, early).is_ok());
+        assert!(signer.verify_sign_with_time("0", &sig, late).is_ok());
+        assert!(matches!(
+            signer.verify_sign_with_time("0", &sig, too_early),
+            Err(TlsSigError::NotYetValid)
+        ));
+        assert!(matches!(
+            signer.verify_sign_with_time("0", &sig, too_late),
+            Err(TlsSigError::Expired)
+        ));
+    }
+
+    #[test]
+    fn test_verify_official_sig() {
+        log_init();
+
+        // url-safe base64 sig produced by the python version
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
+        let mock_sig = "eJyrVgrxCdYrSy1SslIy0jNQ0gHzM1NS80oy0zLBwjDB4pTsxIKCzBQlK0MTAyiAyKRWFGQWpQLFTU1NjeCiJZm5YDEzS0tDAyOYaHFmOtBM54KsUqeUwGLLqPK8UndHryCL-Lx8-1RPy7Ty1AijMk9f30hLk0x-r3CvSFulWgAPYy*9";
+
+        assert!(signer
+            .verify_sign_with_time("0", mock_sig, mock_curr_time)
+            .is_ok());
+    }
+
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn test_sign_generation_matches_official_sdk() {
+        use super::Base64Alphabet;
+
+        // the same bytes as the sigs of the python version, which only
+        // differ in the url-safe base64 alphabet
+        let official = |sig: &str| sig.replace('*', "+").replace('-', "/").replace('_', "=");
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY).with_compressor(super::Deflate);
+
+        assert_eq!(
+            signer
+                .gen_sign_at("0", mock_curr_time, Duration::days(180), None)
+                .unwrap()
+                .as_str(),
+            official("eJyrVgrxCdYrSy1SslIy0jNQ0gHzM1NS80oy0zLBwjDB4pTsxIKCzBQlK0MTAyiAyKRWFGQWpQLFTU1NjeCiJZm5YDEzS0tDAyOYaHFmOtBM54KsUqeUwGLLqPK8UndHryCL-Lx8-1RPy7Ty1AijMk9f30hLk0x-r3CvSFulWgAPYy*9")
+        );
+        assert_eq!(
+            signer
+                .gen_sign_at("0", mock_curr_time, Duration::days(180), Some(MOCK_USERBUF))
+                .unwrap()
+                .as_str(),
+            official("eJw9zEELwiAcBfDv4jmGs1lu0GkRUd0chMeWbv2zDdEZg*i7J5a92-s9eC-UnHj2VBZViGQYLWIHqcYJOoic0El9MQYkqvIC--Jd1GzAquCUUvLXCYZoq7LMMUnqnbKt78KvOB-u6Rr6AG299PTq3YP3bOvWR0HZcOO6sbMf7c5TLLXUtdgXwDbo-QEmHTZF")
+        );
+
+        // and byte for byte in their alphabet
+        let url_safe = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY)
+            .with_compressor(super::Deflate)
+            .with_base64_alphabet(Base64Alphabet::TencentUrlSafe);
+        let sig = url_safe
+            .gen_sign_at("0", mock_curr_time, Duration::days(180), None)
+            .unwrap();
+        assert_eq!(
+            sig.as_str(),
+            "eJyrVgrxCdYrSy1SslIy0jNQ0gHzM1NS80oy0zLBwjDB4pTsxIKCzBQlK0MTAyiAyKRWFGQWpQLFTU1NjeCiJZm5YDEzS0tDAyOYaHFmOtBM54KsUqeUwGLLqPK8UndHryCL-Lx8-1RPy7Ty1AijMk9f30hLk0x-r3CvSFulWgAPYy*9"
+        );
+        assert!(signer
+            .verify_sign_with_time("0", &sig, mock_curr_time)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_verify_malformed_sig() {
+        let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
+
+        assert!(matches!(
+            signer.verify_sign("0", "not a sig"),
+            Err(TlsSigError::MalformedSig(_))
+        ));
+    }
+
     // Ignore for lacking of expect output
     #[test]
     #[ignore]
@@ -184,14 +2150,16 @@ mod test {
 
         // the great moment of the 70th anniversary of the founding of new China!
         // timestamp_millis = 1569910200000
-        let mock_curr_time = Utc.ymd(2019, 10, 1).and_hms(6, 10, 0);
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
         let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
 
         // mock sig generated from python version
         let mock_sig = "eJyrVgrxCdYrSy1SslIy0jNQ0gHzM1NS80oy0zLBwjDB4pTsxIKCzBQlK0MTAyiAyKRWFGQWpQLFTU1NjeCiJZm5YDEzS0tDAyOYaHFmOtBM54KsUqeUwGLLqPK8UndHryCL-Lx8-1RPy7Ty1AijMk9f30hLk0x-r3CvSFulWgAPYy*9";
 
         assert_eq!(
-            &signer.gen_sign_with_time("0", mock_curr_time, Duration::days(180), None),
+            &signer
+                .gen_sign_at("0", mock_curr_time, Duration::days(180), None)
+                .unwrap(),
             mock_sig
         );
     }
@@ -204,18 +2172,15 @@ mod test {
 
         // the great moment of the 70th anniversary of the founding of new China!
         // timestamp_millis = 1569910200000
-        let mock_curr_time = Utc.ymd(2019, 10, 1).and_hms(6, 10, 0);
+        let mock_curr_time = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
         let signer = TlsSigApiVer2::new(MOCK_APPID, MOCK_KEY);
 
         // mock sig generated from python version
         let mock_sig_with_buf = "eJw9zEELwiAcBfDv4jmGs1lu0GkRUd0chMeWbv2zDdEZg*i7J5a92-s9eC-UnHj2VBZViGQYLWIHqcYJOoic0El9MQYkqvIC--Jd1GzAquCUUvLXCYZoq7LMMUnqnbKt78KvOB-u6Rr6AG299PTq3YP3bOvWR0HZcOO6sbMf7c5TLLXUtdgXwDbo-QEmHTZF";
         assert_eq!(
-            &signer.gen_sign_with_time(
-                "0",
-                mock_curr_time,
-                Duration::days(180),
-                Some(MOCK_USERBUF)
-            ),
+            &signer
+                .gen_sign_at("0", mock_curr_time, Duration::days(180), Some(MOCK_USERBUF))
+                .unwrap(),
             mock_sig_with_buf
         );
     }
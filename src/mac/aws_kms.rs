@@ -0,0 +1,334 @@
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use serde_json::{json, Value};
+
+use crate::b64;
+use crate::cloudsign::{hex, hmac, sha256_hex};
+use crate::key::read_var;
+use crate::mac::{MacBackend, MacFuture};
+use crate::timing::{rfc3339, UnixTime};
+use crate::{Result, TlsSigError};
+
+const SERVICE: &str = "kms";
+const TARGET: &str = "TrentService.GenerateMac";
+const CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+
+/// Computes the HMAC with AWS KMS `GenerateMac`, so that the key never
+/// leaves KMS.
+///
+/// The application key must be imported into an `HMAC_256` KMS key, as key
+/// material of KMS generated keys can not be exported to the Tencent console.
+/// Requests are signed with AWS Signature Version 4.
+///
+/// Requires the `aws-kms` feature. The sync methods of the signer block on
+/// the request, use the `_async` ones inside an async runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tls_sig_api::{AwsKmsMac, TlsSigApiVer2};
+/// use std::time::Duration;
+///
+/// // with AWS_REGION, AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY set
+/// let kms = AwsKmsMac::from_env("alias/tls-sig-key").unwrap();
+/// let signer = TlsSigApiVer2::from_mac_backend(1400000000, kms);
+/// let sig = signer.gen_sign("10086", Duration::from_secs(7200), None);
+/// ```
+pub struct AwsKmsMac {
+    region: String,
+    key_id: String,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    endpoint: String,
+    client: reqwest::Client,
+    // built lazily, as building it inside an async runtime panics
+    blocking_client: OnceLock<reqwest::blocking::Client>,
+}
+
+impl AwsKmsMac {
+    pub fn new(region: &str, key_id: &str, access_key_id: &str, secret_access_key: &str) -> Self {
+        AwsKmsMac {
+            region: region.to_string(),
+            key_id: key_id.to_string(),
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            session_token: None,
+            endpoint: format!("https://kms.{}.amazonaws.com/", region),
+            client: reqwest::Client::new(),
+            blocking_client: OnceLock::new(),
+        }
+    }
+
+    /// read the region and credentials from the standard AWS env vars:
+    /// `AWS_REGION`, `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and the
+    /// optional `AWS_SESSION_TOKEN`.
+    pub fn from_env(key_id: &str) -> Result<Self> {
+        let kms = AwsKmsMac::new(
+            &read_var("AWS_REGION")?,
+            key_id,
+            &read_var("AWS_ACCESS_KEY_ID")?,
+            &read_var("AWS_SECRET_ACCESS_KEY")?,
+        );
+
+        Ok(match read_var("AWS_SESSION_TOKEN") {
+            Ok(token) => kms.with_session_token(&token),
+            Err(_) => kms,
+        })
+    }
+
+    /// the session token of temporary credentials.
+    pub fn with_session_token(mut self, token: &str) -> Self {
+        self.session_token = Some(token.to_string());
+        self
+    }
+
+    /// send requests to `endpoint` instead of the public regional one, e.g. a
+    /// VPC endpoint.
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+
+    /// the signed headers and body of a `GenerateMac` request.
+    fn request(&self, content: &[u8]) -> Result<(Vec<(&'static str, String)>, String)> {
+        let body = json!({
+            "KeyId": self.key_id,
+            "MacAlgorithm": "HMAC_SHA_256",
+            "Message": b64::encode(content),
+        })
+        .to_string();
+
+        let url = reqwest::Url::parse(&self.endpoint)
+            .map_err(|e| TlsSigError::MacUnavailable(format!("invalid endpoint: {}", e)))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => {
+                return Err(TlsSigError::MacUnavailable(
+                    "invalid endpoint: no host".to_string(),
+                ))
+            }
+        };
+        let amz_date = amz_date(SystemTime::now().unix_timestamp());
+
+        // sorted by name, as SigV4 requires
+        let mut headers = vec![
+            ("content-type", CONTENT_TYPE.to_string()),
+            ("host", host),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        headers.push(("x-amz-target", TARGET.to_string()));
+
+        let authorization = sigv4_authorization(
+            &Credentials {
+                access_key_id: &self.access_key_id,
+                secret_access_key: &self.secret_access_key,
+                region: &self.region,
+                service: SERVICE,
+            },
+            &amz_date,
+            "POST",
+            url.path(),
+            "",
+            &headers,
+            body.as_bytes(),
+        );
+        headers.retain(|(name, _)| *name != "host");
+        headers.push(("authorization", authorization));
+
+        Ok((headers, body))
+    }
+}
+
+impl MacBackend for AwsKmsMac {
+    fn hmac_sha256(&self, content: &[u8]) -> Result<Vec<u8>> {
+        let (headers, body) = self.request(content)?;
+        let client = self
+            .blocking_client
+            .get_or_init(reqwest::blocking::Client::new);
+
+        let mut req = client.post(&self.endpoint).body(body);
+        for (name, value) in headers {
+            req = req.header(name, value);
+        }
+        let resp = req.send().map_err(unavailable)?;
+        let status = resp.status();
+        let body = resp.text().map_err(unavailable)?;
+
+        parse_response(status, &body)
+    }
+
+    fn hmac_sha256_async<'a>(&'a self, content: &'a [u8]) -> MacFuture<'a> {
+        Box::pin(async move {
+            let (headers, body) = self.request(content)?;
+
+            let mut req = self.client.post(&self.endpoint).body(body);
+            for (name, value) in headers {
+                req = req.header(name, value);
+            }
+            let resp = req.send().await.map_err(unavailable)?;
+            let status = resp.status();
+            let body = resp.text().await.map_err(unavailable)?;
+
+            parse_response(status, &body)
+        })
+    }
+}
+
+fn unavailable(e: reqwest::Error) -> TlsSigError {
+    TlsSigError::MacUnavailable(format!("KMS request failed: {}", e))
+}
+
+fn parse_response(status: reqwest::StatusCode, body: &str) -> Result<Vec<u8>> {
+    let value: Value = serde_json::from_str(body).map_err(|_| {
+        TlsSigError::MacUnavailable(format!("KMS responded {} with {}", status, body))
+    })?;
+
+    if !status.is_success() {
+        let kind = value["__type"].as_str().unwrap_or("unknown error");
+        let message = value["message"]
+            .as_str()
+            .or_else(|| value["Message"].as_str())
+            .unwrap_or("");
+        return Err(TlsSigError::MacUnavailable(format!(
+            "KMS responded {}: {} {}",
+            status, kind, message
+        )));
+    }
+
+    value["Mac"]
+        .as_str()
+        .and_then(|mac| b64::decode(mac).ok())
+        .ok_or_else(|| TlsSigError::MacUnavailable("KMS response has no valid Mac".to_string()))
+}
+
+struct Credentials<'a> {
+    access_key_id: &'a str,
+    secret_access_key: &'a str,
+    region: &'a str,
+    service: &'a str,
+}
+
+/// `20150830T123600Z` from a unix timestamp.
+fn amz_date(timestamp: i64) -> String {
+    rfc3339(timestamp).replace(['-', ':'], "")
+}
+
+/// the `Authorization` header of AWS Signature Version 4. `headers` must be
+/// lowercase and sorted by name.
+fn sigv4_authorization(
+    credentials: &Credentials,
+    amz_date: &str,
+    method: &str,
+    path: &str,
+    query: &str,
+    headers: &[(&str, String)],
+    body: &[u8],
+) -> String {
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method,
+        path,
+        query,
+        canonical_headers,
+        signed_headers,
+        sha256_hex(body)
+    );
+
+    let date = &amz_date[..8];
+    let scope = format!(
+        "{}/{}/{}/aws4_request",
+        date, credentials.region, credentials.service
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        sha256_hex(canonical_request.as_bytes())
+    );
+
+    let secret = format!("AWS4{}", credentials.secret_access_key);
+    let signing_key = [
+        date,
+        credentials.region,
+        credentials.service,
+        "aws4_request",
+    ]
+    .iter()
+    .fold(secret.into_bytes(), |key, part| hmac(&key, part.as_bytes()));
+    let signature = hex(&hmac(&signing_key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{amz_date, parse_response, sigv4_authorization, Credentials};
+    use crate::TlsSigError;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_sigv4_authorization() {
+        // the example from AWS Signature Version 4 docs
+        let credentials = Credentials {
+            access_key_id: "AKIDEXAMPLE",
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            region: "us-east-1",
+            service: "iam",
+        };
+        let amz_date = amz_date(1440938160);
+        assert_eq!(amz_date, "20150830T123600Z");
+
+        let headers = [
+            (
+                "content-type",
+                "application/x-www-form-urlencoded; charset=utf-8".to_string(),
+            ),
+            ("host", "iam.amazonaws.com".to_string()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        assert_eq!(
+            sigv4_authorization(
+                &credentials,
+                &amz_date,
+                "GET",
+                "/",
+                "Action=ListUsers&Version=2010-05-08",
+                &headers,
+                b"",
+            ),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-date, \
+             Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let ok = r#"{"KeyId":"arn:aws:kms:us-east-1:111122223333:key/1","Mac":"YWJj","MacAlgorithm":"HMAC_SHA_256"}"#;
+        assert_eq!(parse_response(StatusCode::OK, ok).unwrap(), b"abc");
+
+        let denied = r#"{"__type":"AccessDeniedException","message":"not allowed"}"#;
+        assert!(matches!(
+            parse_response(StatusCode::BAD_REQUEST, denied),
+            Err(TlsSigError::MacUnavailable(reason)) if reason.contains("AccessDeniedException")
+        ));
+    }
+}
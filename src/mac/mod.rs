@@ -0,0 +1,320 @@
+use std::future::Future;
+use std::pin::Pin;
+#[cfg(not(feature = "zeroize"))]
+use std::sync::Arc;
+
+#[cfg(not(feature = "zeroize"))]
+use arc_swap::ArcSwapOption;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{Key, KeyProvider, Result, SigDigest, TlsSigError};
+
+#[cfg(feature = "aws-kms")]
+mod aws_kms;
+
+#[cfg(feature = "aws-kms")]
+pub use self::aws_kms::AwsKmsMac;
+
+/// The future returned by [`MacBackend::hmac_sha256_async`].
+///
+/// [`MacBackend::hmac_sha256_async`]: trait.MacBackend.html#method.hmac_sha256_async
+pub type MacFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<u8>>> + Send + 'a>>;
+
+/// The future returned by [`MacBackend::verify_hmac_sha256_async`].
+///
+/// [`MacBackend::verify_hmac_sha256_async`]: trait.MacBackend.html#method.verify_hmac_sha256_async
+pub type VerifyFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// Computes the HMAC-SHA256 of the content to be signed.
+///
+/// By default the signer computes it in process with the key from a
+/// [`KeyProvider`], see [`HmacBackend`]. Implement this trait to compute it
+/// elsewhere instead, e.g. in a cloud KMS, so that the raw secret never lives
+/// in process memory.
+///
+/// [`KeyProvider`]: trait.KeyProvider.html
+/// [`HmacBackend`]: struct.HmacBackend.html
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{HmacBackend, MacBackend, Result, TlsSigApiVer2};
+/// use std::time::Duration;
+///
+/// struct Audited(HmacBackend);
+///
+/// impl MacBackend for Audited {
+///     fn hmac_sha256(&self, content: &[u8]) -> Result<Vec<u8>> {
+///         println!("signing {} bytes", content.len());
+///         self.0.hmac_sha256(content)
+///     }
+/// }
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = TlsSigApiVer2::from_mac_backend(0, Audited(HmacBackend::new(mock_key)));
+/// let sig = signer.gen_sign("10086", Duration::from_secs(7200), None);
+/// assert!(signer.verify_sign("10086", &sig).is_ok());
+/// ```
+pub trait MacBackend: Send + Sync {
+    /// the raw HMAC-SHA256 digest of `content`.
+    fn hmac_sha256(&self, content: &[u8]) -> Result<Vec<u8>>;
+
+    /// the async variant of [`hmac_sha256`], used by the `_async` methods of
+    /// the signer. Remote backends should override it to avoid blocking the
+    /// executor, the default one simply calls [`hmac_sha256`].
+    ///
+    /// [`hmac_sha256`]: #tymethod.hmac_sha256
+    fn hmac_sha256_async<'a>(&'a self, content: &'a [u8]) -> MacFuture<'a> {
+        let digest = self.hmac_sha256(content);
+        Box::pin(async move { digest })
+    }
+
+    /// check `digest` against the HMAC of `content` in constant time, see
+    /// [`SigDigest`], failing
+    /// with [`SignatureMismatch`] if it does not match.
+    ///
+    /// Backends holding more than one key, like [`KeyRing`], override it to
+    /// accept any of the keys.
+    ///
+    /// [`SigDigest`]: struct.SigDigest.html
+    /// [`SignatureMismatch`]: enum.TlsSigError.html#variant.SignatureMismatch
+    /// [`KeyRing`]: struct.KeyRing.html
+    fn verify_hmac_sha256(&self, content: &[u8], digest: &[u8]) -> Result<()> {
+        digests_match(self.hmac_sha256(content)?, digest)
+    }
+
+    /// the async variant of [`verify_hmac_sha256`].
+    ///
+    /// [`verify_hmac_sha256`]: #method.verify_hmac_sha256
+    fn verify_hmac_sha256_async<'a>(
+        &'a self,
+        content: &'a [u8],
+        digest: &'a [u8],
+    ) -> VerifyFuture<'a> {
+        Box::pin(async move { digests_match(self.hmac_sha256_async(content).await?, digest) })
+    }
+}
+
+pub(crate) fn digests_match(expected: Vec<u8>, actual: &[u8]) -> Result<()> {
+    let expected = SigDigest::from(expected);
+    if expected == *actual {
+        Ok(())
+    } else {
+        Err(TlsSigError::SignatureMismatch)
+    }
+}
+
+/// Computes the HMAC in process with the key from a [`KeyProvider`].
+///
+/// The HMAC initialized with the key, i.e. with its inner and outer pads
+/// hashed, is kept and reused until the key changes, which saves two of the
+/// four SHA-256 blocks of signing a typical sig content. In the
+/// `hmac_sha256` bench, run by `cargo bench --bench sign -- hmac_sha256` on a
+/// single core, an HMAC took 0.85µs with the HMAC kept against 1.29µs with
+/// the `zeroize` feature, i.e. about a third less time.
+///
+/// The kept HMAC holds the key padded, from which the key is recovered, on
+/// the plain heap. So with the `zeroize` feature, and with `mlock` enabling
+/// it, the HMAC is not kept but initialized again on each call, trading the
+/// speed for the key not being kept outside the [`Key`] between calls.
+///
+/// [`KeyProvider`]: trait.KeyProvider.html
+/// [`Key`]: struct.Key.html
+pub struct HmacBackend<P = Key> {
+    key_provider: P,
+    #[cfg(not(feature = "zeroize"))]
+    schedule: ArcSwapOption<KeySchedule>,
+}
+
+/// the HMAC initialized with `key`.
+#[cfg(not(feature = "zeroize"))]
+struct KeySchedule {
+    key: Key,
+    mac: Hmac<Sha256>,
+}
+
+impl HmacBackend {
+    pub fn new(key: &str) -> Self {
+        HmacBackend::from_key_provider(Key::new(key))
+    }
+}
+
+impl<P: KeyProvider> HmacBackend<P> {
+    pub fn from_key_provider(key_provider: P) -> Self {
+        HmacBackend {
+            key_provider,
+            #[cfg(not(feature = "zeroize"))]
+            schedule: ArcSwapOption::empty(),
+        }
+    }
+
+    /// the HMAC initialized with `key`.
+    #[cfg(feature = "zeroize")]
+    fn keyed_mac(&self, key: Key) -> Result<Hmac<Sha256>> {
+        Hmac::<Sha256>::new_varkey(key.expose().as_bytes()).map_err(|_| TlsSigError::InvalidKey)
+    }
+
+    /// the HMAC initialized with `key`, reused if the key is the same as last
+    /// time.
+    #[cfg(not(feature = "zeroize"))]
+    fn keyed_mac(&self, key: Key) -> Result<Hmac<Sha256>> {
+        if let Some(schedule) = &*self.schedule.load() {
+            if schedule.key == key {
+                return Ok(schedule.mac.clone());
+            }
+        }
+
+        let mac = Hmac::<Sha256>::new_varkey(key.expose().as_bytes())
+            .map_err(|_| TlsSigError::InvalidKey)?;
+        self.schedule.store(Some(Arc::new(KeySchedule {
+            key,
+            mac: mac.clone(),
+        })));
+
+        Ok(mac)
+    }
+}
+
+impl<P: KeyProvider> MacBackend for HmacBackend<P> {
+    fn hmac_sha256(&self, content: &[u8]) -> Result<Vec<u8>> {
+        let mut mac = self.keyed_mac(self.key_provider.current_key())?;
+        mac.input(content);
+
+        Ok(mac.result().code().to_vec())
+    }
+
+    fn hmac_sha256_async<'a>(&'a self, content: &'a [u8]) -> MacFuture<'a> {
+        Box::pin(async move {
+            let mut mac = self.keyed_mac(self.key_provider.current_key_async().await)?;
+            mac.input(content);
+
+            Ok(mac.result().code().to_vec())
+        })
+    }
+}
+
+impl<T: MacBackend + ?Sized> MacBackend for std::sync::Arc<T> {
+    fn hmac_sha256(&self, content: &[u8]) -> Result<Vec<u8>> {
+        (**self).hmac_sha256(content)
+    }
+
+    fn hmac_sha256_async<'a>(&'a self, content: &'a [u8]) -> MacFuture<'a> {
+        (**self).hmac_sha256_async(content)
+    }
+
+    fn verify_hmac_sha256(&self, content: &[u8], digest: &[u8]) -> Result<()> {
+        (**self).verify_hmac_sha256(content, digest)
+    }
+
+    fn verify_hmac_sha256_async<'a>(
+        &'a self,
+        content: &'a [u8],
+        digest: &'a [u8],
+    ) -> VerifyFuture<'a> {
+        (**self).verify_hmac_sha256_async(content, digest)
+    }
+}
+
+impl<T: MacBackend + ?Sized> MacBackend for Box<T> {
+    fn hmac_sha256(&self, content: &[u8]) -> Result<Vec<u8>> {
+        (**self).hmac_sha256(content)
+    }
+
+    fn hmac_sha256_async<'a>(&'a self, content: &'a [u8]) -> MacFuture<'a> {
+        (**self).hmac_sha256_async(content)
+    }
+
+    fn verify_hmac_sha256(&self, content: &[u8], digest: &[u8]) -> Result<()> {
+        (**self).verify_hmac_sha256(content, digest)
+    }
+
+    fn verify_hmac_sha256_async<'a>(
+        &'a self,
+        content: &'a [u8],
+        digest: &'a [u8],
+    ) -> VerifyFuture<'a> {
+        (**self).verify_hmac_sha256_async(content, digest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HmacBackend, MacBackend};
+
+    #[test]
+    fn test_hmac_backend() {
+        // RFC 4231 test case 2
+        let backend = HmacBackend::new("Jefe");
+        let digest = backend
+            .hmac_sha256(b"what do ya want for nothing?")
+            .unwrap();
+
+        assert_eq!(
+            digest
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_hmac_backend_async() {
+        use crate::{Key, KeyFuture, KeyProvider};
+
+        /// hands out the key only asynchronously.
+        struct AsyncOnly;
+
+        impl KeyProvider for AsyncOnly {
+            fn current_key(&self) -> Key {
+                Key::new("")
+            }
+
+            fn current_key_async(&self) -> KeyFuture<'_> {
+                Box::pin(async { Key::new("Jefe") })
+            }
+        }
+
+        let content = b"what do ya want for nothing?";
+        let digest = futures::executor::block_on(
+            HmacBackend::from_key_provider(AsyncOnly).hmac_sha256_async(content),
+        )
+        .unwrap();
+        assert_eq!(
+            digest,
+            HmacBackend::new("Jefe").hmac_sha256(content).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hmac_backend_key_change() {
+        use crate::{Key, KeyProvider};
+        use arc_swap::ArcSwap;
+        use std::sync::Arc;
+
+        struct Swappable(Arc<ArcSwap<Key>>);
+
+        impl KeyProvider for Swappable {
+            fn current_key(&self) -> Key {
+                Key::clone(&self.0.load())
+            }
+        }
+
+        let key = Arc::new(ArcSwap::from_pointee(Key::new("Jefe")));
+        let backend = HmacBackend::from_key_provider(Swappable(Arc::clone(&key)));
+        let content = b"what do ya want for nothing?";
+
+        let jefe = backend.hmac_sha256(content).unwrap();
+        assert_eq!(backend.hmac_sha256(content).unwrap(), jefe);
+
+        // the kept HMAC is not reused for another key
+        key.store(Arc::new(Key::new("other")));
+        let other = backend.hmac_sha256(content).unwrap();
+        assert_ne!(other, jefe);
+        assert_eq!(
+            other,
+            HmacBackend::new("other").hmac_sha256(content).unwrap()
+        );
+    }
+}
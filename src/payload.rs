@@ -0,0 +1,372 @@
+use log::*;
+use miniz_oxide::inflate::decompress_to_vec_zlib_with_limit;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::Value;
+use std::time::{Duration, SystemTime};
+
+use crate::b64;
+use crate::timing::{self, UnixTime};
+use crate::{Base64Alphabet, Result, SigDigest, TlsSigError};
+
+/// the longest sig decoded, far longer than any sig signed with a usual
+/// userbuf, so that sigs sent by clients can not make decoding costly.
+/// Longer sigs are refused when signing too.
+pub(crate) const MAX_SIG_LEN: usize = 4 * 1024;
+
+/// the most bytes a sig is inflated to, so that a small zlib bomb can not
+/// allocate without limit.
+pub(crate) const MAX_SIG_JSON_LEN: usize = 16 * 1024;
+
+/// The payload carried by a user sign.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigPayload {
+    /// Version of the TLS sig API, `"2.0"` for now.
+    pub ver: String,
+    pub identifier: String,
+    pub sdkappid: u64,
+    /// Issue time as unix timestamp in seconds.
+    pub time: i64,
+    /// Validity period in seconds, starting from `time`.
+    pub expire: i64,
+    /// The userbuf, still base64-encoded as it is in the sig.
+    pub userbuf: Option<String>,
+    /// The base64-encoded HMAC-SHA256 digest.
+    pub sig: String,
+}
+
+impl SigPayload {
+    /// When the sig is issued.
+    pub fn issued_at(&self) -> SystemTime {
+        timing::system_time(self.time)
+    }
+
+    /// When the sig expires.
+    pub fn expires_at(&self) -> SystemTime {
+        timing::system_time(self.expires_at_timestamp())
+    }
+
+    /// Whether the sig has expired at `now`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{decode_sign, TlsSigApiVer2};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = TlsSigApiVer2::new(0, mock_key);
+    ///
+    /// let sig = signer.gen_sign("10086", Duration::from_secs(7200), None);
+    /// let payload = decode_sign(&sig).unwrap();
+    ///
+    /// let now = SystemTime::now();
+    /// assert!(!payload.is_expired(now));
+    /// assert!(payload.is_expired(now + Duration::from_secs(7201)));
+    /// assert!(payload.remaining_validity(now) <= Duration::from_secs(7200));
+    /// ```
+    pub fn is_expired<T: UnixTime>(&self, now: T) -> bool {
+        now.unix_timestamp() > self.expires_at_timestamp()
+    }
+
+    /// How long the sig stays valid since `now`, zero if it has expired.
+    pub fn remaining_validity<T: UnixTime>(&self, now: T) -> Duration {
+        let remaining = self
+            .expires_at_timestamp()
+            .saturating_sub(now.unix_timestamp());
+        Duration::from_secs(remaining.max(0) as u64)
+    }
+
+    fn expires_at_timestamp(&self) -> i64 {
+        self.time.saturating_add(self.expire)
+    }
+
+    /// base64-decodes the userbuf into raw bytes.
+    pub fn userbuf_bytes(&self) -> Result<Option<Vec<u8>>> {
+        self.userbuf
+            .as_ref()
+            .map(|buf| {
+                b64::decode(buf)
+                    .map_err(|e| TlsSigError::MalformedSig(format!("invalid TLS.userbuf: {}", e)))
+            })
+            .transpose()
+    }
+
+    /// base64-decodes the HMAC-SHA256 digest, to be compared in constant
+    /// time.
+    pub fn digest(&self) -> Result<SigDigest> {
+        b64::decode(&self.sig)
+            .map(SigDigest::from)
+            .map_err(|e| TlsSigError::MalformedSig(format!("invalid TLS.sig: {}", e)))
+    }
+
+    fn from_json(dict: &Value) -> Result<Self> {
+        let field = |name: &str| {
+            dict.get(name)
+                .ok_or_else(|| TlsSigError::MalformedSig(format!("missing field {}", name)))
+        };
+        let str_field = |name: &str| {
+            field(name)?
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| TlsSigError::MalformedSig(format!("{} is not a string", name)))
+        };
+        // Some of the official SDKs put numbers in strings.
+        let int_field = |name: &str| {
+            let value = field(name)?;
+            value
+                .as_i64()
+                .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+                .ok_or_else(|| TlsSigError::MalformedSig(format!("{} is not an integer", name)))
+        };
+
+        let sdkappid = int_field("TLS.sdkappid")?;
+        if sdkappid < 0 {
+            return Err(TlsSigError::MalformedSig(
+                "TLS.sdkappid is negative".to_string(),
+            ));
+        }
+
+        Ok(SigPayload {
+            ver: str_field("TLS.ver")?,
+            identifier: str_field("TLS.identifier")?,
+            sdkappid: sdkappid as u64,
+            time: int_field("TLS.time")?,
+            expire: int_field("TLS.expire")?,
+            userbuf: match dict.get("TLS.userbuf") {
+                Some(_) => Some(str_field("TLS.userbuf")?),
+                None => None,
+            },
+            sig: str_field("TLS.sig")?,
+        })
+    }
+}
+
+/// The payload of a sig being issued, borrowing its fields.
+///
+/// It serializes straight into JSON with the fields in the same order as the
+/// official SDKs, without building a `serde_json::Value` first.
+pub(crate) struct RawPayload<'a> {
+    pub(crate) ver: &'a str,
+    pub(crate) identifier: &'a str,
+    pub(crate) sdkappid: u64,
+    pub(crate) expire: i64,
+    pub(crate) time: i64,
+    pub(crate) userbuf: Option<&'a str>,
+    pub(crate) sig: &'a str,
+}
+
+impl Serialize for RawPayload<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let len = if self.userbuf.is_some() { 7 } else { 6 };
+        let mut dict = serializer.serialize_struct("RawPayload", len)?;
+        dict.serialize_field("TLS.ver", self.ver)?;
+        dict.serialize_field("TLS.identifier", self.identifier)?;
+        dict.serialize_field("TLS.sdkappid", &self.sdkappid)?;
+        dict.serialize_field("TLS.expire", &self.expire)?;
+        dict.serialize_field("TLS.time", &self.time)?;
+        match self.userbuf {
+            Some(buf) => dict.serialize_field("TLS.userbuf", buf)?,
+            None => dict.skip_field("TLS.userbuf")?,
+        }
+        dict.serialize_field("TLS.sig", self.sig)?;
+        dict.end()
+    }
+}
+
+/// decode a user sign into its payload **without** verifying it.
+///
+/// Both the standard base64 alphabet and the url-safe one used by the
+/// official SDKs (`*`, `-` and `_`) are accepted.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "chrono")] {
+/// use tls_sig_api::{decode_sign, TlsSigApiVer2};
+/// use chrono::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = TlsSigApiVer2::new(1400000000, mock_key);
+///
+/// let sig = signer.gen_sign("10086", Duration::hours(2), None);
+/// let payload = decode_sign(&sig).unwrap();
+/// assert_eq!(payload.identifier, "10086");
+/// assert_eq!(payload.sdkappid, 1400000000);
+/// assert_eq!(payload.expire, 7200);
+/// # }
+/// ```
+pub fn decode_sign(sig: &str) -> Result<SigPayload> {
+    SigPayload::from_json(&decode_sig_json(sig)?)
+}
+
+/// decode the payload of a debug sig from [`gen_debug_sign`], which is not
+/// compressed. The HMAC is not verified.
+///
+/// [`gen_debug_sign`]: struct.TlsSigApiVer2.html#method.gen_debug_sign
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "chrono")] {
+/// use tls_sig_api::{decode_debug_sign, TlsSigApiVer2};
+/// use chrono::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = TlsSigApiVer2::new(1400000000, mock_key);
+///
+/// let debug_sig = signer.gen_debug_sign("10086", Duration::hours(2), None).unwrap();
+/// assert_eq!(decode_debug_sign(&debug_sig).unwrap().expire, 7200);
+/// # }
+/// ```
+pub fn decode_debug_sign(sig: &str) -> Result<SigPayload> {
+    let raw_sig = decode_base64(sig)?;
+    SigPayload::from_json(&parse_json(&raw_sig)?)
+}
+
+/// base64-decodes a sig, rejecting one longer than `MAX_SIG_LEN`.
+fn decode_base64(sig: &str) -> Result<Vec<u8>> {
+    if sig.len() > MAX_SIG_LEN {
+        return Err(TlsSigError::MalformedSig(format!(
+            "sig of {} bytes longer than {}",
+            sig.len(),
+            MAX_SIG_LEN
+        )));
+    }
+    Base64Alphabet::TencentUrlSafe.decode(sig)
+}
+
+/// base64-decodes and inflates a sig into its raw JSON payload, of at most
+/// `MAX_SIG_JSON_LEN` bytes.
+fn decode_sig_json(sig: &str) -> Result<Value> {
+    let compressed = decode_base64(sig)?;
+    let raw_sig = decompress_to_vec_zlib_with_limit(&compressed, MAX_SIG_JSON_LEN)
+        .map_err(|e| TlsSigError::MalformedSig(format!("invalid zlib stream: {}", e)))?;
+    debug!("decoded sig json: {}", String::from_utf8_lossy(&raw_sig));
+
+    parse_json(&raw_sig)
+}
+
+fn parse_json(raw_sig: &[u8]) -> Result<Value> {
+    serde_json::from_slice(raw_sig)
+        .map_err(|e| TlsSigError::MalformedSig(format!("invalid json: {}", e)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode_debug_sign, decode_sign, RawPayload, SigPayload};
+    use super::{MAX_SIG_JSON_LEN, MAX_SIG_LEN};
+    use crate::{Base64Alphabet, TlsSigError};
+    use miniz_oxide::deflate::compress_to_vec_zlib;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_decode_official_sig() {
+        // url-safe base64 sig produced by the python version
+        let mock_sig_with_buf = "eJw9zEELwiAcBfDv4jmGs1lu0GkRUd0chMeWbv2zDdEZg*i7J5a92-s9eC-UnHj2VBZViGQYLWIHqcYJOoic0El9MQYkqvIC--Jd1GzAquCUUvLXCYZoq7LMMUnqnbKt78KvOB-u6Rr6AG299PTq3YP3bOvWR0HZcOO6sbMf7c5TLLXUtdgXwDbo-QEmHTZF";
+        let payload = decode_sign(mock_sig_with_buf).unwrap();
+
+        assert_eq!(
+            payload,
+            SigPayload {
+                ver: "2.0".to_string(),
+                identifier: "0".to_string(),
+                sdkappid: 1400000000,
+                time: 1569910200,
+                expire: 180 * 24 * 3600,
+                userbuf: Some("YWJj".to_string()),
+                sig: "bC3u5cuslSg8Ds7KY58mhSkTrxunrFu50dkdkCYH4i8=".to_string(),
+            }
+        );
+        assert_eq!(payload.userbuf_bytes().unwrap(), Some(b"abc".to_vec()));
+    }
+
+    #[test]
+    fn test_serialize_raw_payload() {
+        let mut payload = RawPayload {
+            ver: "2.0",
+            identifier: "0",
+            sdkappid: 1400000000,
+            expire: 15552000,
+            time: 1569910200,
+            userbuf: None,
+            sig: "bC3u5cuslSg8Ds7KY58mhSkTrxunrFu50dkdkCYH4i8=",
+        };
+        assert_eq!(
+            serde_json::to_string(&payload).unwrap(),
+            r#"{"TLS.ver":"2.0","TLS.identifier":"0","TLS.sdkappid":1400000000,"TLS.expire":15552000,"TLS.time":1569910200,"TLS.sig":"bC3u5cuslSg8Ds7KY58mhSkTrxunrFu50dkdkCYH4i8="}"#
+        );
+
+        payload.userbuf = Some("YWJj");
+        assert_eq!(
+            serde_json::to_string(&payload).unwrap(),
+            r#"{"TLS.ver":"2.0","TLS.identifier":"0","TLS.sdkappid":1400000000,"TLS.expire":15552000,"TLS.time":1569910200,"TLS.userbuf":"YWJj","TLS.sig":"bC3u5cuslSg8Ds7KY58mhSkTrxunrFu50dkdkCYH4i8="}"#
+        );
+    }
+
+    #[test]
+    fn test_expiry_helpers() {
+        let payload = SigPayload {
+            ver: "2.0".to_string(),
+            identifier: "0".to_string(),
+            sdkappid: 1400000000,
+            time: 1569910200,
+            expire: 3600,
+            userbuf: None,
+            sig: String::new(),
+        };
+
+        assert_eq!(
+            payload.expires_at(),
+            UNIX_EPOCH + Duration::from_secs(1569913800)
+        );
+        assert!(!payload.is_expired(1569913800));
+        assert!(payload.is_expired(1569913801));
+        assert_eq!(
+            payload.remaining_validity(1569910200),
+            Duration::from_secs(3600)
+        );
+        assert_eq!(
+            payload.remaining_validity(1569999999),
+            Duration::from_secs(0)
+        );
+    }
+
+    #[test]
+    fn test_decode_malformed_sig() {
+        assert!(matches!(
+            decode_sign("not a sig"),
+            Err(TlsSigError::MalformedSig(_))
+        ));
+        // zlib-compressed `{}`
+        assert!(matches!(
+            decode_sign("eJyrrgUAAXUA+Q=="),
+            Err(TlsSigError::MalformedSig(_))
+        ));
+        // `{}` is missing every field
+        assert!(matches!(
+            decode_debug_sign("e30="),
+            Err(TlsSigError::MalformedSig(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_oversized_sig() {
+        let bomb = compress_to_vec_zlib(&vec![b' '; 64 * MAX_SIG_JSON_LEN], 10);
+        let sig = Base64Alphabet::TencentUrlSafe.encode(&bomb);
+        assert!(sig.len() <= MAX_SIG_LEN);
+        assert!(matches!(
+            decode_sign(&sig),
+            Err(TlsSigError::MalformedSig(_))
+        ));
+
+        let sig = "A".repeat(MAX_SIG_LEN + 4);
+        assert!(matches!(
+            decode_sign(&sig),
+            Err(TlsSigError::MalformedSig(_))
+        ));
+        assert!(matches!(
+            decode_debug_sign(&sig),
+            Err(TlsSigError::MalformedSig(_))
+        ));
+    }
+}
@@ -0,0 +1,117 @@
+use log::*;
+
+use crate::{Result, TlsSigError};
+
+/// The longest validity period of a user sign accepted by Tencent, 180 days.
+pub const MAX_EXPIRE_SECS: u64 = 180 * 24 * 3600;
+
+/// How the signer treats expires out of the allowed range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExpirePolicy {
+    /// Use the expire as is.
+    #[default]
+    Unchecked,
+    /// Reject expires out of `[min_secs, max_secs]` with
+    /// [`TlsSigError::InvalidExpire`].
+    ///
+    /// [`TlsSigError::InvalidExpire`]: enum.TlsSigError.html#variant.InvalidExpire
+    Reject { min_secs: u64, max_secs: u64 },
+    /// Clamp expires into `[min_secs, max_secs]`, with a warning logged.
+    Clamp { min_secs: u64, max_secs: u64 },
+}
+
+impl ExpirePolicy {
+    /// Reject expires longer than [`MAX_EXPIRE_SECS`].
+    ///
+    /// [`MAX_EXPIRE_SECS`]: constant.MAX_EXPIRE_SECS.html
+    pub fn reject_over_max() -> Self {
+        ExpirePolicy::Reject {
+            min_secs: 0,
+            max_secs: MAX_EXPIRE_SECS,
+        }
+    }
+
+    /// Clamp expires longer than [`MAX_EXPIRE_SECS`].
+    ///
+    /// [`MAX_EXPIRE_SECS`]: constant.MAX_EXPIRE_SECS.html
+    pub fn clamp_to_max() -> Self {
+        ExpirePolicy::Clamp {
+            min_secs: 0,
+            max_secs: MAX_EXPIRE_SECS,
+        }
+    }
+
+    /// check the expire in seconds against the policy, returning the expire
+    /// that should be signed.
+    pub(crate) fn apply(&self, expire: i64) -> Result<i64> {
+        match *self {
+            ExpirePolicy::Unchecked => Ok(expire),
+            ExpirePolicy::Reject { min_secs, max_secs } => {
+                if expire < to_i64(min_secs) || expire > to_i64(max_secs) {
+                    Err(TlsSigError::InvalidExpire(expire))
+                } else {
+                    Ok(expire)
+                }
+            }
+            ExpirePolicy::Clamp { min_secs, max_secs } => {
+                let clamped = expire.max(to_i64(min_secs)).min(to_i64(max_secs));
+                if clamped != expire {
+                    warn!("expire {}s is clamped to {}s", expire, clamped);
+                }
+                Ok(clamped)
+            }
+        }
+    }
+}
+
+fn to_i64(secs: u64) -> i64 {
+    if secs > i64::MAX as u64 {
+        i64::MAX
+    } else {
+        secs as i64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ExpirePolicy, MAX_EXPIRE_SECS};
+    use crate::TlsSigError;
+
+    const MAX: i64 = MAX_EXPIRE_SECS as i64;
+
+    #[test]
+    fn test_unchecked() {
+        assert_eq!(ExpirePolicy::Unchecked.apply(MAX + 1).unwrap(), MAX + 1);
+    }
+
+    #[test]
+    fn test_reject() {
+        let policy = ExpirePolicy::Reject {
+            min_secs: 60,
+            max_secs: MAX_EXPIRE_SECS,
+        };
+
+        assert_eq!(policy.apply(MAX).unwrap(), MAX);
+        assert_eq!(policy.apply(60).unwrap(), 60);
+        assert!(matches!(
+            policy.apply(MAX + 1),
+            Err(TlsSigError::InvalidExpire(e)) if e == MAX + 1
+        ));
+        assert!(matches!(
+            policy.apply(59),
+            Err(TlsSigError::InvalidExpire(59))
+        ));
+    }
+
+    #[test]
+    fn test_clamp() {
+        let policy = ExpirePolicy::Clamp {
+            min_secs: 60,
+            max_secs: MAX_EXPIRE_SECS,
+        };
+
+        assert_eq!(policy.apply(MAX + 1).unwrap(), MAX);
+        assert_eq!(policy.apply(1).unwrap(), 60);
+        assert_eq!(policy.apply(3600).unwrap(), 3600);
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::timing::UnixTime;
+use crate::{
+    validate_identifier, ExpireDuration, Result, SigInfo, TlsSigApiVer2, TlsSigError, UserSig,
+};
+
+/// how many identifiers are tracked at most by default.
+const DEFAULT_MAX_IDENTIFIERS: usize = 100_000;
+
+/// A signer capping how many sigs each identifier can be issued within a
+/// time window, to contain credential-minting abuse when the sig endpoint is
+/// semi-public.
+///
+/// Windows are fixed, starting at the first sig of the identifier, and timed
+/// by the clock of the signer. Failed issuances are not counted.
+///
+/// At most 100,000 identifiers are tracked at once, see
+/// [`with_max_identifiers`], so that minting sigs for ever new identifiers
+/// can not exhaust memory.
+///
+/// [`with_max_identifiers`]: #method.with_max_identifiers
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{RateLimitedSigner, TlsSigApiVer2, TlsSigError};
+/// use std::time::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = RateLimitedSigner::new(
+///     TlsSigApiVer2::new(0, mock_key),
+///     2,
+///     Duration::from_secs(60),
+/// );
+///
+/// let expire = Duration::from_secs(7200);
+/// assert!(signer.try_gen_sign("10086", expire, None).is_ok());
+/// assert!(signer.try_gen_sign("10086", expire, None).is_ok());
+/// assert!(matches!(
+///     signer.try_gen_sign("10086", expire, None),
+///     Err(TlsSigError::RateLimited(_))
+/// ));
+///
+/// // other identifiers have their own quota
+/// assert!(signer.try_gen_sign("10010", expire, None).is_ok());
+/// ```
+#[derive(Debug)]
+pub struct RateLimitedSigner {
+    signer: TlsSigApiVer2,
+    max_per_window: u32,
+    window: i64,
+    max_identifiers: usize,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    windows: HashMap<String, Window>,
+    last_pruned: i64,
+}
+
+#[derive(Debug)]
+struct Window {
+    start: i64,
+    issued: u32,
+}
+
+impl RateLimitedSigner {
+    /// allow at most `max_per_window` sigs per identifier in every `window`.
+    pub fn new(signer: TlsSigApiVer2, max_per_window: u32, window: Duration) -> Self {
+        RateLimitedSigner {
+            signer,
+            max_per_window,
+            window: window.expire_secs().max(1),
+            max_identifiers: DEFAULT_MAX_IDENTIFIERS,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// track at most `max` identifiers in their windows at once. Once that
+    /// many are tracked, new identifiers fail with [`RateLimited`] until a
+    /// window finishes.
+    ///
+    /// [`RateLimited`]: enum.TlsSigError.html#variant.RateLimited
+    pub fn with_max_identifiers(mut self, max: usize) -> Self {
+        self.max_identifiers = max;
+        self
+    }
+
+    /// the wrapped signer, e.g. for verifying. Sigs issued through it
+    /// directly are not limited.
+    pub fn signer(&self) -> &TlsSigApiVer2 {
+        &self.signer
+    }
+
+    /// generate user sign like [`TlsSigApiVer2::try_gen_sign`], failing with
+    /// [`RateLimited`] if `identifier` has used up its quota.
+    ///
+    /// [`TlsSigApiVer2::try_gen_sign`]: struct.TlsSigApiVer2.html#method.try_gen_sign
+    /// [`RateLimited`]: enum.TlsSigError.html#variant.RateLimited
+    pub fn try_gen_sign<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<UserSig> {
+        self.gen_sign_info(identifier, expire, userbuf)
+            .map(|info| info.sig)
+    }
+
+    /// generate user sign with its issuance metadata like
+    /// [`TlsSigApiVer2::gen_sign_info`], failing with [`RateLimited`] if
+    /// `identifier` has used up its quota.
+    ///
+    /// [`TlsSigApiVer2::gen_sign_info`]: struct.TlsSigApiVer2.html#method.gen_sign_info
+    /// [`RateLimited`]: enum.TlsSigError.html#variant.RateLimited
+    pub fn gen_sign_info<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<SigInfo> {
+        // invalid identifiers never take a slot, unless the signer accepts
+        // them
+        if self.signer.validate_identifier {
+            validate_identifier(identifier)?;
+        }
+        self.acquire(identifier)?;
+
+        let info = self.signer.gen_sign_info(identifier, expire, userbuf);
+        if info.is_err() {
+            self.release(identifier);
+        }
+
+        info
+    }
+
+    /// count a sig against the quota of `identifier`.
+    fn acquire(&self, identifier: &str) -> Result<()> {
+        let now = self.signer.clock.now().unix_timestamp();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        // forget finished windows now and then, so idle identifiers do not
+        // pile up
+        if now - state.last_pruned >= self.window {
+            let window = self.window;
+            state.windows.retain(|_, w| now - w.start < window);
+            state.last_pruned = now;
+        }
+
+        if !state.windows.contains_key(identifier) && state.windows.len() >= self.max_identifiers {
+            let window = self.window;
+            state.windows.retain(|_, w| now - w.start < window);
+            state.last_pruned = now;
+
+            if state.windows.len() >= self.max_identifiers {
+                let retry_after = state
+                    .windows
+                    .values()
+                    .map(|w| w.start + window - now)
+                    .min()
+                    .unwrap_or(window);
+                return Err(TlsSigError::RateLimited(Duration::from_secs(
+                    retry_after.max(0) as u64,
+                )));
+            }
+        }
+
+        let window = state
+            .windows
+            .entry(identifier.to_string())
+            .or_insert(Window {
+                start: now,
+                issued: 0,
+            });
+        if now - window.start >= self.window {
+            *window = Window {
+                start: now,
+                issued: 0,
+            };
+        }
+
+        if window.issued >= self.max_per_window {
+            let retry_after = window.start + self.window - now;
+            return Err(TlsSigError::RateLimited(Duration::from_secs(
+                retry_after.max(0) as u64,
+            )));
+        }
+        window.issued += 1;
+
+        Ok(())
+    }
+
+    /// give back a sig that failed to be issued.
+    fn release(&self, identifier: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(window) = state.windows.get_mut(identifier) {
+            window.issued = window.issued.saturating_sub(1);
+            if window.issued == 0 {
+                state.windows.remove(identifier);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RateLimitedSigner;
+    use crate::clock::ManualClock;
+    use crate::{TlsSigApiVer2, TlsSigError};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const MOCK_KEY: &str = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    const EXPIRE: Duration = Duration::from_secs(7200);
+
+    #[test]
+    fn test_rate_limit_window() {
+        let clock = ManualClock(Arc::new(AtomicU64::new(1569910200)));
+        let signer = RateLimitedSigner::new(
+            TlsSigApiVer2::new(1400000000, MOCK_KEY).with_clock(clock.clone()),
+            1,
+            Duration::from_secs(60),
+        );
+
+        assert!(signer.try_gen_sign("0", EXPIRE, None).is_ok());
+
+        clock.0.fetch_add(45, Ordering::SeqCst);
+        assert!(matches!(
+            signer.try_gen_sign("0", EXPIRE, None),
+            Err(TlsSigError::RateLimited(retry_after)) if retry_after == Duration::from_secs(15)
+        ));
+
+        clock.0.fetch_add(15, Ordering::SeqCst);
+        assert!(signer.try_gen_sign("0", EXPIRE, None).is_ok());
+    }
+
+    #[test]
+    fn test_failed_issuance_not_counted() {
+        let signer = RateLimitedSigner::new(
+            TlsSigApiVer2::new(1400000000, MOCK_KEY),
+            1,
+            Duration::from_secs(60),
+        );
+
+        assert!(matches!(
+            signer.try_gen_sign("0", Duration::from_secs(0), None),
+            Err(TlsSigError::InvalidExpire(0))
+        ));
+        assert!(signer.try_gen_sign("0", EXPIRE, None).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_identifier_not_tracked() {
+        let signer = RateLimitedSigner::new(
+            TlsSigApiVer2::new(1400000000, MOCK_KEY),
+            1,
+            Duration::from_secs(60),
+        );
+
+        let long = "x".repeat(1024);
+        assert!(matches!(
+            signer.try_gen_sign(&long, EXPIRE, None),
+            Err(TlsSigError::InvalidIdentifier(_))
+        ));
+        assert!(signer
+            .try_gen_sign("0", Duration::from_secs(0), None)
+            .is_err());
+        assert!(signer.state.lock().unwrap().windows.is_empty());
+    }
+
+    #[test]
+    fn test_identifier_validation_opt_out() {
+        let signer = RateLimitedSigner::new(
+            TlsSigApiVer2::new(1400000000, MOCK_KEY).with_identifier_validation(false),
+            1,
+            Duration::from_secs(60),
+        );
+
+        let long = "x".repeat(64);
+        assert!(signer.try_gen_sign(&long, EXPIRE, None).is_ok());
+        assert!(matches!(
+            signer.try_gen_sign(&long, EXPIRE, None),
+            Err(TlsSigError::RateLimited(_))
+        ));
+    }
+
+    #[test]
+    fn test_max_identifiers() {
+        let clock = ManualClock(Arc::new(AtomicU64::new(1569910200)));
+        let signer = RateLimitedSigner::new(
+            TlsSigApiVer2::new(1400000000, MOCK_KEY).with_clock(clock.clone()),
+            1,
+            Duration::from_secs(60),
+        )
+        .with_max_identifiers(2);
+
+        assert!(signer.try_gen_sign("0", EXPIRE, None).is_ok());
+        clock.0.fetch_add(10, Ordering::SeqCst);
+        assert!(signer.try_gen_sign("1", EXPIRE, None).is_ok());
+        assert!(matches!(
+            signer.try_gen_sign("2", EXPIRE, None),
+            Err(TlsSigError::RateLimited(retry_after)) if retry_after == Duration::from_secs(50)
+        ));
+
+        // the window of "0" finishes, making room for "2"
+        clock.0.fetch_add(50, Ordering::SeqCst);
+        assert!(signer.try_gen_sign("2", EXPIRE, None).is_ok());
+    }
+}
@@ -0,0 +1,117 @@
+use serde::Serialize;
+
+/// the body of `im_open_login_svc/account_import`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct AccountImport<'a> {
+    #[serde(rename = "UserID")]
+    pub(crate) user_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) nick: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) face_url: Option<&'a str>,
+}
+
+/// `{"UserID": ..}`, an account listed in the body of batch account
+/// endpoints.
+#[derive(Debug, Serialize)]
+pub(crate) struct UserId<'a> {
+    #[serde(rename = "UserID")]
+    pub(crate) user_id: &'a str,
+}
+
+pub(crate) fn user_ids<'a>(identifiers: &[&'a str]) -> Vec<UserId<'a>> {
+    identifiers
+        .iter()
+        .map(|&user_id| UserId { user_id })
+        .collect()
+}
+
+/// the most accounts imported by a `multiaccount_import`.
+pub(crate) const IMPORT_BATCH_SIZE: usize = 100;
+
+/// how many times `import_accounts_from` imports an account failed.
+pub(crate) const IMPORT_ATTEMPTS: u32 = 3;
+
+/// split `identifiers` into batches of `multiaccount_import`.
+pub(crate) fn import_batches<I>(identifiers: I) -> impl Iterator<Item = Vec<String>>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut identifiers = identifiers.into_iter();
+    std::iter::from_fn(move || {
+        let batch: Vec<String> = identifiers
+            .by_ref()
+            .take(IMPORT_BATCH_SIZE)
+            .map(|identifier| identifier.as_ref().to_string())
+            .collect();
+        Some(batch).filter(|batch| !batch.is_empty())
+    })
+}
+
+/// the body of `im_open_login_svc/account_delete`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct AccountDelete<'a> {
+    pub(crate) delete_item: Vec<UserId<'a>>,
+}
+
+/// the body of `im_open_login_svc/account_check`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct AccountCheck<'a> {
+    pub(crate) check_item: Vec<UserId<'a>>,
+}
+
+/// the body of `im_open_login_svc/multiaccount_import`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct MultiAccountImport<'a> {
+    pub(crate) accounts: &'a [&'a str],
+}
+
+#[cfg(test)]
+mod test {
+    use super::{import_batches, user_ids, AccountDelete, AccountImport, MultiAccountImport};
+    use serde_json::json;
+
+    #[test]
+    fn test_account_import_body() {
+        let body = AccountImport {
+            user_id: "10086",
+            nick: None,
+            face_url: Some("https://example.com/face.png"),
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "UserID": "10086", "FaceUrl": "https://example.com/face.png" })
+        );
+
+        let body = MultiAccountImport {
+            accounts: &["a", "b"],
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "Accounts": ["a", "b"] })
+        );
+
+        let body = AccountDelete {
+            delete_item: user_ids(&["a", "b"]),
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "DeleteItem": [{ "UserID": "a" }, { "UserID": "b" }] })
+        );
+    }
+
+    #[test]
+    fn test_import_batches() {
+        let identifiers: Vec<String> = (0..250).map(|i| i.to_string()).collect();
+        let batches: Vec<Vec<String>> = import_batches(&identifiers).collect();
+        let sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [100, 100, 50]);
+        assert_eq!(batches[2][0], "200");
+        assert_eq!(import_batches(Vec::<&str>::new()).count(), 0);
+    }
+}
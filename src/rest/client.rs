@@ -0,0 +1,2345 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+use log::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::Semaphore;
+
+use super::account::{import_batches, MultiAccountImport, UserId, IMPORT_ATTEMPTS};
+use super::account::{user_ids, AccountCheck, AccountDelete, AccountImport};
+use super::group::{AddGroupMember, AddedGroupMembers, CreatedGroup, DeleteGroupMember};
+use super::group::{CreatedTopic, DestroyedTopics, TopicIds, TopicInfoList};
+use super::group::{ForbidSendMsg, ImportGroup, ImportGroupMembers, ShuttedUinList};
+use super::group::{GetGroupInfo, GroupAttrKey, GroupAttrList, GroupAttrs, GroupId, GroupInfoList};
+use super::message::ImportGroupMsgs;
+use super::openconfig::{GetNoSpeaking, SetNoSpeaking};
+use super::openim::{MsgWithdraw, QueryOnlineStatus, SetMsgRead};
+use super::profile::{PortraitGet, PortraitGetReply, PortraitSet};
+use super::push::{AttrNames, PushAccounts, RemovedAttrs, UserAttrs, UserTags};
+use super::push::{PushReportQuery, PushReports, PushTask};
+use super::recentcontact::DeleteRecentContact;
+use super::response::ImportGroupMsgReply;
+use super::response::{BatchSendMsgReply, Empty, ResultItems};
+use super::sns::{Blacklist, BlacklistCheck, BlacklistCheckReply};
+use super::sns::{
+    FriendAdd, FriendCheck, FriendCheckReply, FriendDelete, FriendResults, FriendUpdates,
+};
+use super::stats::{AppInfos, GetAppInfo, GetHistory, HistoryFiles};
+use super::{json_request, parse_reply, should_resign};
+use super::{AdminSigner, HttpTransport};
+use super::{HttpOptions, Middleware, RetryPolicy};
+use crate::{
+    AccountCheckItem, AccountDeleteItem, AccountImportReport, AccountStatus, AddFriendItem,
+    AddGroupMemberItem, AppInfo, BatchSendMsg, BatchSendMsgResult, BlacklistCheckItem,
+    BlacklistPage, BlacklistQuery, ChatType, CreateGroup, CreateTopic, DestroyTopicItem,
+    FriendCheckItem, FriendPage, FriendQuery, FriendResultItem, FriendType, FriendUpdate,
+    GroupAttr, GroupInfo, GroupMember, GroupMemberPage, GroupMemberQuery, HistoryFile, ImPush,
+    ImportGroupMember, ImportGroupMsg, ImportGroupMsgItem, ImportMsg, ModifyGroupBaseInfo,
+    ModifyGroupMemberInfo, ModifyTopic, MultiAccountImportResult, NoSpeaking, OnlineStatusResult,
+    ProfileItem, ProfileTag, PushAttrs, PushReport, PushTags, RecentContact, RecentContactPage,
+    RecentContactQuery, Result, RoamMsg, RoamMsgPage, RoamMsgQuery, SendGroupMsg,
+    SendGroupMsgResult, SendMsg, SendMsgResult, Session, SharedSigner, ShuttedMember, TopicInfo,
+    UserProfile,
+};
+
+/// An async client of the
+/// [Tencent IM REST API](https://cloud.tencent.com/document/product/269/1519),
+/// signing each request as the app administrator.
+///
+/// Requires the `rest` feature, and a tokio runtime to run the requests in.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tls_sig_api::{TimClient, TlsSigApiVer2};
+///
+/// let signer = TlsSigApiVer2::from_env().unwrap();
+/// let client = TimClient::new(signer, "administrator");
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// runtime.block_on(async {
+///     client.account_import("10086", Some("Alice"), None).await.unwrap();
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct TimClient {
+    auth: AdminSigner,
+    retry: RetryPolicy,
+    transport: Arc<dyn HttpTransport>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    concurrency: Option<Arc<Semaphore>>,
+}
+
+impl TimClient {
+    /// call the API as `admin`, with sigs from `signer`.
+    pub fn new<S: Into<SharedSigner>>(signer: S, admin: &str) -> Self {
+        TimClient {
+            auth: AdminSigner::new(signer.into(), admin),
+            retry: RetryPolicy::default(),
+            transport: Arc::new(reqwest::Client::new()),
+            middleware: Vec::new(),
+            concurrency: None,
+        }
+    }
+
+    /// call the API at `endpoint` instead of [`DEFAULT_ENDPOINT`], e.g.
+    /// `https://adminapisgp.im.qcloud.com` for apps in Singapore.
+    ///
+    /// [`DEFAULT_ENDPOINT`]: constant.DEFAULT_ENDPOINT.html
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.auth.set_endpoint(endpoint);
+        self
+    }
+
+    /// the expire of the admin sig signing requests, an hour by default. It
+    /// is cached and renewed once a fifth of it is left, see
+    /// [`with_sig_renew_before`].
+    ///
+    /// [`with_sig_renew_before`]: #method.with_sig_renew_before
+    pub fn with_sig_expire(mut self, expire: Duration) -> Self {
+        self.auth.sig_expire = expire;
+        self
+    }
+
+    /// renew the admin sig once at most `before` is left until it expires.
+    pub fn with_sig_renew_before(mut self, before: Duration) -> Self {
+        self.auth.renew_before = Some(before);
+        self
+    }
+
+    /// retry failed calls by `policy` instead of the default one.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// connect through a client built from `options`, failing if the proxy
+    /// or a root certificate is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{HttpOptions, TimClient, TlsSigApiVer2};
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let options = HttpOptions::default().with_proxy("http://egress.internal:3128");
+    ///
+    /// let client = TimClient::new(TlsSigApiVer2::new(1400000000, mock_key), "administrator")
+    ///     .with_http_options(&options)
+    ///     .unwrap();
+    /// ```
+    pub fn with_http_options(mut self, options: &HttpOptions) -> Result<Self> {
+        self.transport = Arc::new(options.build()?);
+        Ok(self)
+    }
+
+    /// send requests through `http`, e.g. an instrumented one.
+    pub fn with_http_client(self, http: reqwest::Client) -> Self {
+        self.with_transport(http)
+    }
+
+    /// post requests through `transport` instead of a `reqwest::Client`, see
+    /// [`HttpTransport`].
+    ///
+    /// [`HttpTransport`]: trait.HttpTransport.html
+    pub fn with_transport<T: HttpTransport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// call `middleware` on every request and response, after the ones
+    /// added before, see [`Middleware`].
+    ///
+    /// [`Middleware`]: trait.Middleware.html
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// post at most `max` requests at once, shared by the clones of the
+    /// client, the others waiting for one to be answered. Requests are not
+    /// limited by default.
+    pub fn with_max_concurrency(mut self, max: usize) -> Self {
+        self.concurrency = Some(Arc::new(Semaphore::new(max.max(1))));
+        self
+    }
+
+    /// the signer, e.g. for signing sigs of users.
+    pub fn signer(&self) -> &SharedSigner {
+        &self.auth.signer
+    }
+
+    /// post `body` to `v4/{service}/{command}`, for endpoints without a
+    /// method of their own, parsing the payload next to the status as `T`,
+    /// e.g. a `serde_json::Value`. Fails with [`RestFailed`] if the request
+    /// fails.
+    ///
+    /// Each request has a new random and the cached admin sig. If the sig is
+    /// rejected, e.g. after it expired or the key is rotated, the request is
+    /// retried once with a new one. Other failures are retried by the
+    /// [`RetryPolicy`].
+    ///
+    /// [`RetryPolicy`]: struct.RetryPolicy.html
+    ///
+    /// [`RestFailed`]: enum.TlsSigError.html#variant.RestFailed
+    pub async fn call<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        service: &str,
+        command: &str,
+        body: &B,
+    ) -> Result<T> {
+        let mut attempts = 1;
+        let mut resigned = false;
+        loop {
+            let result = self.send(service, command, body).await;
+            if !resigned && should_resign(&result) {
+                warn!(
+                    "tim rest api {}/{} rejected the admin sig, retrying with a new one",
+                    service, command
+                );
+                self.auth.forget_sig();
+                resigned = true;
+                continue;
+            }
+
+            let delay = match self.retry.retry_after(&result, attempts) {
+                Some(delay) => delay,
+                None => return result,
+            };
+            if let Err(e) = &result {
+                warn!(
+                    "tim rest api {}/{} failed, retrying in {:?}: {}",
+                    service, command, delay, e
+                );
+            }
+            tokio::time::sleep(delay).await;
+            attempts += 1;
+        }
+    }
+
+    /// post the json `body` to `v4/{service_name}/{command}` like
+    /// [`call`], answering the payload next to the status as json, e.g. for
+    /// endpoints added to the API after this crate.
+    ///
+    /// [`call`]: #method.call
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use serde_json::json;
+    /// use tls_sig_api::{TimClient, TlsSigApiVer2};
+    ///
+    /// let signer = TlsSigApiVer2::from_env().unwrap();
+    /// let client = TimClient::new(signer, "administrator");
+    ///
+    /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// runtime.block_on(async {
+    ///     let body = json!({ "Get_Account": "10086" });
+    ///     let payload = client
+    ///         .call_raw("openconfigsvr", "getnospeaking", body)
+    ///         .await
+    ///         .unwrap();
+    ///     println!("{}", payload["GroupmsgNospeakingTime"]);
+    /// });
+    /// ```
+    pub async fn call_raw(
+        &self,
+        service_name: &str,
+        command: &str,
+        body: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        self.call(service_name, command, &body).await
+    }
+
+    /// call `f` with each of `items`, running at most `limit` of the calls
+    /// at once, answering their results in the order of `items`. A call
+    /// failed does not stop the others.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tls_sig_api::{Result, TimClient};
+    ///
+    /// async fn kick_all(client: &TimClient, identifiers: &[&str]) -> Vec<Result<()>> {
+    ///     client
+    ///         .for_each_concurrent(identifiers, 50, |client, identifier| async move {
+    ///             client.kick(identifier).await
+    ///         })
+    ///         .await
+    /// }
+    /// ```
+    pub async fn for_each_concurrent<'a, I, F, Fut, T>(
+        &'a self,
+        items: I,
+        limit: usize,
+        mut f: F,
+    ) -> Vec<Result<T>>
+    where
+        I: IntoIterator,
+        F: FnMut(&'a TimClient, I::Item) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut items = items.into_iter().enumerate();
+        let mut running = Vec::new();
+        let mut results = Vec::new();
+        loop {
+            while running.len() < limit.max(1) {
+                match items.next() {
+                    Some((index, item)) => {
+                        results.push(None);
+                        running.push((index, Box::pin(f(self, item))));
+                    }
+                    None => break,
+                }
+            }
+            if running.is_empty() {
+                break;
+            }
+
+            let (finished, result) = poll_fn(|cx| {
+                for (finished, (_, call)) in running.iter_mut().enumerate() {
+                    if let Poll::Ready(result) = call.as_mut().poll(cx) {
+                        return Poll::Ready((finished, result));
+                    }
+                }
+                Poll::Pending
+            })
+            .await;
+            let (index, _) = running.swap_remove(finished);
+            results[index] = Some(result);
+        }
+        results.into_iter().flatten().collect()
+    }
+
+    async fn send<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        service: &str,
+        command: &str,
+        body: &B,
+    ) -> Result<T> {
+        let url = self.auth.url(service, command)?;
+        debug!("calling tim rest api {}/{}", service, command);
+
+        let mut request = json_request(url, body)?;
+        for middleware in &self.middleware {
+            middleware.on_request(service, command, &mut request);
+        }
+
+        let _permit = match &self.concurrency {
+            Some(concurrency) => concurrency.acquire().await.ok(),
+            None => None,
+        };
+        let posted = Instant::now();
+        let mut response = self.transport.post(request).await;
+        for middleware in &self.middleware {
+            middleware.on_response(service, command, &mut response, posted.elapsed());
+        }
+        parse_reply(service, command, response?)
+    }
+
+    /// import the account `identifier`, so that it can log in and be sent
+    /// messages.
+    pub async fn account_import(
+        &self,
+        identifier: &str,
+        nick: Option<&str>,
+        face_url: Option<&str>,
+    ) -> Result<()> {
+        let body = AccountImport {
+            user_id: identifier,
+            nick,
+            face_url,
+        };
+        self.call::<_, Empty>("im_open_login_svc", "account_import", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// import the accounts `identifiers`, at most 100 at once, answering
+    /// those failed to be imported.
+    pub async fn multiaccount_import(
+        &self,
+        identifiers: &[&str],
+    ) -> Result<MultiAccountImportResult> {
+        let body = MultiAccountImport {
+            accounts: identifiers,
+        };
+        self.call("im_open_login_svc", "multiaccount_import", &body)
+            .await
+    }
+
+    /// import all the accounts `identifiers`, e.g. read from a file, with a
+    /// `multiaccount_import` for every 100 of them. The accounts answered
+    /// failed are imported again, up to 3 times in all, while a batch whose
+    /// request fails is given up after the [`RetryPolicy`].
+    ///
+    /// [`RetryPolicy`]: struct.RetryPolicy.html
+    pub async fn import_accounts_from<I>(&self, identifiers: I) -> AccountImportReport
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut report = AccountImportReport::default();
+        'batches: for mut batch in import_batches(identifiers) {
+            for _ in 0..IMPORT_ATTEMPTS {
+                let accounts: Vec<&str> = batch.iter().map(String::as_str).collect();
+                match self.multiaccount_import(&accounts).await {
+                    Ok(result) => batch = report.imported(batch, &result),
+                    Err(e) => {
+                        warn!("failed to import {} accounts: {}", batch.len(), e);
+                        report.failed_batches.push((batch, e));
+                        continue 'batches;
+                    }
+                }
+                if batch.is_empty() {
+                    continue 'batches;
+                }
+            }
+            report.failed.extend(batch);
+        }
+        report
+    }
+
+    /// delete the accounts `identifiers`, at most 100 at once.
+    pub async fn account_delete(&self, identifiers: &[&str]) -> Result<Vec<AccountDeleteItem>> {
+        let body = AccountDelete {
+            delete_item: user_ids(identifiers),
+        };
+        self.call::<_, ResultItems<_>>("im_open_login_svc", "account_delete", &body)
+            .await
+            .map(|result| result.items)
+    }
+
+    /// check whether the accounts `identifiers` are imported, at most 100 at
+    /// once, e.g. to reconcile them with the users of the app.
+    pub async fn account_check(
+        &self,
+        identifiers: &[&str],
+    ) -> Result<HashMap<String, AccountStatus>> {
+        let body = AccountCheck {
+            check_item: user_ids(identifiers),
+        };
+        self.call::<_, ResultItems<AccountCheckItem>>("im_open_login_svc", "account_check", &body)
+            .await
+            .map(|result| {
+                result
+                    .items
+                    .into_iter()
+                    .map(|item| (item.user_id.clone(), item.status()))
+                    .collect()
+            })
+    }
+
+    /// log `identifier` out of every device.
+    ///
+    /// This does not invalidate its sigs, it can log in again with any sig
+    /// not expired yet. To lock out a banned user, also delete the account
+    /// with [`account_delete`] or stop issuing it sigs; after the key leaks,
+    /// rotate the key too.
+    ///
+    /// [`account_delete`]: #method.account_delete
+    pub async fn kick(&self, identifier: &str) -> Result<()> {
+        let body = UserId {
+            user_id: identifier,
+        };
+        self.call::<_, Empty>("im_open_login_svc", "kick", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// query the online status of `accounts`, at most 500 at once, with
+    /// their status on each platform if `detail`.
+    pub async fn query_online_status(
+        &self,
+        accounts: &[&str],
+        detail: bool,
+    ) -> Result<OnlineStatusResult> {
+        let body = QueryOnlineStatus {
+            is_need_detail: detail as u8,
+            to_account: accounts,
+        };
+        self.call("openim", "query_online_status", &body).await
+    }
+
+    /// send the one-to-one message `msg`.
+    pub async fn send_msg(&self, msg: &SendMsg) -> Result<SendMsgResult> {
+        self.call("openim", "sendmsg", msg).await
+    }
+
+    /// send the message `msg` to each of its recipients, in one request for
+    /// every 500 of them. A failed request does not stop the others, its
+    /// recipients are reported in [`BatchSendMsgResult::failed_batches`].
+    ///
+    /// [`BatchSendMsgResult::failed_batches`]: struct.BatchSendMsgResult.html#structfield.failed_batches
+    pub async fn batch_send_msg(&self, msg: &BatchSendMsg) -> BatchSendMsgResult {
+        let mut result = BatchSendMsgResult::default();
+        for chunk in msg.chunks() {
+            match self
+                .call::<_, BatchSendMsgReply>("openim", "batchsendmsg", &chunk)
+                .await
+            {
+                Ok(sent) => {
+                    result.msg_keys.push(sent.msg_key);
+                    result.error_list.extend(sent.error_list);
+                }
+                Err(e) => {
+                    warn!(
+                        "failed to send message to {} accounts: {}",
+                        chunk.to_account.len(),
+                        e
+                    );
+                    result.failed_batches.push((chunk.to_account.to_vec(), e));
+                }
+            }
+        }
+        result
+    }
+
+    /// import the historical message `msg`.
+    pub async fn import_msg(&self, msg: &ImportMsg) -> Result<()> {
+        self.call::<_, Empty>("openim", "importmsg", msg)
+            .await
+            .map(|_| ())
+    }
+
+    /// withdraw the message with `msg_key` sent from `from_account` to
+    /// `to_account`, e.g. for moderation.
+    ///
+    /// The key is answered by [`send_msg`].
+    ///
+    /// [`send_msg`]: #method.send_msg
+    pub async fn withdraw_msg(
+        &self,
+        from_account: &str,
+        to_account: &str,
+        msg_key: &str,
+    ) -> Result<()> {
+        let body = MsgWithdraw {
+            from_account,
+            to_account,
+            msg_key,
+        };
+        self.call::<_, Empty>("openim", "admin_msgwithdraw", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// mark the messages sent to `report_account` by `peer_account` as read.
+    pub async fn set_msg_read(&self, report_account: &str, peer_account: &str) -> Result<()> {
+        let body = SetMsgRead {
+            report_account,
+            peer_account,
+        };
+        self.call::<_, Empty>("openim", "admin_set_msg_read", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// a page of the messages of `query`, see [`RoamMsgQuery::next_page`]
+    /// for the next one.
+    ///
+    /// [`RoamMsgQuery::next_page`]: struct.RoamMsgQuery.html#method.next_page
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tls_sig_api::{Result, RoamMsgQuery, TimClient};
+    ///
+    /// async fn print_history(client: &TimClient) -> Result<()> {
+    ///     let mut query = Some(RoamMsgQuery::new("alice", "bob", 1569900000, 1569910200));
+    ///     while let Some(current) = query.take() {
+    ///         let page = client.get_roam_msg(&current).await?;
+    ///         query = current.next_page(&page);
+    ///         for msg in page.msg_list {
+    ///             println!("{:?}", msg.msg_body);
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_roam_msg(&self, query: &RoamMsgQuery) -> Result<RoamMsgPage> {
+        self.call("openim", "admin_getroammsg", query).await
+    }
+
+    /// stream the messages of `query`, fetching the pages one after another
+    /// until one fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use futures::StreamExt;
+    /// use tls_sig_api::{Result, RoamMsgQuery, TimClient};
+    ///
+    /// async fn print_history(client: &TimClient) -> Result<()> {
+    ///     let query = RoamMsgQuery::new("alice", "bob", 1569900000, 1569910200);
+    ///     let mut msgs = client.roam_msgs(query);
+    ///     while let Some(msg) = msgs.next().await {
+    ///         println!("{:?}", msg?.msg_body);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn roam_msgs(&self, query: RoamMsgQuery) -> RoamMsgStream<'_> {
+        RoamMsgStream(PageStream::new(self, query, |client, query| {
+            Box::pin(async move {
+                let page = client.get_roam_msg(&query).await?;
+                Ok((query.next_page(&page), page.msg_list))
+            })
+        }))
+    }
+
+    /// create the group `group`, answering its id.
+    pub async fn create_group(&self, group: &CreateGroup) -> Result<String> {
+        self.call::<_, CreatedGroup>("group_open_http_svc", "create_group", group)
+            .await
+            .map(|created| created.group_id)
+    }
+
+    /// dismiss the group `group_id`.
+    pub async fn destroy_group(&self, group_id: &str) -> Result<()> {
+        let body = GroupId { group_id };
+        self.call::<_, Empty>("group_open_http_svc", "destroy_group", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// the info of the groups `group_ids`, at most 50 at once.
+    pub async fn get_group_info(&self, group_ids: &[&str]) -> Result<Vec<GroupInfo>> {
+        let body = GetGroupInfo {
+            group_id_list: group_ids,
+        };
+        self.call::<_, GroupInfoList>("group_open_http_svc", "get_group_info", &body)
+            .await
+            .map(|list| list.group_info)
+    }
+
+    /// change the base info of a group by `changes`.
+    pub async fn modify_group_base_info(&self, changes: &ModifyGroupBaseInfo) -> Result<()> {
+        self.call::<_, Empty>("group_open_http_svc", "modify_group_base_info", changes)
+            .await
+            .map(|_| ())
+    }
+
+    /// add `members` to the group `group_id`, without notifying the group if
+    /// `silence`.
+    pub async fn add_group_member(
+        &self,
+        group_id: &str,
+        members: &[&str],
+        silence: bool,
+    ) -> Result<Vec<AddGroupMemberItem>> {
+        let body = AddGroupMember::new(group_id, members, silence);
+        self.call::<_, AddedGroupMembers>("group_open_http_svc", "add_group_member", &body)
+            .await
+            .map(|added| added.member_list)
+    }
+
+    /// remove `members` from the group `group_id` for `reason`, without
+    /// notifying the group if `silence`.
+    pub async fn delete_group_member(
+        &self,
+        group_id: &str,
+        members: &[&str],
+        silence: bool,
+        reason: Option<&str>,
+    ) -> Result<()> {
+        let body = DeleteGroupMember {
+            group_id,
+            silence: silence.then_some(1),
+            reason,
+            member_to_del_account: members,
+        };
+        self.call::<_, Empty>("group_open_http_svc", "delete_group_member", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// a page of the members of `query`, see [`GroupMemberQuery::next_page`]
+    /// for the next one.
+    ///
+    /// [`GroupMemberQuery::next_page`]: struct.GroupMemberQuery.html#method.next_page
+    pub async fn get_group_member_info(&self, query: &GroupMemberQuery) -> Result<GroupMemberPage> {
+        self.call("group_open_http_svc", "get_group_member_info", query)
+            .await
+    }
+
+    /// stream the members of `query`, fetching the pages one after another
+    /// until one fails.
+    pub fn group_members(&self, query: GroupMemberQuery) -> GroupMemberStream<'_> {
+        GroupMemberStream(PageStream::new(self, query, |client, query| {
+            Box::pin(async move {
+                let page = client.get_group_member_info(&query).await?;
+                Ok((query.next_page(&page), page.member_list))
+            })
+        }))
+    }
+
+    /// change the role, mute or name card of a member by `changes`.
+    pub async fn modify_group_member_info(&self, changes: &ModifyGroupMemberInfo) -> Result<()> {
+        self.call::<_, Empty>("group_open_http_svc", "modify_group_member_info", changes)
+            .await
+            .map(|_| ())
+    }
+
+    /// send the message `msg` to its group.
+    pub async fn send_group_msg(&self, msg: &SendGroupMsg) -> Result<SendGroupMsgResult> {
+        self.call("group_open_http_svc", "send_group_msg", msg)
+            .await
+    }
+
+    /// the custom attributes of the group `group_id`.
+    pub async fn get_group_attr(&self, group_id: &str) -> Result<Vec<GroupAttr>> {
+        let body = GroupId { group_id };
+        self.call::<_, GroupAttrList>("group_open_http_svc", "get_group_attr", &body)
+            .await
+            .map(|list| list.group_attr_ary)
+    }
+
+    /// replace all the attributes of the group `group_id` with `attrs`.
+    ///
+    /// It overwrites the attributes set by others since they are got, so
+    /// prefer [`modify_group_attr`] when the group is written concurrently.
+    ///
+    /// [`modify_group_attr`]: #method.modify_group_attr
+    pub async fn set_group_attr(&self, group_id: &str, attrs: &[GroupAttr]) -> Result<()> {
+        let body = GroupAttrs {
+            group_id,
+            group_attr: attrs,
+        };
+        self.call::<_, Empty>("group_open_http_svc", "set_group_attr", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// add or change the attributes `attrs` of the group `group_id`, keeping
+    /// the others.
+    pub async fn modify_group_attr(&self, group_id: &str, attrs: &[GroupAttr]) -> Result<()> {
+        let body = GroupAttrs {
+            group_id,
+            group_attr: attrs,
+        };
+        self.call::<_, Empty>("group_open_http_svc", "modify_group_attr", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// delete the attributes of `keys` of the group `group_id`.
+    pub async fn delete_group_attr(&self, group_id: &str, keys: &[&str]) -> Result<()> {
+        let keys: Vec<GroupAttrKey> = keys.iter().map(|&key| GroupAttrKey { key }).collect();
+        let body = GroupAttrs {
+            group_id,
+            group_attr: &keys,
+        };
+        self.call::<_, Empty>("group_open_http_svc", "delete_group_attr", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// mute `members` of the group `group_id` for `shut_up_time` seconds, or
+    /// unmute them with 0.
+    pub async fn forbid_send_msg(
+        &self,
+        group_id: &str,
+        members: &[&str],
+        shut_up_time: u32,
+    ) -> Result<()> {
+        let body = ForbidSendMsg {
+            group_id,
+            members_account: members,
+            shut_up_time,
+        };
+        self.call::<_, Empty>("group_open_http_svc", "forbid_send_msg", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// the members muted in the group `group_id`.
+    pub async fn get_group_shutted_uin(&self, group_id: &str) -> Result<Vec<ShuttedMember>> {
+        let body = GroupId { group_id };
+        self.call::<_, ShuttedUinList>("group_open_http_svc", "get_group_shutted_uin", &body)
+            .await
+            .map(|list| list.shutted_uin_list)
+    }
+
+    /// import the historical group `group` created at the unix time
+    /// `create_time`, answering its id. The members of `group` are not
+    /// imported, see [`import_group_member`].
+    ///
+    /// [`import_group_member`]: #method.import_group_member
+    pub async fn import_group(&self, group: &CreateGroup, create_time: u64) -> Result<String> {
+        let body = ImportGroup::new(group, create_time);
+        self.call::<_, CreatedGroup>("group_open_http_svc", "import_group", &body)
+            .await
+            .map(|created| created.group_id)
+    }
+
+    /// import the historical `members` of the group `group_id`, at most 500
+    /// at once.
+    pub async fn import_group_member(
+        &self,
+        group_id: &str,
+        members: &[ImportGroupMember],
+    ) -> Result<Vec<AddGroupMemberItem>> {
+        let body = ImportGroupMembers {
+            group_id,
+            member_list: members,
+        };
+        self.call::<_, AddedGroupMembers>("group_open_http_svc", "import_group_member", &body)
+            .await
+            .map(|added| added.member_list)
+    }
+
+    /// import the historical `msgs` of the group `group_id`, at most 20 at
+    /// once, answering the result of each in order.
+    pub async fn import_group_msg(
+        &self,
+        group_id: &str,
+        msgs: &[ImportGroupMsg],
+    ) -> Result<Vec<ImportGroupMsgItem>> {
+        let body = ImportGroupMsgs {
+            group_id,
+            msg_list: msgs,
+        };
+        self.call::<_, ImportGroupMsgReply>("group_open_http_svc", "import_group_msg", &body)
+            .await
+            .map(|reply| reply.import_msg_result)
+    }
+
+    /// create the topic `topic` in its community, answering its id.
+    pub async fn create_topic(&self, topic: &CreateTopic) -> Result<String> {
+        self.call::<_, CreatedTopic>("million_group_open_http_svc", "create_topic", topic)
+            .await
+            .map(|created| created.topic_id)
+    }
+
+    /// destroy the topics `topic_ids` of the community `group_id`.
+    pub async fn destroy_topic(
+        &self,
+        group_id: &str,
+        topic_ids: &[&str],
+    ) -> Result<Vec<DestroyTopicItem>> {
+        let body = TopicIds {
+            group_id,
+            topic_id_list: topic_ids,
+        };
+        self.call::<_, DestroyedTopics>("million_group_open_http_svc", "destroy_topic", &body)
+            .await
+            .map(|destroyed| destroyed.destroy_result_item)
+    }
+
+    /// the info of the topics `topic_ids` of the community `group_id`, or of
+    /// all its topics if `topic_ids` is empty.
+    pub async fn get_topic(&self, group_id: &str, topic_ids: &[&str]) -> Result<Vec<TopicInfo>> {
+        let body = TopicIds {
+            group_id,
+            topic_id_list: topic_ids,
+        };
+        self.call::<_, TopicInfoList>("million_group_open_http_svc", "get_topic", &body)
+            .await
+            .map(|list| list.topic_response_info)
+    }
+
+    /// change a topic by `changes`.
+    pub async fn modify_topic(&self, changes: &ModifyTopic) -> Result<()> {
+        self.call::<_, Empty>("million_group_open_http_svc", "modify_topic", changes)
+            .await
+            .map(|_| ())
+    }
+
+    /// the fields `tags` of the profiles of `accounts`, at most 100 at once.
+    pub async fn portrait_get(
+        &self,
+        accounts: &[&str],
+        tags: &[ProfileTag],
+    ) -> Result<Vec<UserProfile>> {
+        let body = PortraitGet {
+            to_account: accounts,
+            tag_list: tags,
+        };
+        self.call::<_, PortraitGetReply>("profile", "portrait_get", &body)
+            .await
+            .map(|reply| reply.user_profile_item)
+    }
+
+    /// set the fields `items` of the profile of `account`.
+    pub async fn portrait_set(&self, account: &str, items: &[ProfileItem]) -> Result<()> {
+        let body = PortraitSet {
+            from_account: account,
+            profile_item: items,
+        };
+        self.call::<_, Empty>("profile", "portrait_set", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// add the friends `items` to `from_account`, `add_type` ways, without
+    /// their confirmation if `force`.
+    pub async fn friend_add(
+        &self,
+        from_account: &str,
+        items: &[AddFriendItem],
+        add_type: FriendType,
+        force: bool,
+    ) -> Result<Vec<FriendResultItem>> {
+        let body = FriendAdd::new(from_account, items, add_type, force);
+        self.call::<_, FriendResults>("sns", "friend_add", &body)
+            .await
+            .map(|results| results.result_item)
+    }
+
+    /// delete the friends `accounts` of `from_account`, `delete_type` ways.
+    pub async fn friend_delete(
+        &self,
+        from_account: &str,
+        accounts: &[&str],
+        delete_type: FriendType,
+    ) -> Result<Vec<FriendResultItem>> {
+        let body = FriendDelete::new(from_account, accounts, delete_type);
+        self.call::<_, FriendResults>("sns", "friend_delete", &body)
+            .await
+            .map(|results| results.result_item)
+    }
+
+    /// change the friends of `from_account` by `changes`.
+    pub async fn friend_update(
+        &self,
+        from_account: &str,
+        changes: &[FriendUpdate],
+    ) -> Result<Vec<FriendResultItem>> {
+        let body = FriendUpdates {
+            from_account,
+            update_item: changes,
+        };
+        self.call::<_, FriendResults>("sns", "friend_update", &body)
+            .await
+            .map(|results| results.result_item)
+    }
+
+    /// a page of the friends of `query`, see [`FriendQuery::next_page`] for
+    /// the next one.
+    ///
+    /// [`FriendQuery::next_page`]: struct.FriendQuery.html#method.next_page
+    pub async fn friend_get(&self, query: &FriendQuery) -> Result<FriendPage> {
+        self.call("sns", "friend_get", query).await
+    }
+
+    /// the relationships between `from_account` and `accounts`, checked
+    /// `check_type` ways.
+    pub async fn friend_check(
+        &self,
+        from_account: &str,
+        accounts: &[&str],
+        check_type: FriendType,
+    ) -> Result<Vec<FriendCheckItem>> {
+        let body = FriendCheck::new(from_account, accounts, check_type);
+        self.call::<_, FriendCheckReply>("sns", "friend_check", &body)
+            .await
+            .map(|reply| reply.info_item)
+    }
+
+    /// blacklist `accounts` for `from_account`.
+    pub async fn black_list_add(
+        &self,
+        from_account: &str,
+        accounts: &[&str],
+    ) -> Result<Vec<FriendResultItem>> {
+        let body = Blacklist {
+            from_account,
+            to_account: accounts,
+        };
+        self.call::<_, FriendResults>("sns", "black_list_add", &body)
+            .await
+            .map(|results| results.result_item)
+    }
+
+    /// remove `accounts` from the blacklist of `from_account`.
+    pub async fn black_list_delete(
+        &self,
+        from_account: &str,
+        accounts: &[&str],
+    ) -> Result<Vec<FriendResultItem>> {
+        let body = Blacklist {
+            from_account,
+            to_account: accounts,
+        };
+        self.call::<_, FriendResults>("sns", "black_list_delete", &body)
+            .await
+            .map(|results| results.result_item)
+    }
+
+    /// a page of the blacklist of `query`, see [`BlacklistQuery::next_page`]
+    /// for the next one.
+    ///
+    /// [`BlacklistQuery::next_page`]: struct.BlacklistQuery.html#method.next_page
+    pub async fn black_list_get(&self, query: &BlacklistQuery) -> Result<BlacklistPage> {
+        self.call("sns", "black_list_get", query).await
+    }
+
+    /// whether `from_account` and `accounts` have blacklisted each other,
+    /// checked `check_type` ways.
+    pub async fn black_list_check(
+        &self,
+        from_account: &str,
+        accounts: &[&str],
+        check_type: FriendType,
+    ) -> Result<Vec<BlacklistCheckItem>> {
+        let body = BlacklistCheck::new(from_account, accounts, check_type);
+        self.call::<_, BlacklistCheckReply>("sns", "black_list_check", &body)
+            .await
+            .map(|reply| reply.black_list_check_item)
+    }
+
+    /// a page of the recent contacts of `query`, see
+    /// [`RecentContactQuery::next_page`] for the next one.
+    ///
+    /// [`RecentContactQuery::next_page`]: struct.RecentContactQuery.html#method.next_page
+    pub async fn get_recent_contact_list(
+        &self,
+        query: &RecentContactQuery,
+    ) -> Result<RecentContactPage> {
+        self.call("recentcontact", "get_list", query).await
+    }
+
+    /// stream the recent contacts of `query`, fetching the pages one after
+    /// another until one fails.
+    pub fn recent_contacts(&self, query: RecentContactQuery) -> RecentContactStream<'_> {
+        RecentContactStream(PageStream::new(self, query, |client, query| {
+            Box::pin(async move {
+                let page = client.get_recent_contact_list(&query).await?;
+                Ok((query.next_page(&page), page.session_item))
+            })
+        }))
+    }
+
+    /// all the recent contacts of `from_account`, fetching the pages one
+    /// after another.
+    pub async fn get_all_recent_contacts(&self, from_account: &str) -> Result<Vec<RecentContact>> {
+        let mut contacts = Vec::new();
+        let mut query = Some(RecentContactQuery::new(from_account));
+        while let Some(current) = query.take() {
+            let page = self.get_recent_contact_list(&current).await?;
+            query = current.next_page(&page);
+            contacts.extend(page.session_item);
+        }
+        Ok(contacts)
+    }
+
+    /// delete `session` from the recent contacts of `from_account`, with its
+    /// roaming messages if `clear_ramble`.
+    pub async fn delete_recent_contact(
+        &self,
+        from_account: &str,
+        session: &Session,
+        clear_ramble: bool,
+    ) -> Result<()> {
+        let body = DeleteRecentContact::new(from_account, session, clear_ramble);
+        self.call::<_, Empty>("recentcontact", "delete", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// push the message `push` to all its accounts, answering the id of the
+    /// push task, see [`im_get_push_report`].
+    ///
+    /// [`im_get_push_report`]: #method.im_get_push_report
+    pub async fn all_member_push(&self, push: &ImPush) -> Result<String> {
+        self.call::<_, PushTask>("all_member_push", "im_push", push)
+            .await
+            .map(|task| task.task_id)
+    }
+
+    /// the delivery of the push tasks `task_ids`.
+    pub async fn im_get_push_report(&self, task_ids: &[&str]) -> Result<Vec<PushReport>> {
+        let body = PushReportQuery { task_ids };
+        self.call::<_, PushReports>("all_member_push", "im_get_push_report", &body)
+            .await
+            .map(|reports| reports.reports)
+    }
+
+    /// name the push attributes by their indexes, from 0 to 9.
+    pub async fn im_set_attr_name(&self, attr_names: &BTreeMap<u8, String>) -> Result<()> {
+        let body = AttrNames::new(attr_names);
+        self.call::<_, Empty>("all_member_push", "im_set_attr_name", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// the names of the push attributes by their indexes.
+    pub async fn im_get_attr_name(&self) -> Result<BTreeMap<u8, String>> {
+        self.call::<_, AttrNames>("all_member_push", "im_get_attr_name", &json!({}))
+            .await
+            .map(AttrNames::into_names)
+    }
+
+    /// the push attributes of `accounts`.
+    pub async fn im_get_attr(&self, accounts: &[&str]) -> Result<Vec<PushAttrs>> {
+        let body = PushAccounts {
+            to_account: accounts,
+        };
+        self.call::<_, UserAttrs<Vec<PushAttrs>>>("all_member_push", "im_get_attr", &body)
+            .await
+            .map(|attrs| attrs.user_attrs)
+    }
+
+    /// set the push attributes `attrs` of their accounts, keeping the
+    /// others.
+    pub async fn im_set_attr(&self, attrs: &[PushAttrs]) -> Result<()> {
+        let body = UserAttrs { user_attrs: attrs };
+        self.call::<_, Empty>("all_member_push", "im_set_attr", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// remove the push attributes `attr_names` of each of `accounts`.
+    pub async fn im_remove_attr(&self, accounts: &[&str], attr_names: &[&str]) -> Result<()> {
+        let body = UserAttrs {
+            user_attrs: accounts
+                .iter()
+                .map(|&to_account| RemovedAttrs {
+                    to_account,
+                    attrs: attr_names,
+                })
+                .collect::<Vec<_>>(),
+        };
+        self.call::<_, Empty>("all_member_push", "im_remove_attr", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// the push tags of `accounts`.
+    pub async fn im_get_tag(&self, accounts: &[&str]) -> Result<Vec<PushTags>> {
+        let body = PushAccounts {
+            to_account: accounts,
+        };
+        self.call::<_, UserTags<Vec<PushTags>>>("all_member_push", "im_get_tag", &body)
+            .await
+            .map(|tags| tags.user_tags)
+    }
+
+    /// add the push tags `tags` to their accounts.
+    pub async fn im_add_tag(&self, tags: &[PushTags]) -> Result<()> {
+        let body = UserTags { user_tags: tags };
+        self.call::<_, Empty>("all_member_push", "im_add_tag", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// remove the push tags `tags` from their accounts.
+    pub async fn im_remove_tag(&self, tags: &[PushTags]) -> Result<()> {
+        let body = UserTags { user_tags: tags };
+        self.call::<_, Empty>("all_member_push", "im_remove_tag", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// remove all the push tags of `accounts`.
+    pub async fn im_remove_all_tags(&self, accounts: &[&str]) -> Result<()> {
+        let body = PushAccounts {
+            to_account: accounts,
+        };
+        self.call::<_, Empty>("all_member_push", "im_remove_all_tags", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// the operational data of the app, e.g. its daily active accounts,
+    /// messages and callbacks, for each of the last 30 days. Only the fields
+    /// named in `fields` are answered, e.g. `ActiveUserNum`, or all of them
+    /// if it is empty.
+    pub async fn get_app_info(&self, fields: &[&str]) -> Result<Vec<AppInfo>> {
+        let body = GetAppInfo {
+            request_field: fields,
+        };
+        self.call::<_, AppInfos>("openconfigsvr", "getappinfo", &body)
+            .await
+            .map(|infos| infos.result)
+    }
+
+    /// the files of the `chat_type` messages sent within the hour
+    /// `msg_time`, e.g. `2015120121`, in the last 7 days.
+    pub async fn get_history_data(
+        &self,
+        chat_type: ChatType,
+        msg_time: &str,
+    ) -> Result<Vec<HistoryFile>> {
+        let body = GetHistory {
+            chat_type,
+            msg_time,
+        };
+        self.call::<_, HistoryFiles>("open_msg_svc", "get_history", &body)
+            .await
+            .map(|files| files.file)
+    }
+
+    /// mute `account` from sending one-to-one and group messages, or unmute
+    /// it with [`NoSpeaking::unmuted`].
+    ///
+    /// [`NoSpeaking::unmuted`]: struct.NoSpeaking.html#method.unmuted
+    pub async fn set_no_speaking(&self, account: &str, no_speaking: NoSpeaking) -> Result<()> {
+        let body = SetNoSpeaking {
+            set_account: account,
+            no_speaking,
+        };
+        self.call::<_, Empty>("openconfigsvr", "setnospeaking", &body)
+            .await
+            .map(|_| ())
+    }
+
+    /// how long `account` is muted globally.
+    pub async fn get_no_speaking(&self, account: &str) -> Result<NoSpeaking> {
+        let body = GetNoSpeaking {
+            get_account: account,
+        };
+        self.call("openconfigsvr", "getnospeaking", &body).await
+    }
+}
+
+/// the fetch of a page, answering the query of the next page with the items.
+type PageFuture<'a, Q, T> = Pin<Box<dyn Future<Output = Result<(Option<Q>, Vec<T>)>> + Send + 'a>>;
+
+/// a stream over the items of the pages of a query, fetching a page at a
+/// time by `fetch`.
+struct PageStream<'a, Q, T> {
+    client: &'a TimClient,
+    fetch: fn(&'a TimClient, Q) -> PageFuture<'a, Q, T>,
+    query: Option<Q>,
+    page: std::vec::IntoIter<T>,
+    fetching: Option<PageFuture<'a, Q, T>>,
+}
+
+impl<'a, Q, T> PageStream<'a, Q, T> {
+    fn new(
+        client: &'a TimClient,
+        query: Q,
+        fetch: fn(&'a TimClient, Q) -> PageFuture<'a, Q, T>,
+    ) -> Self {
+        PageStream {
+            client,
+            fetch,
+            query: Some(query),
+            page: Vec::new().into_iter(),
+            fetching: None,
+        }
+    }
+}
+
+impl<Q: Unpin, T: Unpin> Stream for PageStream<'_, Q, T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(item) = this.page.next() {
+                return Poll::Ready(Some(Ok(item)));
+            }
+
+            let fetching = match &mut this.fetching {
+                Some(fetching) => fetching,
+                None => match this.query.take() {
+                    Some(query) => this.fetching.insert((this.fetch)(this.client, query)),
+                    None => return Poll::Ready(None),
+                },
+            };
+            let fetched = match fetching.as_mut().poll(cx) {
+                Poll::Ready(fetched) => fetched,
+                Poll::Pending => return Poll::Pending,
+            };
+            this.fetching = None;
+            match fetched {
+                Ok((query, items)) => {
+                    this.query = query;
+                    this.page = items.into_iter();
+                }
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            }
+        }
+    }
+}
+
+impl<Q: fmt::Debug, T: fmt::Debug> fmt::Debug for PageStream<'_, Q, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PageStream")
+            .field("client", &self.client)
+            .field("query", &self.query)
+            .field("page", &self.page)
+            .field("fetching", &self.fetching.is_some())
+            .finish()
+    }
+}
+
+/// A stream of the messages of a [`RoamMsgQuery`], fetching a page at a
+/// time, see [`TimClient::roam_msgs`].
+///
+/// [`RoamMsgQuery`]: struct.RoamMsgQuery.html
+/// [`TimClient::roam_msgs`]: struct.TimClient.html#method.roam_msgs
+#[derive(Debug)]
+pub struct RoamMsgStream<'a>(PageStream<'a, RoamMsgQuery, RoamMsg>);
+
+impl Stream for RoamMsgStream<'_> {
+    type Item = Result<RoamMsg>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.0).poll_next(cx)
+    }
+}
+
+/// A stream of the members of a [`GroupMemberQuery`], fetching a page at a
+/// time, see [`TimClient::group_members`].
+///
+/// [`GroupMemberQuery`]: struct.GroupMemberQuery.html
+/// [`TimClient::group_members`]: struct.TimClient.html#method.group_members
+#[derive(Debug)]
+pub struct GroupMemberStream<'a>(PageStream<'a, GroupMemberQuery, GroupMember>);
+
+impl Stream for GroupMemberStream<'_> {
+    type Item = Result<GroupMember>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.0).poll_next(cx)
+    }
+}
+
+/// A stream of the recent contacts of a [`RecentContactQuery`], fetching a
+/// page at a time, see [`TimClient::recent_contacts`].
+///
+/// [`RecentContactQuery`]: struct.RecentContactQuery.html
+/// [`TimClient::recent_contacts`]: struct.TimClient.html#method.recent_contacts
+#[derive(Debug)]
+pub struct RecentContactStream<'a>(PageStream<'a, RecentContactQuery, RecentContact>);
+
+impl Stream for RecentContactStream<'_> {
+    type Item = Result<RecentContact>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.0).poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TimClient;
+    use crate::rest::test::{serve, serve_all, MOCK_KEY};
+    use crate::{
+        AccountStatus, AddFriendItem, BatchSendMsg, BlacklistQuery, BlacklistRelation, ChatType,
+        CreateGroup, CreateTopic, FriendQuery, FriendRelation, FriendType, FriendUpdate, GroupAttr,
+        GroupMemberQuery, GroupRole, GroupType, HttpRequest, HttpResponse, HttpTransport, ImPush,
+        ImportGroupMember, ImportGroupMsg, ImportMsg, Middleware, ModifyGroupBaseInfo,
+        ModifyGroupMemberInfo, ModifyTopic, MsgElement, NoSpeaking, OnlineState, ProfileItem,
+        ProfileTag, PushAttrs, PushTags, RecentContactQuery, Result, RetryPolicy, RoamMsgQuery,
+        SendGroupMsg, SendMsg, Session, TimErrorCode, TlsSigApiVer2, TlsSigError, TransportFuture,
+    };
+    use futures::StreamExt;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// answers `status` to every request, keeping the requests.
+    #[derive(Debug, Clone)]
+    struct Recorded {
+        status: u16,
+        requests: Arc<Mutex<Vec<HttpRequest>>>,
+    }
+
+    impl HttpTransport for Recorded {
+        fn post(&self, request: HttpRequest) -> TransportFuture<'_> {
+            self.requests.lock().unwrap().push(request);
+            let status = self.status;
+            Box::pin(async move {
+                Ok(HttpResponse {
+                    status,
+                    body: r#"{"ActionStatus":"OK"}"#.to_string(),
+                })
+            })
+        }
+    }
+
+    /// adds a trace header to requests, and fails responses with `status`.
+    #[derive(Debug)]
+    struct Traced(u16);
+
+    impl Middleware for Traced {
+        fn on_request(&self, _: &str, _: &str, request: &mut HttpRequest) {
+            request
+                .headers
+                .push(("x-trace-id".to_string(), "42".to_string()));
+        }
+
+        fn on_response(
+            &self,
+            _: &str,
+            command: &str,
+            response: &mut Result<HttpResponse>,
+            _: Duration,
+        ) {
+            assert_eq!(command, "kick");
+            if let Ok(response) = response {
+                response.status = self.0;
+            }
+        }
+    }
+
+    /// answers after a while, keeping the most requests posted at once.
+    #[derive(Debug, Clone, Default)]
+    struct Delayed {
+        posted: Arc<Mutex<(usize, usize)>>,
+    }
+
+    impl HttpTransport for Delayed {
+        fn post(&self, _: HttpRequest) -> TransportFuture<'_> {
+            Box::pin(async move {
+                {
+                    let mut posted = self.posted.lock().unwrap();
+                    posted.0 += 1;
+                    posted.1 = posted.1.max(posted.0);
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                self.posted.lock().unwrap().0 -= 1;
+                Ok(HttpResponse {
+                    status: 200,
+                    body: r#"{"ActionStatus":"OK","ErrorCode":0}"#.to_string(),
+                })
+            })
+        }
+    }
+
+    fn client(endpoint: &str) -> TimClient {
+        TimClient::new(TlsSigApiVer2::new(1400000000, MOCK_KEY), "admin").with_endpoint(endpoint)
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_account_import() {
+        let (addr, server) = serve(r#"{"ActionStatus":"OK","ErrorCode":0,"ErrorInfo":""}"#);
+        client(&addr)
+            .account_import("10086", Some("Alice"), None)
+            .await
+            .unwrap();
+
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v4/im_open_login_svc/account_import?sdkappid=1400000000&identifier=admin&usersig="));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body, json!({ "UserID": "10086", "Nick": "Alice" }));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_account_check() {
+        let (addr, server) = serve(
+            r#"{"ActionStatus":"OK","ErrorCode":0,"ResultItem":[
+                {"UserID":"a","ResultCode":0,"AccountStatus":"Imported"},
+                {"UserID":"b","ResultCode":0,"AccountStatus":"NotImported"},
+                {"UserID":"c","ResultCode":70402,"ResultInfo":"invalid UserID"}
+            ]}"#,
+        );
+        let checked = client(&addr).account_check(&["a", "b", "c"]).await.unwrap();
+        assert_eq!(checked["a"], AccountStatus::Imported);
+        assert_eq!(checked["b"], AccountStatus::NotImported);
+        assert_eq!(
+            checked["c"],
+            AccountStatus::Failed(TimErrorCode::InvalidParameter)
+        );
+
+        let (_, body) = server.join().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({ "CheckItem": [{ "UserID": "a" }, { "UserID": "b" }, { "UserID": "c" }] })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_multiaccount_import() {
+        let (addr, server) = serve(r#"{"ActionStatus":"OK","ErrorCode":0,"FailAccounts":["b"]}"#);
+        let imported = client(&addr)
+            .multiaccount_import(&["a", "b"])
+            .await
+            .unwrap();
+        assert!(!imported.is_complete());
+        assert_eq!(imported.fail_accounts, ["b"]);
+
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v4/im_open_login_svc/multiaccount_import?"));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body, json!({ "Accounts": ["a", "b"] }));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_account_delete() {
+        let (addr, server) = serve(
+            r#"{"ActionStatus":"OK","ErrorCode":0,"ResultItem":[
+                {"UserID":"a","ResultCode":0,"ResultInfo":""},
+                {"UserID":"b","ResultCode":70107,"ResultInfo":"user not exist"}
+            ]}"#,
+        );
+        let deleted = client(&addr).account_delete(&["a", "b"]).await.unwrap();
+        assert!(deleted[0].is_deleted());
+        assert_eq!(deleted[1].user_id, "b");
+        assert!(!deleted[1].is_deleted());
+
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v4/im_open_login_svc/account_delete?"));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({ "DeleteItem": [{ "UserID": "a" }, { "UserID": "b" }] })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_query_online_status() {
+        let (addr, server) = serve(
+            r#"{"ActionStatus":"OK","ErrorCode":0,"QueryResult":[
+                {"To_Account":"a","Status":"Online","Detail":[{"Platform":"Web","Status":"Online"}]}
+            ]}"#,
+        );
+        let result = client(&addr)
+            .query_online_status(&["a"], true)
+            .await
+            .unwrap();
+        assert_eq!(result.query_result[0].account, "a");
+        assert_eq!(result.query_result[0].detail[0].status, OnlineState::Online);
+
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v4/openim/query_online_status?"));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body, json!({ "IsNeedDetail": 1, "To_Account": ["a"] }));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_send_msg() {
+        let (addr, server) = serve(
+            r#"{"ActionStatus":"OK","ErrorCode":0,"MsgTime":1569910200,"MsgKey":"89541_2574206_1569910200"}"#,
+        );
+        let msg = SendMsg::new("10086", vec![MsgElement::text("hi")]);
+        let sent = client(&addr).send_msg(&msg).await.unwrap();
+        assert_eq!(sent.msg_time, 1569910200);
+        assert_eq!(sent.msg_key, "89541_2574206_1569910200");
+
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v4/openim/sendmsg?"));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["To_Account"], "10086");
+        assert_eq!(body["MsgRandom"], msg.msg_random());
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_batch_send_msg() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"MsgKey":"k1"}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"MsgKey":"k2","ErrorList":[{"To_Account":"600","ErrorCode":70107}]}"#,
+        ]);
+        let recipients: Vec<String> = (0..600).map(|id| id.to_string()).collect();
+        let msg = BatchSendMsg::new(recipients, vec![MsgElement::text("hi")]);
+        let sent = client(&addr).batch_send_msg(&msg).await;
+        assert_eq!(sent.msg_keys, ["k1", "k2"]);
+        assert_eq!(sent.error_list[0].account, "600");
+        assert!(!sent.is_complete());
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].0.starts_with("POST /v4/openim/batchsendmsg?"));
+        let second: serde_json::Value = serde_json::from_str(&requests[1].1).unwrap();
+        assert_eq!(second["To_Account"].as_array().unwrap().len(), 100);
+        assert_eq!(second["MsgRandom"], msg.msg_random());
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_batch_send_msg_partial_failure() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"MsgKey":"k1"}"#,
+            r#"{"ActionStatus":"FAIL","ErrorCode":90001,"ErrorInfo":"json parse error"}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"MsgKey":"k3"}"#,
+        ]);
+        let recipients: Vec<String> = (0..1100).map(|id| id.to_string()).collect();
+        let msg = BatchSendMsg::new(recipients, vec![MsgElement::text("hi")]);
+        let sent = client(&addr).batch_send_msg(&msg).await;
+        server.join().unwrap();
+
+        // the chunks around the failed one are still sent and reported
+        assert_eq!(sent.msg_keys, ["k1", "k3"]);
+        assert_eq!(sent.failed_batches.len(), 1);
+        assert!(matches!(
+            sent.failed_batches[0].1,
+            TlsSigError::RestFailed {
+                code: TimErrorCode::InvalidJson,
+                ..
+            }
+        ));
+        let unsent: Vec<&str> = sent.unsent().collect();
+        assert_eq!(unsent.len(), 500);
+        assert_eq!(unsent[0], "500");
+        assert!(!sent.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_import_msg() {
+        let (addr, server) = serve(r#"{"ActionStatus":"OK","ErrorCode":0}"#);
+        let msg = ImportMsg::new("alice", "bob", 1569910200, vec![MsgElement::text("hi")]);
+        client(&addr).import_msg(&msg).await.unwrap();
+
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v4/openim/importmsg?"));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["MsgTimeStamp"], 1569910200);
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_withdraw_msg() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+        ]);
+        let client = client(&addr);
+        client.withdraw_msg("alice", "bob", "k").await.unwrap();
+        client.set_msg_read("bob", "alice").await.unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .0
+            .starts_with("POST /v4/openim/admin_msgwithdraw?"));
+        let body: serde_json::Value = serde_json::from_str(&requests[0].1).unwrap();
+        assert_eq!(body["MsgKey"], "k");
+        assert!(requests[1]
+            .0
+            .starts_with("POST /v4/openim/admin_set_msg_read?"));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_get_roam_msg() {
+        let (addr, server) = serve(
+            r#"{"ActionStatus":"OK","ErrorCode":0,"Complete":1,"MsgCnt":1,"LastMsgTime":150,"LastMsgKey":"k","MsgList":[
+                {"From_Account":"alice","To_Account":"bob","MsgSeq":1,"MsgRandom":2,"MsgTimeStamp":150,"MsgFlagBits":0,"MsgKey":"k",
+                 "MsgBody":[{"MsgType":"TIMTextElem","MsgContent":{"Text":"hi"}}]}
+            ]}"#,
+        );
+        let query = RoamMsgQuery::new("alice", "bob", 100, 200);
+        let page = client(&addr).get_roam_msg(&query).await.unwrap();
+        assert!(page.is_complete());
+        assert_eq!(page.msg_list[0].msg_key, "k");
+
+        let (request_line, _) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v4/openim/admin_getroammsg?"));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_group_lifecycle() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"GroupId":"@TGS#2J4SZEAEL"}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"GroupInfo":[{"GroupId":"@TGS#2J4SZEAEL","ErrorCode":0,"Type":"Public","Name":"rust"}]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+        ]);
+        let client = client(&addr);
+        let group_id = client
+            .create_group(&CreateGroup::new(GroupType::Public, "rust"))
+            .await
+            .unwrap();
+        assert_eq!(group_id, "@TGS#2J4SZEAEL");
+        let info = client.get_group_info(&[&group_id]).await.unwrap();
+        assert_eq!(info[0].name, "rust");
+        let changes = ModifyGroupBaseInfo::new(&group_id).with_name("rustaceans");
+        client.modify_group_base_info(&changes).await.unwrap();
+        client.destroy_group(&group_id).await.unwrap();
+
+        let requests = server.join().unwrap();
+        let commands: Vec<&str> = requests
+            .iter()
+            .map(|(line, _)| line.split(&['/', '?'][..]).nth(3).unwrap())
+            .collect();
+        assert_eq!(
+            commands,
+            [
+                "create_group",
+                "get_group_info",
+                "modify_group_base_info",
+                "destroy_group"
+            ]
+        );
+        let body: serde_json::Value = serde_json::from_str(&requests[3].1).unwrap();
+        assert_eq!(body, json!({ "GroupId": "@TGS#2J4SZEAEL" }));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_group_members() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"MemberList":[{"Member_Account":"bob","Result":1}]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"MemberNum":2,"MemberList":[{"Member_Account":"alice","Role":"Owner"},{"Member_Account":"bob","Role":"Member"}]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+        ]);
+        let client = client(&addr);
+        let added = client
+            .add_group_member("rust", &["bob"], false)
+            .await
+            .unwrap();
+        assert!(added[0].is_added());
+        let query = GroupMemberQuery::new("rust");
+        let page = client.get_group_member_info(&query).await.unwrap();
+        assert_eq!(page.member_list[0].role, Some(GroupRole::Owner));
+        assert_eq!(query.next_page(&page), None);
+        let changes = ModifyGroupMemberInfo::new("rust", "bob").with_shut_up_time(60);
+        client.modify_group_member_info(&changes).await.unwrap();
+        client
+            .delete_group_member("rust", &["bob"], true, Some("spam"))
+            .await
+            .unwrap();
+
+        let requests = server.join().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&requests[3].1).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "GroupId": "rust",
+                "Silence": 1,
+                "Reason": "spam",
+                "MemberToDel_Account": ["bob"],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_send_group_msg() {
+        let (addr, server) =
+            serve(r#"{"ActionStatus":"OK","ErrorCode":0,"MsgTime":1569910200,"MsgSeq":7}"#);
+        let msg =
+            SendGroupMsg::new("rust", vec![MsgElement::text("hi")]).with_from_account("alice");
+        let sent = client(&addr).send_group_msg(&msg).await.unwrap();
+        assert_eq!(sent.msg_time, 1569910200);
+        assert_eq!(sent.msg_seq, 7);
+
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v4/group_open_http_svc/send_group_msg?"));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["From_Account"], "alice");
+        assert_eq!(body["Random"], msg.random());
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_group_attr() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"GroupAttrAry":[{"key":"topic","value":"rust"}]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+        ]);
+        let client = client(&addr);
+        let attrs = client.get_group_attr("rust").await.unwrap();
+        assert_eq!(attrs, [GroupAttr::new("topic", "rust")]);
+        client.set_group_attr("rust", &attrs).await.unwrap();
+        client
+            .modify_group_attr("rust", &[GroupAttr::new("mode", "quiet")])
+            .await
+            .unwrap();
+        client.delete_group_attr("rust", &["topic"]).await.unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[2]
+            .0
+            .starts_with("POST /v4/group_open_http_svc/modify_group_attr?"));
+        let body: serde_json::Value = serde_json::from_str(&requests[3].1).unwrap();
+        assert_eq!(
+            body,
+            json!({ "GroupId": "rust", "GroupAttr": [{ "key": "topic" }] })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_group_shut_up() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"GroupId":"rust","ShuttedUinList":[{"Member_Account":"bob","ShuttedUntil":1569910200}]}"#,
+        ]);
+        let client = client(&addr);
+        client.forbid_send_msg("rust", &["bob"], 600).await.unwrap();
+        let muted = client.get_group_shutted_uin("rust").await.unwrap();
+        assert_eq!(muted[0].member_account, "bob");
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .0
+            .starts_with("POST /v4/group_open_http_svc/forbid_send_msg?"));
+        let body: serde_json::Value = serde_json::from_str(&requests[0].1).unwrap();
+        assert_eq!(body["ShutUpTime"], 600);
+        assert!(requests[1]
+            .0
+            .starts_with("POST /v4/group_open_http_svc/get_group_shutted_uin?"));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_import_group() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"GroupId":"old"}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"MemberList":[{"Member_Account":"bob","Result":2}]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"ImportMsgResult":[{"MsgSeq":1,"MsgTime":1569910200,"Result":0}]}"#,
+        ]);
+        let client = client(&addr);
+        let group = CreateGroup::new(GroupType::Public, "old").with_group_id("old");
+        let group_id = client.import_group(&group, 1569900000).await.unwrap();
+        let members = [ImportGroupMember::new("bob", 1569900000)];
+        let imported = client
+            .import_group_member(&group_id, &members)
+            .await
+            .unwrap();
+        assert!(!imported[0].is_added());
+        let msgs = [ImportGroupMsg::new(
+            "bob",
+            1569910200,
+            vec![MsgElement::text("hi")],
+        )];
+        let imported = client.import_group_msg(&group_id, &msgs).await.unwrap();
+        assert!(imported[0].is_imported());
+        assert_eq!(imported[0].msg_seq, 1);
+
+        let requests = server.join().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&requests[0].1).unwrap();
+        assert_eq!(body["CreateTime"], 1569900000);
+        assert!(requests[1]
+            .0
+            .starts_with("POST /v4/group_open_http_svc/import_group_member?"));
+        assert!(requests[2]
+            .0
+            .starts_with("POST /v4/group_open_http_svc/import_group_msg?"));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_topics() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"TopicId":"news"}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"TopicResponseInfo":[{"TopicId":"news","TopicName":"news"}]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"DestroyResultItem":[{"ErrorCode":0,"TopicId":"news"}]}"#,
+        ]);
+        let client = client(&addr);
+        let topic_id = client
+            .create_topic(&CreateTopic::new("community", "news"))
+            .await
+            .unwrap();
+        let topics = client.get_topic("community", &[]).await.unwrap();
+        assert_eq!(topics[0].topic_id, topic_id);
+        let changes = ModifyTopic::new("community", &topic_id).with_notification("be nice");
+        client.modify_topic(&changes).await.unwrap();
+        let destroyed = client
+            .destroy_topic("community", &[&topic_id])
+            .await
+            .unwrap();
+        assert!(destroyed[0].is_destroyed());
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .0
+            .starts_with("POST /v4/million_group_open_http_svc/create_topic?"));
+        let body: serde_json::Value = serde_json::from_str(&requests[3].1).unwrap();
+        assert_eq!(
+            body,
+            json!({ "GroupId": "community", "TopicIdList": ["news"] })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_portrait() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"UserProfileItem":[{"To_Account":"alice","ProfileItem":[{"Tag":"Tag_Profile_IM_Nick","Value":"Alice"}],"ResultCode":0}]}"#,
+        ]);
+        let client = client(&addr);
+        client
+            .portrait_set("alice", &[ProfileItem::new(ProfileTag::Nick, "Alice")])
+            .await
+            .unwrap();
+        let profiles = client
+            .portrait_get(&["alice"], &[ProfileTag::Nick])
+            .await
+            .unwrap();
+        assert_eq!(profiles[0].get(&ProfileTag::Nick), Some(&json!("Alice")));
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].0.starts_with("POST /v4/profile/portrait_set?"));
+        let body: serde_json::Value = serde_json::from_str(&requests[1].1).unwrap();
+        assert_eq!(
+            body,
+            json!({ "To_Account": ["alice"], "TagList": ["Tag_Profile_IM_Nick"] })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_friends() {
+        let result = r#"{"ActionStatus":"OK","ErrorCode":0,"ResultItem":[{"To_Account":"bob","ResultCode":0}]}"#;
+        let (addr, server) = serve_all(vec![
+            result,
+            result,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"UserDataItem":[{"To_Account":"bob"}],"FriendNum":1,"CompleteFlag":1}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"InfoItem":[{"To_Account":"bob","Relation":"CheckResult_Type_BothWay"}]}"#,
+            result,
+        ]);
+        let client = client(&addr);
+        let items = [AddFriendItem::new("bob", "Web")];
+        let added = client
+            .friend_add("alice", &items, FriendType::Both, false)
+            .await
+            .unwrap();
+        assert!(added[0].is_ok());
+        let changes = [FriendUpdate::new("bob").with_remark("Bob")];
+        client.friend_update("alice", &changes).await.unwrap();
+        let query = FriendQuery::new("alice");
+        let page = client.friend_get(&query).await.unwrap();
+        assert_eq!(page.user_data_item[0].to_account, "bob");
+        assert_eq!(query.next_page(&page), None);
+        let checked = client
+            .friend_check("alice", &["bob"], FriendType::Both)
+            .await
+            .unwrap();
+        assert_eq!(checked[0].relation, FriendRelation::BothWay);
+        client
+            .friend_delete("alice", &["bob"], FriendType::Both)
+            .await
+            .unwrap();
+
+        let requests = server.join().unwrap();
+        let commands: Vec<&str> = requests
+            .iter()
+            .map(|(line, _)| line.split(&['/', '?'][..]).nth(3).unwrap())
+            .collect();
+        assert_eq!(
+            commands,
+            [
+                "friend_add",
+                "friend_update",
+                "friend_get",
+                "friend_check",
+                "friend_delete"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_blacklist() {
+        let result = r#"{"ActionStatus":"OK","ErrorCode":0,"ResultItem":[{"To_Account":"bob","ResultCode":0}]}"#;
+        let (addr, server) = serve_all(vec![
+            result,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"BlackListItem":[{"To_Account":"bob","AddBlackTimeStamp":1569910200}],"StartIndex":0,"CurruentSequence":1}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"BlackListCheckItem":[{"To_Account":"bob","Relation":"BlackCheckResult_Type_AWithB"}]}"#,
+            result,
+        ]);
+        let client = client(&addr);
+        let added = client.black_list_add("alice", &["bob"]).await.unwrap();
+        assert!(added[0].is_ok());
+        let query = BlacklistQuery::new("alice");
+        let page = client.black_list_get(&query).await.unwrap();
+        assert_eq!(page.black_list_item[0].to_account, "bob");
+        assert_eq!(query.next_page(&page), None);
+        let checked = client
+            .black_list_check("alice", &["bob"], FriendType::Both)
+            .await
+            .unwrap();
+        assert_eq!(checked[0].relation, BlacklistRelation::AWithB);
+        client.black_list_delete("alice", &["bob"]).await.unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[3].0.starts_with("POST /v4/sns/black_list_delete?"));
+        let body: serde_json::Value = serde_json::from_str(&requests[3].1).unwrap();
+        assert_eq!(
+            body,
+            json!({ "From_Account": "alice", "To_Account": ["bob"] })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_recent_contacts() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"CompleteFlag":0,"TimeStamp":1569910200,"StartIndex":1,"TopTimeStamp":0,"TopStartIndex":0,"SessionItem":[{"Type":1,"To_Account":"bob","MsgTime":1569910200}]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"CompleteFlag":1,"SessionItem":[{"Type":2,"GroupId":"rust","MsgTime":1569910100}]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+        ]);
+        let client = client(&addr);
+        let contacts = client.get_all_recent_contacts("alice").await.unwrap();
+        let sessions: Vec<Session> = contacts.iter().map(|contact| contact.session()).collect();
+        assert_eq!(
+            sessions,
+            [
+                Session::C2c("bob".to_string()),
+                Session::Group("rust".to_string())
+            ]
+        );
+        client
+            .delete_recent_contact("alice", &sessions[0], false)
+            .await
+            .unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .0
+            .starts_with("POST /v4/recentcontact/get_list?"));
+        let second: serde_json::Value = serde_json::from_str(&requests[1].1).unwrap();
+        assert_eq!(second["StartIndex"], 1);
+        assert!(requests[2].0.starts_with("POST /v4/recentcontact/delete?"));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_all_member_push() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"TaskId":"53D6D8C0"}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"Reports":[{"TaskId":"53D6D8C0","State":2,"Total":50}]}"#,
+        ]);
+        let client = client(&addr);
+        let push = ImPush::new(vec![MsgElement::text("hi")]);
+        let task_id = client.all_member_push(&push).await.unwrap();
+        assert_eq!(task_id, "53D6D8C0");
+        let reports = client.im_get_push_report(&[&task_id]).await.unwrap();
+        assert!(reports[0].is_finished());
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .0
+            .starts_with("POST /v4/all_member_push/im_push?"));
+        let body: serde_json::Value = serde_json::from_str(&requests[0].1).unwrap();
+        assert_eq!(body["MsgRandom"], push.msg_random());
+        let body: serde_json::Value = serde_json::from_str(&requests[1].1).unwrap();
+        assert_eq!(body, json!({ "TaskIds": ["53D6D8C0"] }));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_push_attrs_and_tags() {
+        let ok = r#"{"ActionStatus":"OK","ErrorCode":0}"#;
+        let (addr, server) = serve_all(vec![
+            ok,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"AttrNames":{"0":"sex"}}"#,
+            ok,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"UserAttrs":[{"To_Account":"alice","Attrs":{"sex":"female"}}]}"#,
+            ok,
+            ok,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"UserTags":[{"To_Account":"alice","Tags":["vip"]}]}"#,
+            ok,
+            ok,
+        ]);
+        let client = client(&addr);
+        let names: BTreeMap<u8, String> = vec![(0, "sex".to_string())].into_iter().collect();
+        client.im_set_attr_name(&names).await.unwrap();
+        assert_eq!(client.im_get_attr_name().await.unwrap(), names);
+        let attrs = [PushAttrs::new("alice").with_attr("sex", "female")];
+        client.im_set_attr(&attrs).await.unwrap();
+        assert_eq!(client.im_get_attr(&["alice"]).await.unwrap(), attrs);
+        client.im_remove_attr(&["alice"], &["sex"]).await.unwrap();
+        let tags = [PushTags::new("alice", &["vip"])];
+        client.im_add_tag(&tags).await.unwrap();
+        assert_eq!(client.im_get_tag(&["alice"]).await.unwrap(), tags);
+        client.im_remove_tag(&tags).await.unwrap();
+        client.im_remove_all_tags(&["alice"]).await.unwrap();
+
+        let requests = server.join().unwrap();
+        let commands: Vec<&str> = requests
+            .iter()
+            .map(|(line, _)| line.split(&['/', '?'][..]).nth(3).unwrap())
+            .collect();
+        assert_eq!(
+            commands,
+            [
+                "im_set_attr_name",
+                "im_get_attr_name",
+                "im_set_attr",
+                "im_get_attr",
+                "im_remove_attr",
+                "im_add_tag",
+                "im_get_tag",
+                "im_remove_tag",
+                "im_remove_all_tags",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_stats() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"Result":[{"Date":"20160607","ActiveUserNum":"120"}]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"File":[{"URL":"https://download.tim.qq.com/2015120121.gz","FileSize":4127}]}"#,
+        ]);
+        let client = client(&addr);
+        let infos = client.get_app_info(&["ActiveUserNum"]).await.unwrap();
+        assert_eq!(infos[0].active_user_num, 120);
+        let files = client
+            .get_history_data(ChatType::Group, "2015120121")
+            .await
+            .unwrap();
+        assert_eq!(files[0].file_size, 4127);
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .0
+            .starts_with("POST /v4/openconfigsvr/getappinfo?"));
+        let body: serde_json::Value = serde_json::from_str(&requests[0].1).unwrap();
+        assert_eq!(body, json!({ "RequestField": ["ActiveUserNum"] }));
+        assert!(requests[1]
+            .0
+            .starts_with("POST /v4/open_msg_svc/get_history?"));
+        let body: serde_json::Value = serde_json::from_str(&requests[1].1).unwrap();
+        assert_eq!(
+            body,
+            json!({ "ChatType": "Group", "MsgTime": "2015120121" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_no_speaking() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"C2CmsgNospeakingTime":0,"GroupmsgNospeakingTime":7200}"#,
+        ]);
+        let client = client(&addr);
+        let muted = NoSpeaking::unmuted().with_group_msg_nospeaking_time(7200);
+        client.set_no_speaking("alice", muted).await.unwrap();
+        assert_eq!(client.get_no_speaking("alice").await.unwrap(), muted);
+
+        let requests = server.join().unwrap();
+        assert!(requests[0]
+            .0
+            .starts_with("POST /v4/openconfigsvr/setnospeaking?"));
+        let body: serde_json::Value = serde_json::from_str(&requests[0].1).unwrap();
+        assert_eq!(body["GroupmsgNospeakingTime"], 7200);
+        let body: serde_json::Value = serde_json::from_str(&requests[1].1).unwrap();
+        assert_eq!(body, json!({ "Get_Account": "alice" }));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_call_raw() {
+        let (addr, server) = serve(
+            r#"{"ActionStatus":"OK","ErrorCode":0,"ErrorInfo":"","C2CmsgNospeakingTime":60}"#,
+        );
+        let payload = client(&addr)
+            .call_raw(
+                "openconfigsvr",
+                "getnospeaking",
+                json!({ "Get_Account": "alice" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(payload, json!({ "C2CmsgNospeakingTime": 60 }));
+
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /v4/openconfigsvr/getnospeaking?"));
+        assert!(request_line.contains("&random="));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body, json!({ "Get_Account": "alice" }));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_paged_streams() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"Complete":0,"LastMsgTime":150,"LastMsgKey":"k2","MsgList":[
+                {"From_Account":"alice","To_Account":"bob","MsgTimeStamp":160,"MsgKey":"k1"},
+                {"From_Account":"alice","To_Account":"bob","MsgTimeStamp":150,"MsgKey":"k2"}
+            ]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"Complete":1,"MsgList":[
+                {"From_Account":"bob","To_Account":"alice","MsgTimeStamp":120,"MsgKey":"k3"}
+            ]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"MemberNum":3,"MemberList":[{"Member_Account":"alice"},{"Member_Account":"bob"}],"Next":"c1"}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"MemberNum":3,"MemberList":[{"Member_Account":"carol"}]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"CompleteFlag":0,"TimeStamp":1569910200,"StartIndex":1,"SessionItem":[{"Type":1,"To_Account":"bob","MsgTime":1569910200}]}"#,
+            r#"{"ActionStatus":"FAIL","ErrorCode":90001,"ErrorInfo":"json parse error"}"#,
+        ]);
+        let client = client(&addr);
+        let query = RoamMsgQuery::new("alice", "bob", 100, 200).with_max_cnt(2);
+        let mut msgs = client.roam_msgs(query);
+        let mut keys = Vec::new();
+        while let Some(msg) = msgs.next().await {
+            keys.push(msg.unwrap().msg_key);
+        }
+        assert_eq!(keys, ["k1", "k2", "k3"]);
+
+        let query = GroupMemberQuery::new("@TGS#_rust").with_limit(2);
+        let members: Vec<String> = client
+            .group_members(query)
+            .map(|member| member.unwrap().member_account)
+            .collect()
+            .await;
+        assert_eq!(members, ["alice", "bob", "carol"]);
+
+        let mut contacts = client.recent_contacts(RecentContactQuery::new("alice"));
+        let contact = contacts.next().await.unwrap().unwrap();
+        assert_eq!(contact.session(), Session::C2c("bob".to_string()));
+        assert!(contacts.next().await.unwrap().is_err());
+        assert!(contacts.next().await.is_none());
+
+        let requests = server.join().unwrap();
+        let second: serde_json::Value = serde_json::from_str(&requests[1].1).unwrap();
+        assert_eq!(second["LastMsgKey"], "k2");
+        let fourth: serde_json::Value = serde_json::from_str(&requests[3].1).unwrap();
+        assert_eq!(fourth["Next"], "c1");
+        let sixth: serde_json::Value = serde_json::from_str(&requests[5].1).unwrap();
+        assert_eq!(sixth["StartIndex"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_for_each_concurrent() {
+        let transport = Delayed::default();
+        let results = client("http://127.0.0.1:1")
+            .with_transport(transport.clone())
+            .for_each_concurrent(0..10, 3, |client, i| async move {
+                client.kick(&i.to_string()).await.map(|_| i)
+            })
+            .await;
+        let done: Vec<u32> = results.into_iter().map(|result| result.unwrap()).collect();
+        assert_eq!(done, (0..10).collect::<Vec<_>>());
+        assert_eq!(*transport.posted.lock().unwrap(), (0, 3));
+
+        let transport = Delayed::default();
+        let client = client("http://127.0.0.1:1")
+            .with_transport(transport.clone())
+            .with_max_concurrency(2);
+        let clone = client.clone();
+        let results = client
+            .for_each_concurrent(0..10, 10, |_, i| {
+                let client = if i % 2 == 0 { &client } else { &clone };
+                client.kick("alice")
+            })
+            .await;
+        assert!(results.iter().all(|result| result.is_ok()));
+        assert_eq!(*transport.posted.lock().unwrap(), (0, 2));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_import_accounts_from() {
+        let (addr, server) = serve_all(vec![
+            r#"{"ActionStatus":"OK","ErrorCode":0,"FailAccounts":["7","8"]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"FailAccounts":["8"]}"#,
+            r#"{"ActionStatus":"OK","ErrorCode":0,"FailAccounts":["8"]}"#,
+            r#"{"ActionStatus":"FAIL","ErrorCode":70107,"ErrorInfo":"user not exist"}"#,
+        ]);
+        let report = client(&addr)
+            .import_accounts_from((0..150).map(|i| i.to_string()))
+            .await;
+        assert_eq!(report.succeeded.len(), 99);
+        assert_eq!(report.failed, ["8"]);
+        assert_eq!(report.failed_batches[0].0.len(), 50);
+        assert_eq!(report.not_imported().count(), 51);
+
+        let requests = server.join().unwrap();
+        let bodies: Vec<serde_json::Value> = requests
+            .iter()
+            .map(|(_, body)| serde_json::from_str(body).unwrap())
+            .collect();
+        assert_eq!(bodies[0]["Accounts"].as_array().unwrap().len(), 100);
+        assert_eq!(bodies[1], json!({ "Accounts": ["7", "8"] }));
+        assert_eq!(bodies[2], json!({ "Accounts": ["8"] }));
+        assert_eq!(bodies[3]["Accounts"][0], "100");
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_failed() {
+        let (addr, server) =
+            serve(r#"{"ActionStatus":"FAIL","ErrorCode":70107,"ErrorInfo":"user not exist"}"#);
+        let resp = client(&addr).kick("10086").await;
+        assert!(matches!(
+            resp,
+            Err(TlsSigError::RestFailed {
+                code: TimErrorCode::AccountNotFound,
+                ..
+            })
+        ));
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_resigns() {
+        const EXPIRED: &str =
+            r#"{"ActionStatus":"FAIL","ErrorCode":70001,"ErrorInfo":"usersig expired"}"#;
+
+        let (addr, server) = serve_all(vec![EXPIRED, r#"{"ActionStatus":"OK"}"#]);
+        client(&addr).kick("10086").await.unwrap();
+        let requests = server.join().unwrap();
+        assert_ne!(requests[0].0, requests[1].0);
+
+        // retried only once
+        let (addr, server) = serve_all(vec![EXPIRED, EXPIRED]);
+        let resp = client(&addr).kick("10086").await;
+        assert!(matches!(
+            resp,
+            Err(TlsSigError::RestFailed {
+                code: TimErrorCode::SigExpired,
+                ..
+            })
+        ));
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_retries() {
+        const BUSY: &str =
+            r#"{"ActionStatus":"FAIL","ErrorCode":70500,"ErrorInfo":"internal error"}"#;
+        let policy =
+            RetryPolicy::default().with_backoff(Duration::from_millis(1), Duration::from_millis(1));
+
+        let (addr, server) = serve_all(vec![BUSY, BUSY, r#"{"ActionStatus":"OK"}"#]);
+        let client = client(&addr).with_retry_policy(policy.clone());
+        client.kick("10086").await.unwrap();
+        assert_eq!(server.join().unwrap().len(), 3);
+
+        let (addr, server) = serve_all(vec![BUSY]);
+        let client = self::client(&addr).with_retry_policy(RetryPolicy::never());
+        assert!(client.kick("10086").await.is_err());
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_transport() {
+        let transport = Recorded {
+            status: 200,
+            requests: Arc::default(),
+        };
+        let client = TimClient::new(TlsSigApiVer2::new(1400000000, MOCK_KEY), "admin")
+            .with_transport(transport.clone());
+        client.kick("10086").await.unwrap();
+
+        let requests = transport.requests.lock().unwrap().clone();
+        assert_eq!(requests[0].url.path(), "/v4/im_open_login_svc/kick");
+        assert_eq!(requests[0].url.host_str(), Some("console.tim.qq.com"));
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body, json!({ "UserID": "10086" }));
+
+        let client = client
+            .with_transport(Recorded {
+                status: 502,
+                requests: Arc::default(),
+            })
+            .with_retry_policy(RetryPolicy::never());
+        assert!(matches!(
+            client.kick("10086").await,
+            Err(TlsSigError::RestHttpStatus(502))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_middleware() {
+        let transport = Recorded {
+            status: 200,
+            requests: Arc::default(),
+        };
+        let client = TimClient::new(TlsSigApiVer2::new(1400000000, MOCK_KEY), "admin")
+            .with_transport(transport.clone())
+            .with_retry_policy(RetryPolicy::never());
+        client
+            .clone()
+            .with_middleware(Traced(200))
+            .kick("10086")
+            .await
+            .unwrap();
+        let requests = transport.requests.lock().unwrap().clone();
+        assert!(requests[0]
+            .headers
+            .contains(&("x-trace-id".to_string(), "42".to_string())));
+
+        let resp = client.with_middleware(Traced(503)).kick("10086").await;
+        assert!(matches!(resp, Err(TlsSigError::RestHttpStatus(503))));
+    }
+
+    #[tokio::test]
+    async fn test_tim_client_unreachable() {
+        // nothing listens on the discard port
+        let resp = client("http://127.0.0.1:9").kick("10086").await;
+        assert!(matches!(resp, Err(TlsSigError::RestUnavailable(_))));
+    }
+}
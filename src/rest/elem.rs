@@ -0,0 +1,519 @@
+use serde::Serialize;
+
+use super::MsgElement;
+
+/// the `Download_Flag` of uploaded files, downloaded from their urls.
+const DOWNLOAD_BY_URL: u8 = 2;
+
+/// the element of `msg_type` with the fields of `content`.
+fn element<T: Serialize>(msg_type: &str, content: &T) -> MsgElement {
+    let content = serde_json::to_value(content).expect("msg elements serialize to json");
+    MsgElement::new(msg_type, content)
+}
+
+/// A `TIMTextElem`, see
+/// [the message elements](https://cloud.tencent.com/document/product/269/2720).
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{MsgElement, TextElem};
+///
+/// assert_eq!(MsgElement::from(TextElem::new("hi")), MsgElement::text("hi"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TextElem {
+    text: String,
+}
+
+impl TextElem {
+    pub fn new(text: &str) -> Self {
+        TextElem {
+            text: text.to_string(),
+        }
+    }
+}
+
+impl From<TextElem> for MsgElement {
+    fn from(elem: TextElem) -> Self {
+        element("TIMTextElem", &elem)
+    }
+}
+
+/// A `TIMCustomElem`, a payload of the app passed through to the clients.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{CustomElem, MsgElement};
+///
+/// let elem: MsgElement = CustomElem::new(r#"{"order":42}"#)
+///     .with_desc("order paid")
+///     .into();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CustomElem {
+    data: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    desc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ext: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sound: Option<String>,
+}
+
+impl CustomElem {
+    pub fn new(data: &str) -> Self {
+        CustomElem {
+            data: data.to_string(),
+            ..CustomElem::default()
+        }
+    }
+
+    /// the text of the offline push of the message.
+    pub fn with_desc(mut self, desc: &str) -> Self {
+        self.desc = Some(desc.to_string());
+        self
+    }
+
+    /// the payload of the offline push of the message.
+    pub fn with_ext(mut self, ext: &str) -> Self {
+        self.ext = Some(ext.to_string());
+        self
+    }
+
+    /// the sound of the offline push of the message on iOS.
+    pub fn with_sound(mut self, sound: &str) -> Self {
+        self.sound = Some(sound.to_string());
+        self
+    }
+}
+
+impl From<CustomElem> for MsgElement {
+    fn from(elem: CustomElem) -> Self {
+        element("TIMCustomElem", &elem)
+    }
+}
+
+/// A `TIMFaceElem`, an emoji of the app by its index.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FaceElem {
+    index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+}
+
+impl FaceElem {
+    pub fn new(index: u32) -> Self {
+        FaceElem { index, data: None }
+    }
+
+    pub fn with_data(mut self, data: &str) -> Self {
+        self.data = Some(data.to_string());
+        self
+    }
+}
+
+impl From<FaceElem> for MsgElement {
+    fn from(elem: FaceElem) -> Self {
+        element("TIMFaceElem", &elem)
+    }
+}
+
+/// A `TIMLocationElem`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LocationElem {
+    desc: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+impl LocationElem {
+    /// the place `desc` at `latitude` and `longitude`.
+    pub fn new(desc: &str, latitude: f64, longitude: f64) -> Self {
+        LocationElem {
+            desc: desc.to_string(),
+            latitude,
+            longitude,
+        }
+    }
+}
+
+impl From<LocationElem> for MsgElement {
+    fn from(elem: LocationElem) -> Self {
+        element("TIMLocationElem", &elem)
+    }
+}
+
+/// A `TIMImageElem` of an uploaded image, in the sizes added by
+/// [`with_image`].
+///
+/// [`with_image`]: #method.with_image
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{ImageElem, ImageInfo, MsgElement};
+///
+/// let url = "https://example.com/cat.jpg";
+/// let elem: MsgElement = ImageElem::new("1853095_D61040894AC3DE44CDFFFB3EC7EB720F", 1)
+///     .with_image(ImageInfo::original(url, 1853095, 2448, 3264))
+///     .with_image(ImageInfo::thumbnail(url, 2438, 149, 198))
+///     .into();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImageElem {
+    #[serde(rename = "UUID")]
+    uuid: String,
+    #[serde(rename = "ImageFormat")]
+    image_format: u32,
+    #[serde(rename = "ImageInfoArray")]
+    image_info_array: Vec<ImageInfo>,
+}
+
+impl ImageElem {
+    /// the image `uuid` of `image_format`: 1 for JPG, 2 for GIF, 3 for PNG,
+    /// 4 for BMP or 255 for others.
+    pub fn new(uuid: &str, image_format: u32) -> Self {
+        ImageElem {
+            uuid: uuid.to_string(),
+            image_format,
+            image_info_array: Vec::new(),
+        }
+    }
+
+    pub fn with_image(mut self, info: ImageInfo) -> Self {
+        self.image_info_array.push(info);
+        self
+    }
+}
+
+impl From<ImageElem> for MsgElement {
+    fn from(elem: ImageElem) -> Self {
+        element("TIMImageElem", &elem)
+    }
+}
+
+/// A size of the image of an [`ImageElem`].
+///
+/// [`ImageElem`]: struct.ImageElem.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ImageInfo {
+    /// 1 for the original image, 2 for the big one, 3 for the thumbnail.
+    #[serde(rename = "Type")]
+    image_type: u8,
+    size: u64,
+    width: u32,
+    height: u32,
+    #[serde(rename = "URL")]
+    url: String,
+}
+
+impl ImageInfo {
+    /// the original image at `url`, of `size` bytes.
+    pub fn original(url: &str, size: u64, width: u32, height: u32) -> Self {
+        ImageInfo::new(1, url, size, width, height)
+    }
+
+    pub fn big(url: &str, size: u64, width: u32, height: u32) -> Self {
+        ImageInfo::new(2, url, size, width, height)
+    }
+
+    pub fn thumbnail(url: &str, size: u64, width: u32, height: u32) -> Self {
+        ImageInfo::new(3, url, size, width, height)
+    }
+
+    fn new(image_type: u8, url: &str, size: u64, width: u32, height: u32) -> Self {
+        ImageInfo {
+            image_type,
+            size,
+            width,
+            height,
+            url: url.to_string(),
+        }
+    }
+}
+
+/// A `TIMSoundElem` of an uploaded voice message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SoundElem {
+    #[serde(rename = "Url")]
+    url: String,
+    #[serde(rename = "UUID", skip_serializing_if = "Option::is_none")]
+    uuid: Option<String>,
+    #[serde(rename = "Size")]
+    size: u64,
+    #[serde(rename = "Second")]
+    second: u32,
+    #[serde(rename = "Download_Flag")]
+    download_flag: u8,
+}
+
+impl SoundElem {
+    /// the voice at `url` of `size` bytes, lasting `second`s.
+    pub fn new(url: &str, size: u64, second: u32) -> Self {
+        SoundElem {
+            url: url.to_string(),
+            uuid: None,
+            size,
+            second,
+            download_flag: DOWNLOAD_BY_URL,
+        }
+    }
+
+    pub fn with_uuid(mut self, uuid: &str) -> Self {
+        self.uuid = Some(uuid.to_string());
+        self
+    }
+}
+
+impl From<SoundElem> for MsgElement {
+    fn from(elem: SoundElem) -> Self {
+        element("TIMSoundElem", &elem)
+    }
+}
+
+/// A `TIMVideoFileElem` of an uploaded video with its thumbnail.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{MsgElement, VideoFileElem};
+///
+/// let elem: MsgElement = VideoFileElem::new("https://example.com/cat.mp4", 1194603, 5, "mp4")
+///     .with_thumb("https://example.com/cat.jpg", 13907, 720, 1280, "JPG")
+///     .into();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct VideoFileElem {
+    video_url: String,
+    #[serde(rename = "VideoUUID", skip_serializing_if = "Option::is_none")]
+    video_uuid: Option<String>,
+    video_size: u64,
+    video_second: u32,
+    video_format: String,
+    video_download_flag: u8,
+    thumb_url: String,
+    #[serde(rename = "ThumbUUID", skip_serializing_if = "Option::is_none")]
+    thumb_uuid: Option<String>,
+    thumb_size: u64,
+    thumb_width: u32,
+    thumb_height: u32,
+    thumb_format: String,
+    thumb_download_flag: u8,
+}
+
+impl VideoFileElem {
+    /// the video at `url` of `size` bytes, lasting `second`s, e.g. in the
+    /// format `mp4`.
+    pub fn new(url: &str, size: u64, second: u32, format: &str) -> Self {
+        VideoFileElem {
+            video_url: url.to_string(),
+            video_uuid: None,
+            video_size: size,
+            video_second: second,
+            video_format: format.to_string(),
+            video_download_flag: DOWNLOAD_BY_URL,
+            thumb_url: String::new(),
+            thumb_uuid: None,
+            thumb_size: 0,
+            thumb_width: 0,
+            thumb_height: 0,
+            thumb_format: String::new(),
+            thumb_download_flag: DOWNLOAD_BY_URL,
+        }
+    }
+
+    pub fn with_video_uuid(mut self, uuid: &str) -> Self {
+        self.video_uuid = Some(uuid.to_string());
+        self
+    }
+
+    /// the thumbnail at `url` of `size` bytes, e.g. in the format `JPG`.
+    pub fn with_thumb(
+        mut self,
+        url: &str,
+        size: u64,
+        width: u32,
+        height: u32,
+        format: &str,
+    ) -> Self {
+        self.thumb_url = url.to_string();
+        self.thumb_size = size;
+        self.thumb_width = width;
+        self.thumb_height = height;
+        self.thumb_format = format.to_string();
+        self
+    }
+
+    pub fn with_thumb_uuid(mut self, uuid: &str) -> Self {
+        self.thumb_uuid = Some(uuid.to_string());
+        self
+    }
+}
+
+impl From<VideoFileElem> for MsgElement {
+    fn from(elem: VideoFileElem) -> Self {
+        element("TIMVideoFileElem", &elem)
+    }
+}
+
+/// A `TIMFileElem` of an uploaded file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileElem {
+    #[serde(rename = "Url")]
+    url: String,
+    #[serde(rename = "UUID", skip_serializing_if = "Option::is_none")]
+    uuid: Option<String>,
+    #[serde(rename = "FileSize")]
+    file_size: u64,
+    #[serde(rename = "FileName")]
+    file_name: String,
+    #[serde(rename = "Download_Flag")]
+    download_flag: u8,
+}
+
+impl FileElem {
+    /// the file `file_name` at `url` of `file_size` bytes.
+    pub fn new(url: &str, file_size: u64, file_name: &str) -> Self {
+        FileElem {
+            url: url.to_string(),
+            uuid: None,
+            file_size,
+            file_name: file_name.to_string(),
+            download_flag: DOWNLOAD_BY_URL,
+        }
+    }
+
+    pub fn with_uuid(mut self, uuid: &str) -> Self {
+        self.uuid = Some(uuid.to_string());
+        self
+    }
+}
+
+impl From<FileElem> for MsgElement {
+    fn from(elem: FileElem) -> Self {
+        element("TIMFileElem", &elem)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CustomElem, FaceElem, FileElem, ImageElem, ImageInfo, LocationElem};
+    use super::{SoundElem, TextElem, VideoFileElem};
+    use crate::MsgElement;
+    use serde_json::json;
+
+    fn body(elem: impl Into<MsgElement>) -> serde_json::Value {
+        serde_json::to_value(elem.into()).unwrap()
+    }
+
+    #[test]
+    fn test_text_custom_face_location_elems() {
+        assert_eq!(
+            body(TextElem::new("hi")),
+            json!({ "MsgType": "TIMTextElem", "MsgContent": { "Text": "hi" } })
+        );
+        assert_eq!(
+            body(
+                CustomElem::new("{}")
+                    .with_desc("paid")
+                    .with_sound("dingdong.aiff")
+            ),
+            json!({
+                "MsgType": "TIMCustomElem",
+                "MsgContent": { "Data": "{}", "Desc": "paid", "Sound": "dingdong.aiff" },
+            })
+        );
+        assert_eq!(
+            body(FaceElem::new(1).with_data("smile")),
+            json!({ "MsgType": "TIMFaceElem", "MsgContent": { "Index": 1, "Data": "smile" } })
+        );
+        assert_eq!(
+            body(LocationElem::new("Shenzhen", 22.54, 114.06)),
+            json!({
+                "MsgType": "TIMLocationElem",
+                "MsgContent": { "Desc": "Shenzhen", "Latitude": 22.54, "Longitude": 114.06 },
+            })
+        );
+    }
+
+    #[test]
+    fn test_image_elem() {
+        let url = "https://example.com/cat.jpg";
+        let elem = ImageElem::new("cat", 1)
+            .with_image(ImageInfo::original(url, 1853095, 2448, 3264))
+            .with_image(ImageInfo::thumbnail(url, 2438, 149, 198));
+        assert_eq!(
+            body(elem),
+            json!({
+                "MsgType": "TIMImageElem",
+                "MsgContent": {
+                    "UUID": "cat",
+                    "ImageFormat": 1,
+                    "ImageInfoArray": [
+                        { "Type": 1, "Size": 1853095, "Width": 2448, "Height": 3264, "URL": url },
+                        { "Type": 3, "Size": 2438, "Width": 149, "Height": 198, "URL": url },
+                    ],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_file_elems() {
+        assert_eq!(
+            body(SoundElem::new("https://example.com/hi.amr", 2563, 3).with_uuid("hi")),
+            json!({
+                "MsgType": "TIMSoundElem",
+                "MsgContent": {
+                    "Url": "https://example.com/hi.amr",
+                    "UUID": "hi",
+                    "Size": 2563,
+                    "Second": 3,
+                    "Download_Flag": 2,
+                },
+            })
+        );
+        assert_eq!(
+            body(FileElem::new("https://example.com/a.txt", 27, "a.txt")),
+            json!({
+                "MsgType": "TIMFileElem",
+                "MsgContent": {
+                    "Url": "https://example.com/a.txt",
+                    "FileSize": 27,
+                    "FileName": "a.txt",
+                    "Download_Flag": 2,
+                },
+            })
+        );
+
+        let video = VideoFileElem::new("https://example.com/cat.mp4", 1194603, 5, "mp4")
+            .with_thumb("https://example.com/cat.jpg", 13907, 720, 1280, "JPG");
+        assert_eq!(
+            body(video),
+            json!({
+                "MsgType": "TIMVideoFileElem",
+                "MsgContent": {
+                    "VideoUrl": "https://example.com/cat.mp4",
+                    "VideoSize": 1194603,
+                    "VideoSecond": 5,
+                    "VideoFormat": "mp4",
+                    "VideoDownloadFlag": 2,
+                    "ThumbUrl": "https://example.com/cat.jpg",
+                    "ThumbSize": 13907,
+                    "ThumbWidth": 720,
+                    "ThumbHeight": 1280,
+                    "ThumbFormat": "JPG",
+                    "ThumbDownloadFlag": 2,
+                },
+            })
+        );
+    }
+}
@@ -0,0 +1,1203 @@
+use serde::{Deserialize, Serialize};
+
+/// The type of a group, see
+/// [the group types](https://cloud.tencent.com/document/product/269/1502).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GroupType {
+    /// joined by applying, like QQ groups.
+    Public,
+    /// joined by invitation, like WeChat groups.
+    Private,
+    /// a meeting, joined and left freely.
+    ChatRoom,
+    /// a live-streaming room with unlimited members.
+    AVChatRoom,
+    /// a community with topics.
+    Community,
+    /// a type unknown to this crate, never sent.
+    #[serde(other)]
+    Unknown,
+}
+
+/// How accounts may join a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ApplyJoinOption {
+    FreeAccess,
+    NeedPermission,
+    DisableApply,
+}
+
+/// `{"Member_Account": ..}`, a member listed in group bodies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct Member {
+    #[serde(rename = "Member_Account")]
+    member_account: String,
+}
+
+/// A group created by `create_group`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{ApplyJoinOption, CreateGroup, GroupType};
+///
+/// let group = CreateGroup::new(GroupType::Public, "rustaceans")
+///     .with_owner("alice")
+///     .with_introduction("all about rust")
+///     .with_apply_join_option(ApplyJoinOption::FreeAccess)
+///     .with_members(&["bob", "carol"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CreateGroup {
+    #[serde(rename = "Owner_Account", skip_serializing_if = "Option::is_none")]
+    owner_account: Option<String>,
+    #[serde(rename = "Type")]
+    group_type: GroupType,
+    #[serde(rename = "GroupId", skip_serializing_if = "Option::is_none")]
+    group_id: Option<String>,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Introduction", skip_serializing_if = "Option::is_none")]
+    introduction: Option<String>,
+    #[serde(rename = "Notification", skip_serializing_if = "Option::is_none")]
+    notification: Option<String>,
+    #[serde(rename = "FaceUrl", skip_serializing_if = "Option::is_none")]
+    face_url: Option<String>,
+    #[serde(rename = "MaxMemberCount", skip_serializing_if = "Option::is_none")]
+    max_member_count: Option<u32>,
+    #[serde(rename = "ApplyJoinOption", skip_serializing_if = "Option::is_none")]
+    apply_join_option: Option<ApplyJoinOption>,
+    #[serde(rename = "MemberList", skip_serializing_if = "Vec::is_empty")]
+    member_list: Vec<Member>,
+}
+
+impl CreateGroup {
+    /// a group of `group_type` named `name`, without an owner, so that it is
+    /// managed by the admin.
+    pub fn new(group_type: GroupType, name: &str) -> Self {
+        CreateGroup {
+            owner_account: None,
+            group_type,
+            group_id: None,
+            name: name.to_string(),
+            introduction: None,
+            notification: None,
+            face_url: None,
+            max_member_count: None,
+            apply_join_option: None,
+            member_list: Vec::new(),
+        }
+    }
+
+    pub fn with_owner(mut self, owner_account: &str) -> Self {
+        self.owner_account = Some(owner_account.to_string());
+        self
+    }
+
+    /// create the group as `group_id` instead of a generated id.
+    pub fn with_group_id(mut self, group_id: &str) -> Self {
+        self.group_id = Some(group_id.to_string());
+        self
+    }
+
+    pub fn with_introduction(mut self, introduction: &str) -> Self {
+        self.introduction = Some(introduction.to_string());
+        self
+    }
+
+    pub fn with_notification(mut self, notification: &str) -> Self {
+        self.notification = Some(notification.to_string());
+        self
+    }
+
+    pub fn with_face_url(mut self, face_url: &str) -> Self {
+        self.face_url = Some(face_url.to_string());
+        self
+    }
+
+    pub fn with_max_member_count(mut self, count: u32) -> Self {
+        self.max_member_count = Some(count);
+        self
+    }
+
+    pub fn with_apply_join_option(mut self, option: ApplyJoinOption) -> Self {
+        self.apply_join_option = Some(option);
+        self
+    }
+
+    /// the initial members besides the owner.
+    pub fn with_members(mut self, members: &[&str]) -> Self {
+        self.member_list = members
+            .iter()
+            .map(|member| Member {
+                member_account: member.to_string(),
+            })
+            .collect();
+        self
+    }
+}
+
+/// The changes of the base info of a group made by `modify_group_base_info`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::ModifyGroupBaseInfo;
+///
+/// let changes = ModifyGroupBaseInfo::new("@TGS#2J4SZEAEL")
+///     .with_name("rustaceans")
+///     .with_notification("be nice");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModifyGroupBaseInfo {
+    #[serde(rename = "GroupId")]
+    group_id: String,
+    #[serde(rename = "Name", skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(rename = "Introduction", skip_serializing_if = "Option::is_none")]
+    introduction: Option<String>,
+    #[serde(rename = "Notification", skip_serializing_if = "Option::is_none")]
+    notification: Option<String>,
+    #[serde(rename = "FaceUrl", skip_serializing_if = "Option::is_none")]
+    face_url: Option<String>,
+    #[serde(rename = "MaxMemberNum", skip_serializing_if = "Option::is_none")]
+    max_member_num: Option<u32>,
+    #[serde(rename = "ApplyJoinOption", skip_serializing_if = "Option::is_none")]
+    apply_join_option: Option<ApplyJoinOption>,
+}
+
+impl ModifyGroupBaseInfo {
+    /// change nothing of `group_id` yet.
+    pub fn new(group_id: &str) -> Self {
+        ModifyGroupBaseInfo {
+            group_id: group_id.to_string(),
+            name: None,
+            introduction: None,
+            notification: None,
+            face_url: None,
+            max_member_num: None,
+            apply_join_option: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn with_introduction(mut self, introduction: &str) -> Self {
+        self.introduction = Some(introduction.to_string());
+        self
+    }
+
+    pub fn with_notification(mut self, notification: &str) -> Self {
+        self.notification = Some(notification.to_string());
+        self
+    }
+
+    pub fn with_face_url(mut self, face_url: &str) -> Self {
+        self.face_url = Some(face_url.to_string());
+        self
+    }
+
+    pub fn with_max_member_num(mut self, num: u32) -> Self {
+        self.max_member_num = Some(num);
+        self
+    }
+
+    pub fn with_apply_join_option(mut self, option: ApplyJoinOption) -> Self {
+        self.apply_join_option = Some(option);
+        self
+    }
+}
+
+/// The info of a group answered by `get_group_info`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct GroupInfo {
+    #[serde(rename = "GroupId")]
+    pub group_id: String,
+    /// not 0 if the info of the group could not be got.
+    #[serde(rename = "ErrorCode", default)]
+    pub error_code: u32,
+    #[serde(rename = "ErrorInfo", default)]
+    pub error_info: String,
+    #[serde(rename = "Type", default)]
+    pub group_type: Option<GroupType>,
+    #[serde(rename = "Name", default)]
+    pub name: String,
+    #[serde(rename = "Introduction", default)]
+    pub introduction: String,
+    #[serde(rename = "Notification", default)]
+    pub notification: String,
+    #[serde(rename = "FaceUrl", default)]
+    pub face_url: String,
+    #[serde(rename = "Owner_Account", default)]
+    pub owner_account: String,
+    /// the unix time the group is created at.
+    #[serde(rename = "CreateTime", default)]
+    pub create_time: u64,
+    #[serde(rename = "MemberNum", default)]
+    pub member_num: u32,
+    #[serde(rename = "MaxMemberNum", default)]
+    pub max_member_num: u32,
+    #[serde(rename = "ApplyJoinOption", default)]
+    pub apply_join_option: Option<ApplyJoinOption>,
+}
+
+/// The role of a member in a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GroupRole {
+    Owner,
+    Admin,
+    Member,
+}
+
+/// The result of adding a member with `add_group_member` or
+/// `import_group_member`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AddGroupMemberItem {
+    #[serde(rename = "Member_Account")]
+    pub member_account: String,
+    /// 0 if failed, 1 if added, 2 if already a member, 3 if invited and
+    /// waiting for the confirmation, which imported members never are.
+    #[serde(rename = "Result")]
+    pub result: u8,
+}
+
+impl AddGroupMemberItem {
+    pub fn is_added(&self) -> bool {
+        self.status() == AddGroupMemberStatus::Added
+    }
+
+    pub fn status(&self) -> AddGroupMemberStatus {
+        match self.result {
+            1 => AddGroupMemberStatus::Added,
+            2 => AddGroupMemberStatus::AlreadyMember,
+            3 => AddGroupMemberStatus::Invited,
+            _ => AddGroupMemberStatus::Failed,
+        }
+    }
+}
+
+/// Whether an account is added to a group with `add_group_member`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddGroupMemberStatus {
+    Added,
+    AlreadyMember,
+    /// invited, waiting for the account to confirm.
+    Invited,
+    Failed,
+}
+
+/// A historical member of a group imported by `import_group_member`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{GroupRole, ImportGroupMember};
+///
+/// let member = ImportGroupMember::new("bob", 1569900000).with_role(GroupRole::Admin);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportGroupMember {
+    #[serde(rename = "Member_Account")]
+    member_account: String,
+    #[serde(rename = "Role", skip_serializing_if = "Option::is_none")]
+    role: Option<GroupRole>,
+    #[serde(rename = "JoinTime")]
+    join_time: u64,
+    #[serde(rename = "UnreadMsgNum", skip_serializing_if = "Option::is_none")]
+    unread_msg_num: Option<u32>,
+}
+
+impl ImportGroupMember {
+    /// `member_account`, joined at the unix time `join_time`.
+    pub fn new(member_account: &str, join_time: u64) -> Self {
+        ImportGroupMember {
+            member_account: member_account.to_string(),
+            role: None,
+            join_time,
+            unread_msg_num: None,
+        }
+    }
+
+    /// make the member an `Admin`, a plain `Member` by default.
+    pub fn with_role(mut self, role: GroupRole) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    /// the number of the messages of the group the member has not read.
+    pub fn with_unread_msg_num(mut self, num: u32) -> Self {
+        self.unread_msg_num = Some(num);
+        self
+    }
+}
+
+/// the body of `group_open_http_svc/import_group_member`.
+#[derive(Debug, Serialize)]
+pub(crate) struct ImportGroupMembers<'a> {
+    #[serde(rename = "GroupId")]
+    pub(crate) group_id: &'a str,
+    #[serde(rename = "MemberList")]
+    pub(crate) member_list: &'a [ImportGroupMember],
+}
+
+/// the body of `group_open_http_svc/import_group`, a [`CreateGroup`] created
+/// at the unix time `create_time`.
+///
+/// [`CreateGroup`]: struct.CreateGroup.html
+#[derive(Debug, Serialize)]
+pub(crate) struct ImportGroup {
+    #[serde(flatten)]
+    group: CreateGroup,
+    #[serde(rename = "CreateTime")]
+    create_time: u64,
+}
+
+impl ImportGroup {
+    /// import `group` without its members, which are imported by
+    /// `import_group_member` instead.
+    pub(crate) fn new(group: &CreateGroup, create_time: u64) -> Self {
+        let mut group = group.clone();
+        group.member_list.clear();
+        ImportGroup { group, create_time }
+    }
+}
+
+/// A query of the members of a group, answered a page at a time by
+/// `get_group_member_info`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{GroupMemberQuery, GroupRole};
+///
+/// let query = GroupMemberQuery::new("@TGS#2J4SZEAEL")
+///     .with_limit(50)
+///     .with_role_filter(&[GroupRole::Owner, GroupRole::Admin])
+///     .with_info_filter(&["Role", "ShutUpUntil"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GroupMemberQuery {
+    #[serde(rename = "GroupId")]
+    group_id: String,
+    #[serde(rename = "Limit")]
+    limit: u32,
+    #[serde(rename = "Offset")]
+    offset: u32,
+    /// the cursor of community groups, which are not paged by offset.
+    #[serde(rename = "Next", skip_serializing_if = "Option::is_none")]
+    next: Option<String>,
+    #[serde(rename = "MemberRoleFilter", skip_serializing_if = "Vec::is_empty")]
+    member_role_filter: Vec<GroupRole>,
+    #[serde(rename = "MemberInfoFilter", skip_serializing_if = "Vec::is_empty")]
+    member_info_filter: Vec<String>,
+}
+
+impl GroupMemberQuery {
+    /// all the members of `group_id` with all their info.
+    pub fn new(group_id: &str) -> Self {
+        GroupMemberQuery {
+            group_id: group_id.to_string(),
+            limit: 100,
+            offset: 0,
+            next: None,
+            member_role_filter: Vec::new(),
+            member_info_filter: Vec::new(),
+        }
+    }
+
+    /// answer at most `limit` members a page, 100 by default.
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// only the members of `roles`.
+    pub fn with_role_filter(mut self, roles: &[GroupRole]) -> Self {
+        self.member_role_filter = roles.to_vec();
+        self
+    }
+
+    /// only the info named `fields` of each member, e.g. `"Role"`.
+    pub fn with_info_filter(mut self, fields: &[&str]) -> Self {
+        self.member_info_filter = fields.iter().map(|field| field.to_string()).collect();
+        self
+    }
+
+    /// the query of the page after `page`, if it is not the last one.
+    pub fn next_page(&self, page: &GroupMemberPage) -> Option<Self> {
+        if page.member_list.is_empty() {
+            return None;
+        }
+
+        let mut next = self.clone();
+        if !page.next.is_empty() {
+            next.next = Some(page.next.clone());
+            return Some(next);
+        }
+        if self.next.is_some() {
+            return None;
+        }
+
+        next.offset += page.member_list.len() as u32;
+        if next.offset < page.member_num {
+            Some(next)
+        } else {
+            None
+        }
+    }
+}
+
+/// A page of members answered by `get_group_member_info`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct GroupMemberPage {
+    /// the number of all the members of the group.
+    #[serde(rename = "MemberNum", default)]
+    pub member_num: u32,
+    #[serde(rename = "MemberList", default)]
+    pub member_list: Vec<GroupMember>,
+    /// the cursor of the next page of a community group, empty on the last.
+    #[serde(rename = "Next", default)]
+    pub next: String,
+}
+
+/// A member answered by `get_group_member_info`, with only the info asked
+/// for.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct GroupMember {
+    #[serde(rename = "Member_Account")]
+    pub member_account: String,
+    #[serde(rename = "Role", default)]
+    pub role: Option<GroupRole>,
+    /// the unix time the member joined at.
+    #[serde(rename = "JoinTime", default)]
+    pub join_time: u64,
+    #[serde(rename = "MsgSeq", default)]
+    pub msg_seq: u64,
+    #[serde(rename = "MsgFlag", default)]
+    pub msg_flag: String,
+    #[serde(rename = "LastSendMsgTime", default)]
+    pub last_send_msg_time: u64,
+    /// the unix time the member is muted until, 0 if not muted.
+    #[serde(rename = "ShutUpUntil", default)]
+    pub shut_up_until: u64,
+    #[serde(rename = "NameCard", default)]
+    pub name_card: String,
+}
+
+/// The changes of a member of a group made by `modify_group_member_info`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{GroupRole, ModifyGroupMemberInfo};
+///
+/// let changes = ModifyGroupMemberInfo::new("@TGS#2J4SZEAEL", "bob")
+///     .with_role(GroupRole::Admin)
+///     .with_shut_up_time(600);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModifyGroupMemberInfo {
+    #[serde(rename = "GroupId")]
+    group_id: String,
+    #[serde(rename = "Member_Account")]
+    member_account: String,
+    #[serde(rename = "Role", skip_serializing_if = "Option::is_none")]
+    role: Option<GroupRole>,
+    #[serde(rename = "ShutUpTime", skip_serializing_if = "Option::is_none")]
+    shut_up_time: Option<u32>,
+    #[serde(rename = "NameCard", skip_serializing_if = "Option::is_none")]
+    name_card: Option<String>,
+}
+
+impl ModifyGroupMemberInfo {
+    /// change nothing of `member_account` in `group_id` yet.
+    pub fn new(group_id: &str, member_account: &str) -> Self {
+        ModifyGroupMemberInfo {
+            group_id: group_id.to_string(),
+            member_account: member_account.to_string(),
+            role: None,
+            shut_up_time: None,
+            name_card: None,
+        }
+    }
+
+    /// make the member an `Admin` or a plain `Member`.
+    pub fn with_role(mut self, role: GroupRole) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    /// mute the member for `seconds`, or unmute it with 0.
+    pub fn with_shut_up_time(mut self, seconds: u32) -> Self {
+        self.shut_up_time = Some(seconds);
+        self
+    }
+
+    pub fn with_name_card(mut self, name_card: &str) -> Self {
+        self.name_card = Some(name_card.to_string());
+        self
+    }
+}
+
+/// A member muted in a group, answered by `get_group_shutted_uin`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ShuttedMember {
+    #[serde(rename = "Member_Account")]
+    pub member_account: String,
+    /// the unix time the member is muted until.
+    #[serde(rename = "ShuttedUntil")]
+    pub shutted_until: u64,
+}
+
+/// the body of `group_open_http_svc/forbid_send_msg`.
+#[derive(Debug, Serialize)]
+pub(crate) struct ForbidSendMsg<'a> {
+    #[serde(rename = "GroupId")]
+    pub(crate) group_id: &'a str,
+    #[serde(rename = "Members_Account")]
+    pub(crate) members_account: &'a [&'a str],
+    #[serde(rename = "ShutUpTime")]
+    pub(crate) shut_up_time: u32,
+}
+
+/// the result of `group_open_http_svc/get_group_shutted_uin`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ShuttedUinList {
+    #[serde(rename = "ShuttedUinList", default)]
+    pub(crate) shutted_uin_list: Vec<ShuttedMember>,
+}
+
+/// A custom attribute of a group, e.g. the state of a live room.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::GroupAttr;
+///
+/// let attr = GroupAttr::new("topic", "rust 2018");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GroupAttr {
+    pub key: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+impl GroupAttr {
+    pub fn new(key: &str, value: &str) -> Self {
+        GroupAttr {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+/// `{"key": ..}`, an attribute deleted by `delete_group_attr`.
+#[derive(Debug, Serialize)]
+pub(crate) struct GroupAttrKey<'a> {
+    pub(crate) key: &'a str,
+}
+
+/// the body of the endpoints changing the attributes of a group.
+#[derive(Debug, Serialize)]
+pub(crate) struct GroupAttrs<'a, T> {
+    #[serde(rename = "GroupId")]
+    pub(crate) group_id: &'a str,
+    #[serde(rename = "GroupAttr")]
+    pub(crate) group_attr: &'a [T],
+}
+
+/// the result of `group_open_http_svc/get_group_attr`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct GroupAttrList {
+    #[serde(rename = "GroupAttrAry", default)]
+    pub(crate) group_attr_ary: Vec<GroupAttr>,
+}
+
+/// A topic of a `Community` group created by `create_topic`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::CreateTopic;
+///
+/// let topic = CreateTopic::new("@TGS#_rust", "announcements")
+///     .with_from_account("alice")
+///     .with_introduction("news of the community");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CreateTopic {
+    #[serde(rename = "GroupId")]
+    group_id: String,
+    #[serde(rename = "TopicId", skip_serializing_if = "Option::is_none")]
+    topic_id: Option<String>,
+    #[serde(rename = "From_Account", skip_serializing_if = "Option::is_none")]
+    from_account: Option<String>,
+    #[serde(rename = "TopicName")]
+    topic_name: String,
+    #[serde(rename = "FaceUrl", skip_serializing_if = "Option::is_none")]
+    face_url: Option<String>,
+    #[serde(rename = "Notification", skip_serializing_if = "Option::is_none")]
+    notification: Option<String>,
+    #[serde(rename = "Introduction", skip_serializing_if = "Option::is_none")]
+    introduction: Option<String>,
+    #[serde(rename = "CustomString", skip_serializing_if = "Option::is_none")]
+    custom_string: Option<String>,
+}
+
+impl CreateTopic {
+    /// a topic named `topic_name` in the community `group_id`.
+    pub fn new(group_id: &str, topic_name: &str) -> Self {
+        CreateTopic {
+            group_id: group_id.to_string(),
+            topic_id: None,
+            from_account: None,
+            topic_name: topic_name.to_string(),
+            face_url: None,
+            notification: None,
+            introduction: None,
+            custom_string: None,
+        }
+    }
+
+    /// create the topic as `topic_id` instead of a generated id, which must
+    /// start with the id of the group followed by `@TOPIC#_`.
+    pub fn with_topic_id(mut self, topic_id: &str) -> Self {
+        self.topic_id = Some(topic_id.to_string());
+        self
+    }
+
+    /// create the topic as the member `from_account` instead of the admin.
+    pub fn with_from_account(mut self, from_account: &str) -> Self {
+        self.from_account = Some(from_account.to_string());
+        self
+    }
+
+    pub fn with_face_url(mut self, face_url: &str) -> Self {
+        self.face_url = Some(face_url.to_string());
+        self
+    }
+
+    pub fn with_notification(mut self, notification: &str) -> Self {
+        self.notification = Some(notification.to_string());
+        self
+    }
+
+    pub fn with_introduction(mut self, introduction: &str) -> Self {
+        self.introduction = Some(introduction.to_string());
+        self
+    }
+
+    /// the data of the app attached to the topic.
+    pub fn with_custom_string(mut self, custom_string: &str) -> Self {
+        self.custom_string = Some(custom_string.to_string());
+        self
+    }
+}
+
+/// The changes of a topic made by `modify_topic`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::ModifyTopic;
+///
+/// let changes = ModifyTopic::new("@TGS#_rust", "@TGS#_rust@TOPIC#_news")
+///     .with_topic_name("news")
+///     .with_mute_all_member(true);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModifyTopic {
+    #[serde(rename = "GroupId")]
+    group_id: String,
+    #[serde(rename = "TopicId")]
+    topic_id: String,
+    #[serde(rename = "TopicName", skip_serializing_if = "Option::is_none")]
+    topic_name: Option<String>,
+    #[serde(rename = "FaceUrl", skip_serializing_if = "Option::is_none")]
+    face_url: Option<String>,
+    #[serde(rename = "Notification", skip_serializing_if = "Option::is_none")]
+    notification: Option<String>,
+    #[serde(rename = "Introduction", skip_serializing_if = "Option::is_none")]
+    introduction: Option<String>,
+    #[serde(rename = "CustomString", skip_serializing_if = "Option::is_none")]
+    custom_string: Option<String>,
+    /// `On` or `Off`.
+    #[serde(rename = "MuteAllMember", skip_serializing_if = "Option::is_none")]
+    mute_all_member: Option<&'static str>,
+}
+
+impl ModifyTopic {
+    /// change nothing of the topic `topic_id` of `group_id` yet.
+    pub fn new(group_id: &str, topic_id: &str) -> Self {
+        ModifyTopic {
+            group_id: group_id.to_string(),
+            topic_id: topic_id.to_string(),
+            topic_name: None,
+            face_url: None,
+            notification: None,
+            introduction: None,
+            custom_string: None,
+            mute_all_member: None,
+        }
+    }
+
+    pub fn with_topic_name(mut self, topic_name: &str) -> Self {
+        self.topic_name = Some(topic_name.to_string());
+        self
+    }
+
+    pub fn with_face_url(mut self, face_url: &str) -> Self {
+        self.face_url = Some(face_url.to_string());
+        self
+    }
+
+    pub fn with_notification(mut self, notification: &str) -> Self {
+        self.notification = Some(notification.to_string());
+        self
+    }
+
+    pub fn with_introduction(mut self, introduction: &str) -> Self {
+        self.introduction = Some(introduction.to_string());
+        self
+    }
+
+    pub fn with_custom_string(mut self, custom_string: &str) -> Self {
+        self.custom_string = Some(custom_string.to_string());
+        self
+    }
+
+    /// mute or unmute all the members but the admins in the topic.
+    pub fn with_mute_all_member(mut self, mute: bool) -> Self {
+        self.mute_all_member = Some(if mute { "On" } else { "Off" });
+        self
+    }
+}
+
+/// The info of a topic answered by `get_topic`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct TopicInfo {
+    #[serde(rename = "TopicId")]
+    pub topic_id: String,
+    /// not 0 if the info of the topic could not be got.
+    #[serde(rename = "ErrorCode", default)]
+    pub error_code: u32,
+    #[serde(rename = "ErrorInfo", default)]
+    pub error_info: String,
+    #[serde(rename = "TopicName", default)]
+    pub topic_name: String,
+    #[serde(rename = "FaceUrl", default)]
+    pub face_url: String,
+    #[serde(rename = "Notification", default)]
+    pub notification: String,
+    #[serde(rename = "Introduction", default)]
+    pub introduction: String,
+    #[serde(rename = "CustomString", default)]
+    pub custom_string: String,
+    /// the unix time the topic is created at.
+    #[serde(rename = "CreateTime", default)]
+    pub create_time: u64,
+}
+
+/// The result of destroying a topic with `destroy_topic`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DestroyTopicItem {
+    #[serde(rename = "TopicId")]
+    pub topic_id: String,
+    #[serde(rename = "ErrorCode", default)]
+    pub error_code: u32,
+    #[serde(rename = "ErrorInfo", default)]
+    pub error_info: String,
+}
+
+impl DestroyTopicItem {
+    pub fn is_destroyed(&self) -> bool {
+        self.error_code == 0
+    }
+}
+
+/// the body of `million_group_open_http_svc/destroy_topic` and `get_topic`.
+#[derive(Debug, Serialize)]
+pub(crate) struct TopicIds<'a> {
+    #[serde(rename = "GroupId")]
+    pub(crate) group_id: &'a str,
+    #[serde(rename = "TopicIdList", skip_serializing_if = "<[_]>::is_empty")]
+    pub(crate) topic_id_list: &'a [&'a str],
+}
+
+/// the result of `million_group_open_http_svc/create_topic`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct CreatedTopic {
+    #[serde(rename = "TopicId")]
+    pub(crate) topic_id: String,
+}
+
+/// the result of `million_group_open_http_svc/get_topic`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct TopicInfoList {
+    #[serde(rename = "TopicResponseInfo", default)]
+    pub(crate) topic_response_info: Vec<TopicInfo>,
+}
+
+/// the result of `million_group_open_http_svc/destroy_topic`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct DestroyedTopics {
+    #[serde(rename = "DestroyResultItem", default)]
+    pub(crate) destroy_result_item: Vec<DestroyTopicItem>,
+}
+
+/// `{"GroupId": ..}`, the body of endpoints taking nothing but a group.
+#[derive(Debug, Serialize)]
+pub(crate) struct GroupId<'a> {
+    #[serde(rename = "GroupId")]
+    pub(crate) group_id: &'a str,
+}
+
+/// the body of `group_open_http_svc/get_group_info`.
+#[derive(Debug, Serialize)]
+pub(crate) struct GetGroupInfo<'a> {
+    #[serde(rename = "GroupIdList")]
+    pub(crate) group_id_list: &'a [&'a str],
+}
+
+/// the result of `group_open_http_svc/get_group_info`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct GroupInfoList {
+    #[serde(rename = "GroupInfo", default)]
+    pub(crate) group_info: Vec<GroupInfo>,
+}
+
+/// the result of `group_open_http_svc/create_group`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct CreatedGroup {
+    #[serde(rename = "GroupId")]
+    pub(crate) group_id: String,
+}
+
+/// the body of `group_open_http_svc/add_group_member`.
+#[derive(Debug, Serialize)]
+pub(crate) struct AddGroupMember<'a> {
+    #[serde(rename = "GroupId")]
+    group_id: &'a str,
+    #[serde(rename = "Silence", skip_serializing_if = "Option::is_none")]
+    silence: Option<u8>,
+    #[serde(rename = "MemberList")]
+    member_list: Vec<Member>,
+}
+
+impl<'a> AddGroupMember<'a> {
+    /// add `members` to `group_id`, without notifying the group if `silence`.
+    pub(crate) fn new(group_id: &'a str, members: &[&str], silence: bool) -> Self {
+        AddGroupMember {
+            group_id,
+            silence: silence.then_some(1),
+            member_list: members
+                .iter()
+                .map(|member| Member {
+                    member_account: member.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// the result of `group_open_http_svc/add_group_member`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct AddedGroupMembers {
+    #[serde(rename = "MemberList", default)]
+    pub(crate) member_list: Vec<AddGroupMemberItem>,
+}
+
+/// the body of `group_open_http_svc/delete_group_member`.
+#[derive(Debug, Serialize)]
+pub(crate) struct DeleteGroupMember<'a> {
+    #[serde(rename = "GroupId")]
+    pub(crate) group_id: &'a str,
+    #[serde(rename = "Silence", skip_serializing_if = "Option::is_none")]
+    pub(crate) silence: Option<u8>,
+    #[serde(rename = "Reason", skip_serializing_if = "Option::is_none")]
+    pub(crate) reason: Option<&'a str>,
+    #[serde(rename = "MemberToDel_Account")]
+    pub(crate) member_to_del_account: &'a [&'a str],
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AddGroupMember, AddGroupMemberStatus, AddedGroupMembers, ApplyJoinOption};
+    use super::{CreateGroup, GroupInfoList, GroupMemberPage, GroupMemberQuery, GroupRole};
+    use super::{CreateTopic, DestroyedTopics, ModifyTopic, TopicIds, TopicInfoList};
+    use super::{
+        ForbidSendMsg, ImportGroup, ImportGroupMember, ImportGroupMembers, ShuttedUinList,
+    };
+    use super::{GroupAttr, GroupAttrKey, GroupAttrList, GroupAttrs};
+    use super::{GroupType, ModifyGroupBaseInfo, ModifyGroupMemberInfo};
+    use serde_json::json;
+
+    #[test]
+    fn test_create_group_body() {
+        let group = CreateGroup::new(GroupType::AVChatRoom, "live");
+        assert_eq!(
+            serde_json::to_value(&group).unwrap(),
+            json!({ "Type": "AVChatRoom", "Name": "live" })
+        );
+
+        let group = CreateGroup::new(GroupType::Public, "rustaceans")
+            .with_owner("alice")
+            .with_group_id("rust")
+            .with_max_member_count(500)
+            .with_apply_join_option(ApplyJoinOption::NeedPermission)
+            .with_members(&["bob"]);
+        assert_eq!(
+            serde_json::to_value(&group).unwrap(),
+            json!({
+                "Owner_Account": "alice",
+                "Type": "Public",
+                "GroupId": "rust",
+                "Name": "rustaceans",
+                "MaxMemberCount": 500,
+                "ApplyJoinOption": "NeedPermission",
+                "MemberList": [{ "Member_Account": "bob" }],
+            })
+        );
+
+        let changes = ModifyGroupBaseInfo::new("rust").with_max_member_num(1000);
+        assert_eq!(
+            serde_json::to_value(&changes).unwrap(),
+            json!({ "GroupId": "rust", "MaxMemberNum": 1000 })
+        );
+    }
+
+    #[test]
+    fn test_group_info() {
+        let list: GroupInfoList = serde_json::from_value(json!({
+            "GroupInfo": [
+                {
+                    "GroupId": "rust",
+                    "ErrorCode": 0,
+                    "Type": "Community",
+                    "Name": "rustaceans",
+                    "Owner_Account": "alice",
+                    "MemberNum": 2,
+                    "ApplyJoinOption": "FreeAccess",
+                },
+                { "GroupId": "gone", "ErrorCode": 10010, "ErrorInfo": "group dismissed" },
+                { "GroupId": "new", "ErrorCode": 0, "Type": "Work" },
+            ]
+        }))
+        .unwrap();
+        let info = &list.group_info;
+        assert_eq!(info[0].group_type, Some(GroupType::Community));
+        assert_eq!(info[0].member_num, 2);
+        assert_eq!(info[0].apply_join_option, Some(ApplyJoinOption::FreeAccess));
+        assert_eq!(info[1].error_code, 10010);
+        assert_eq!(info[1].group_type, None);
+        assert_eq!(info[2].group_type, Some(GroupType::Unknown));
+    }
+    #[test]
+    fn test_group_member_bodies() {
+        let body = AddGroupMember::new("rust", &["bob"], true);
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "GroupId": "rust", "Silence": 1, "MemberList": [{ "Member_Account": "bob" }] })
+        );
+        let body = AddGroupMember::new("rust", &[], false);
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "GroupId": "rust", "MemberList": [] })
+        );
+
+        let added: AddedGroupMembers = serde_json::from_value(json!({
+            "MemberList": [
+                { "Member_Account": "bob", "Result": 1 },
+                { "Member_Account": "carol", "Result": 2 },
+                { "Member_Account": "dave", "Result": 0 },
+            ]
+        }))
+        .unwrap();
+        let statuses: Vec<_> = added.member_list.iter().map(|item| item.status()).collect();
+        assert_eq!(
+            statuses,
+            [
+                AddGroupMemberStatus::Added,
+                AddGroupMemberStatus::AlreadyMember,
+                AddGroupMemberStatus::Failed,
+            ]
+        );
+
+        let changes = ModifyGroupMemberInfo::new("rust", "bob")
+            .with_role(GroupRole::Admin)
+            .with_shut_up_time(0);
+        assert_eq!(
+            serde_json::to_value(&changes).unwrap(),
+            json!({ "GroupId": "rust", "Member_Account": "bob", "Role": "Admin", "ShutUpTime": 0 })
+        );
+    }
+
+    #[test]
+    fn test_import_group_bodies() {
+        let group = CreateGroup::new(GroupType::Private, "old").with_members(&["bob"]);
+        assert_eq!(
+            serde_json::to_value(ImportGroup::new(&group, 1569900000)).unwrap(),
+            json!({ "Type": "Private", "Name": "old", "CreateTime": 1569900000 })
+        );
+
+        let members = [
+            ImportGroupMember::new("alice", 1569900000)
+                .with_role(GroupRole::Admin)
+                .with_unread_msg_num(3),
+            ImportGroupMember::new("bob", 1569900100),
+        ];
+        let body = ImportGroupMembers {
+            group_id: "old",
+            member_list: &members,
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({
+                "GroupId": "old",
+                "MemberList": [
+                    {
+                        "Member_Account": "alice",
+                        "Role": "Admin",
+                        "JoinTime": 1569900000,
+                        "UnreadMsgNum": 3,
+                    },
+                    { "Member_Account": "bob", "JoinTime": 1569900100 },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_group_shut_up_bodies() {
+        let body = ForbidSendMsg {
+            group_id: "rust",
+            members_account: &["bob", "carol"],
+            shut_up_time: 0,
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "GroupId": "rust", "Members_Account": ["bob", "carol"], "ShutUpTime": 0 })
+        );
+
+        let list: ShuttedUinList = serde_json::from_value(json!({
+            "GroupId": "rust",
+            "ShuttedUinList": [{ "Member_Account": "bob", "ShuttedUntil": 1569910200 }]
+        }))
+        .unwrap();
+        assert_eq!(list.shutted_uin_list[0].member_account, "bob");
+        assert_eq!(list.shutted_uin_list[0].shutted_until, 1569910200);
+    }
+
+    #[test]
+    fn test_topic_bodies() {
+        let topic = CreateTopic::new("community", "news").with_custom_string("pinned");
+        assert_eq!(
+            serde_json::to_value(&topic).unwrap(),
+            json!({ "GroupId": "community", "TopicName": "news", "CustomString": "pinned" })
+        );
+
+        let changes = ModifyTopic::new("community", "news").with_mute_all_member(false);
+        assert_eq!(
+            serde_json::to_value(&changes).unwrap(),
+            json!({ "GroupId": "community", "TopicId": "news", "MuteAllMember": "Off" })
+        );
+
+        let all = TopicIds {
+            group_id: "community",
+            topic_id_list: &[],
+        };
+        assert_eq!(
+            serde_json::to_value(&all).unwrap(),
+            json!({ "GroupId": "community" })
+        );
+
+        let list: TopicInfoList = serde_json::from_value(json!({
+            "TopicResponseInfo": [
+                { "TopicId": "news", "ErrorCode": 0, "TopicName": "news" },
+                { "TopicId": "gone", "ErrorCode": 10010, "ErrorInfo": "topic not found" },
+            ]
+        }))
+        .unwrap();
+        assert_eq!(list.topic_response_info[0].topic_name, "news");
+        assert_eq!(list.topic_response_info[1].error_code, 10010);
+
+        let destroyed: DestroyedTopics = serde_json::from_value(json!({
+            "DestroyResultItem": [{ "ErrorCode": 0, "ErrorInfo": "", "TopicId": "news" }]
+        }))
+        .unwrap();
+        assert!(destroyed.destroy_result_item[0].is_destroyed());
+    }
+
+    #[test]
+    fn test_group_attr_bodies() {
+        let attrs = [GroupAttr::new("topic", "rust")];
+        let body = GroupAttrs {
+            group_id: "rust",
+            group_attr: &attrs,
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "GroupId": "rust", "GroupAttr": [{ "key": "topic", "value": "rust" }] })
+        );
+
+        let keys = [GroupAttrKey { key: "topic" }];
+        let body = GroupAttrs {
+            group_id: "rust",
+            group_attr: &keys,
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "GroupId": "rust", "GroupAttr": [{ "key": "topic" }] })
+        );
+
+        let list: GroupAttrList = serde_json::from_value(json!({
+            "GroupAttrAry": [{ "key": "topic", "value": "rust" }, { "key": "empty" }]
+        }))
+        .unwrap();
+        assert_eq!(
+            list.group_attr_ary,
+            [GroupAttr::new("topic", "rust"), GroupAttr::new("empty", "")]
+        );
+    }
+
+    #[test]
+    fn test_group_member_next_page() {
+        let query = GroupMemberQuery::new("rust").with_limit(2);
+        let page: GroupMemberPage = serde_json::from_value(json!({
+            "MemberNum": 3,
+            "MemberList": [
+                { "Member_Account": "alice", "Role": "Owner" },
+                { "Member_Account": "bob", "Role": "Member" },
+            ],
+        }))
+        .unwrap();
+        let next = query.next_page(&page).unwrap();
+        assert_eq!(serde_json::to_value(&next).unwrap()["Offset"], 2);
+
+        let page: GroupMemberPage = serde_json::from_value(json!({
+            "MemberNum": 3,
+            "MemberList": [{ "Member_Account": "carol" }],
+        }))
+        .unwrap();
+        assert_eq!(next.next_page(&page), None);
+
+        let page: GroupMemberPage = serde_json::from_value(json!({
+            "MemberNum": 3,
+            "MemberList": [{ "Member_Account": "alice" }],
+            "Next": "cursor",
+        }))
+        .unwrap();
+        let next = query.next_page(&page).unwrap();
+        assert_eq!(serde_json::to_value(&next).unwrap()["Next"], "cursor");
+        let page: GroupMemberPage = serde_json::from_value(json!({
+            "MemberNum": 3,
+            "MemberList": [{ "Member_Account": "bob" }],
+        }))
+        .unwrap();
+        assert_eq!(next.next_page(&page), None);
+    }
+}
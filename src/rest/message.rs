@@ -0,0 +1,703 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::random;
+
+/// An element of the `MsgBody` of a message, e.g. a text, built from
+/// [`TextElem`] and the other typed elements, or from its json.
+///
+/// [`TextElem`]: struct.TextElem.html
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{CustomElem, MsgElement};
+/// use serde_json::json;
+///
+/// let text = MsgElement::text("hello");
+/// let custom: MsgElement = CustomElem::new("order-paid").into();
+/// let raw = MsgElement::new("TIMCustomElem", json!({ "Data": "order-paid" }));
+/// assert_eq!(custom, raw);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MsgElement {
+    /// e.g. `TIMTextElem`.
+    pub msg_type: String,
+    pub msg_content: Value,
+}
+
+impl MsgElement {
+    pub fn new(msg_type: &str, msg_content: Value) -> Self {
+        MsgElement {
+            msg_type: msg_type.to_string(),
+            msg_content,
+        }
+    }
+
+    /// a `TIMTextElem` of `text`.
+    pub fn text(text: &str) -> Self {
+        MsgElement::new("TIMTextElem", json!({ "Text": text }))
+    }
+}
+
+/// How a message is pushed to recipients who are offline.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct OfflinePushInfo {
+    /// 0 to push, 1 not to.
+    push_flag: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    desc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ext: Option<String>,
+}
+
+impl OfflinePushInfo {
+    /// the title of the push.
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// the text of the push, the text of the message by default.
+    pub fn with_desc(mut self, desc: &str) -> Self {
+        self.desc = Some(desc.to_string());
+        self
+    }
+
+    /// the payload passed through to the app.
+    pub fn with_ext(mut self, ext: &str) -> Self {
+        self.ext = Some(ext.to_string());
+        self
+    }
+
+    /// do not push the message.
+    pub fn without_push(mut self) -> Self {
+        self.push_flag = 1;
+        self
+    }
+}
+
+/// the fields shared by one-to-one and batch messages.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct MsgOptions {
+    #[serde(rename = "SyncOtherMachine", skip_serializing_if = "Option::is_none")]
+    sync_other_machine: Option<u8>,
+    #[serde(rename = "From_Account", skip_serializing_if = "Option::is_none")]
+    from_account: Option<String>,
+    #[serde(rename = "MsgRandom")]
+    msg_random: u32,
+    #[serde(rename = "MsgBody")]
+    msg_body: Vec<MsgElement>,
+    #[serde(rename = "OfflinePushInfo", skip_serializing_if = "Option::is_none")]
+    offline_push_info: Option<OfflinePushInfo>,
+}
+
+impl MsgOptions {
+    fn new(msg_body: Vec<MsgElement>) -> Self {
+        MsgOptions {
+            sync_other_machine: None,
+            from_account: None,
+            msg_random: random(),
+            msg_body,
+            offline_push_info: None,
+        }
+    }
+}
+
+/// A one-to-one message sent by `send_msg`.
+///
+/// It has a new random `MsgRandom`, which is kept when the call is retried
+/// so that the message is not sent twice.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{MsgElement, OfflinePushInfo, SendMsg};
+///
+/// let msg = SendMsg::new("10086", vec![MsgElement::text("your order is shipped")])
+///     .with_from_account("shop")
+///     .with_sync_other_machine(false)
+///     .with_offline_push_info(OfflinePushInfo::default().with_title("Shop"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SendMsg {
+    #[serde(rename = "To_Account")]
+    to_account: String,
+    #[serde(flatten)]
+    options: MsgOptions,
+}
+
+impl SendMsg {
+    /// a message of `msg_body` to `to_account`, sent by the admin.
+    pub fn new(to_account: &str, msg_body: Vec<MsgElement>) -> Self {
+        SendMsg {
+            to_account: to_account.to_string(),
+            options: MsgOptions::new(msg_body),
+        }
+    }
+
+    /// send the message as `from_account` instead of the admin.
+    pub fn with_from_account(mut self, from_account: &str) -> Self {
+        self.options.from_account = Some(from_account.to_string());
+        self
+    }
+
+    /// whether the message is synced to the other devices of the sender.
+    pub fn with_sync_other_machine(mut self, sync: bool) -> Self {
+        self.options.sync_other_machine = Some(if sync { 1 } else { 2 });
+        self
+    }
+
+    pub fn with_msg_random(mut self, msg_random: u32) -> Self {
+        self.options.msg_random = msg_random;
+        self
+    }
+
+    pub fn with_offline_push_info(mut self, info: OfflinePushInfo) -> Self {
+        self.options.offline_push_info = Some(info);
+        self
+    }
+
+    pub fn msg_random(&self) -> u32 {
+        self.options.msg_random
+    }
+}
+
+/// A message sent to many accounts by `batch_send_msg`, like [`SendMsg`].
+///
+/// The API takes at most 500 recipients at once, so the message is sent in
+/// one request for every 500 of them.
+///
+/// [`SendMsg`]: struct.SendMsg.html
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{BatchSendMsg, MsgElement};
+///
+/// let recipients: Vec<String> = (0..1200).map(|id| id.to_string()).collect();
+/// let msg = BatchSendMsg::new(recipients, vec![MsgElement::text("maintenance at 2am")])
+///     .with_sync_other_machine(false);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchSendMsg {
+    to_account: Vec<String>,
+    options: MsgOptions,
+}
+
+impl BatchSendMsg {
+    /// a message of `msg_body` to every account of `to_account`, sent by the
+    /// admin.
+    pub fn new(to_account: Vec<String>, msg_body: Vec<MsgElement>) -> Self {
+        BatchSendMsg {
+            to_account,
+            options: MsgOptions::new(msg_body),
+        }
+    }
+
+    /// send the message as `from_account` instead of the admin.
+    pub fn with_from_account(mut self, from_account: &str) -> Self {
+        self.options.from_account = Some(from_account.to_string());
+        self
+    }
+
+    /// whether the message is synced to the other devices of the sender.
+    pub fn with_sync_other_machine(mut self, sync: bool) -> Self {
+        self.options.sync_other_machine = Some(if sync { 1 } else { 2 });
+        self
+    }
+
+    pub fn with_msg_random(mut self, msg_random: u32) -> Self {
+        self.options.msg_random = msg_random;
+        self
+    }
+
+    pub fn with_offline_push_info(mut self, info: OfflinePushInfo) -> Self {
+        self.options.offline_push_info = Some(info);
+        self
+    }
+
+    pub fn msg_random(&self) -> u32 {
+        self.options.msg_random
+    }
+
+    /// the bodies of the requests, one for every 500 recipients.
+    pub(crate) fn chunks(&self) -> impl Iterator<Item = BatchSendMsgChunk<'_>> {
+        self.to_account
+            .chunks(BATCH_SEND_MSG_LIMIT)
+            .map(move |to_account| BatchSendMsgChunk {
+                to_account,
+                options: &self.options,
+            })
+    }
+}
+
+/// the most recipients of a request of `openim/batchsendmsg`.
+const BATCH_SEND_MSG_LIMIT: usize = 500;
+
+/// the body of a request of `openim/batchsendmsg`.
+#[derive(Debug, Serialize)]
+pub(crate) struct BatchSendMsgChunk<'a> {
+    #[serde(rename = "To_Account")]
+    pub(crate) to_account: &'a [String],
+    #[serde(flatten)]
+    options: &'a MsgOptions,
+}
+
+/// The priority of a group message, which decides the messages dropped first
+/// when a group is flooded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum MsgPriority {
+    High,
+    Normal,
+    Low,
+    Lowest,
+}
+
+/// A message sent to a group by `send_group_msg`.
+///
+/// Like [`SendMsg`], it has a new random `Random` kept across retries.
+///
+/// [`SendMsg`]: struct.SendMsg.html
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{MsgElement, MsgPriority, SendGroupMsg};
+///
+/// let msg = SendGroupMsg::new("@TGS#2J4SZEAEL", vec![MsgElement::text("welcome!")])
+///     .with_from_account("alice")
+///     .with_msg_priority(MsgPriority::High);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SendGroupMsg {
+    #[serde(rename = "GroupId")]
+    group_id: String,
+    #[serde(rename = "From_Account", skip_serializing_if = "Option::is_none")]
+    from_account: Option<String>,
+    #[serde(rename = "Random")]
+    random: u32,
+    #[serde(rename = "MsgPriority", skip_serializing_if = "Option::is_none")]
+    msg_priority: Option<MsgPriority>,
+    #[serde(rename = "MsgBody")]
+    msg_body: Vec<MsgElement>,
+    #[serde(rename = "OnlineOnlyFlag", skip_serializing_if = "Option::is_none")]
+    online_only_flag: Option<u8>,
+    #[serde(rename = "OfflinePushInfo", skip_serializing_if = "Option::is_none")]
+    offline_push_info: Option<OfflinePushInfo>,
+}
+
+impl SendGroupMsg {
+    /// a message of `msg_body` to the group `group_id`, sent by the admin.
+    pub fn new(group_id: &str, msg_body: Vec<MsgElement>) -> Self {
+        SendGroupMsg {
+            group_id: group_id.to_string(),
+            from_account: None,
+            random: random(),
+            msg_priority: None,
+            msg_body,
+            online_only_flag: None,
+            offline_push_info: None,
+        }
+    }
+
+    /// send the message as the member `from_account` instead of the admin,
+    /// e.g. to announce on behalf of the owner.
+    pub fn with_from_account(mut self, from_account: &str) -> Self {
+        self.from_account = Some(from_account.to_string());
+        self
+    }
+
+    pub fn with_random(mut self, random: u32) -> Self {
+        self.random = random;
+        self
+    }
+
+    pub fn with_msg_priority(mut self, priority: MsgPriority) -> Self {
+        self.msg_priority = Some(priority);
+        self
+    }
+
+    /// only deliver the message to the members online, without storing it.
+    pub fn with_online_only(mut self, online_only: bool) -> Self {
+        self.online_only_flag = if online_only { Some(1) } else { None };
+        self
+    }
+
+    pub fn with_offline_push_info(mut self, info: OfflinePushInfo) -> Self {
+        self.offline_push_info = Some(info);
+        self
+    }
+
+    pub fn random(&self) -> u32 {
+        self.random
+    }
+}
+
+/// A historical message of a group imported by `import_group_msg`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{ImportGroupMsg, MsgElement};
+///
+/// let msg = ImportGroupMsg::new("alice", 1569910200, vec![MsgElement::text("hi all")]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportGroupMsg {
+    #[serde(rename = "From_Account")]
+    from_account: String,
+    #[serde(rename = "SendTime")]
+    send_time: u64,
+    #[serde(rename = "Random")]
+    random: u32,
+    #[serde(rename = "MsgBody")]
+    msg_body: Vec<MsgElement>,
+}
+
+impl ImportGroupMsg {
+    /// a message of `msg_body` from the member `from_account`, sent at the
+    /// unix time `send_time`.
+    pub fn new(from_account: &str, send_time: u64, msg_body: Vec<MsgElement>) -> Self {
+        ImportGroupMsg {
+            from_account: from_account.to_string(),
+            send_time,
+            random: random(),
+            msg_body,
+        }
+    }
+
+    /// the random telling the message apart from others sent at the same
+    /// time, which must be kept when importing the message again.
+    pub fn with_random(mut self, random: u32) -> Self {
+        self.random = random;
+        self
+    }
+}
+
+/// the body of `group_open_http_svc/import_group_msg`.
+#[derive(Debug, Serialize)]
+pub(crate) struct ImportGroupMsgs<'a> {
+    #[serde(rename = "GroupId")]
+    pub(crate) group_id: &'a str,
+    #[serde(rename = "MsgList")]
+    pub(crate) msg_list: &'a [ImportGroupMsg],
+}
+
+/// A historical one-to-one message imported by `import_msg`, e.g. when
+/// migrating from another chat provider.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{ImportMsg, MsgElement};
+///
+/// let msg = ImportMsg::new("alice", "bob", 1569910200, vec![MsgElement::text("hi bob")]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportMsg {
+    /// 1 to count the message as unread and trigger callbacks, 2 not to.
+    #[serde(rename = "SyncFromOldSystem")]
+    sync_from_old_system: u8,
+    #[serde(rename = "From_Account")]
+    from_account: String,
+    #[serde(rename = "To_Account")]
+    to_account: String,
+    #[serde(rename = "MsgRandom")]
+    msg_random: u32,
+    #[serde(rename = "MsgTimeStamp")]
+    msg_time_stamp: u64,
+    #[serde(rename = "MsgBody")]
+    msg_body: Vec<MsgElement>,
+}
+
+impl ImportMsg {
+    /// a message of `msg_body` from `from_account` to `to_account`, sent at
+    /// the unix time `msg_time_stamp`.
+    pub fn new(
+        from_account: &str,
+        to_account: &str,
+        msg_time_stamp: u64,
+        msg_body: Vec<MsgElement>,
+    ) -> Self {
+        ImportMsg {
+            sync_from_old_system: 2,
+            from_account: from_account.to_string(),
+            to_account: to_account.to_string(),
+            msg_random: random(),
+            msg_time_stamp,
+            msg_body,
+        }
+    }
+
+    /// count the message as unread by the recipient, as if it is sent in
+    /// real time. Off by default, which suits backfilling history.
+    pub fn with_unread(mut self, unread: bool) -> Self {
+        self.sync_from_old_system = if unread { 1 } else { 2 };
+        self
+    }
+
+    /// the random telling the message apart from others sent at the same
+    /// time, which must be kept when importing the message again.
+    pub fn with_msg_random(mut self, msg_random: u32) -> Self {
+        self.msg_random = msg_random;
+        self
+    }
+}
+
+/// A query of the one-to-one messages between two accounts sent within a
+/// time range, answered a page at a time by `get_roam_msg`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::RoamMsgQuery;
+///
+/// let query = RoamMsgQuery::new("alice", "bob", 1569900000, 1569910200).with_max_cnt(50);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoamMsgQuery {
+    #[serde(rename = "Operator_Account")]
+    operator_account: String,
+    #[serde(rename = "Peer_Account")]
+    peer_account: String,
+    #[serde(rename = "MaxCnt")]
+    max_cnt: u32,
+    #[serde(rename = "MinTime")]
+    min_time: u64,
+    #[serde(rename = "MaxTime")]
+    max_time: u64,
+    #[serde(rename = "LastMsgKey", skip_serializing_if = "Option::is_none")]
+    last_msg_key: Option<String>,
+}
+
+impl RoamMsgQuery {
+    /// the messages between `operator_account` and `peer_account` sent from
+    /// the unix time `min_time` to `max_time`.
+    pub fn new(operator_account: &str, peer_account: &str, min_time: u64, max_time: u64) -> Self {
+        RoamMsgQuery {
+            operator_account: operator_account.to_string(),
+            peer_account: peer_account.to_string(),
+            max_cnt: 100,
+            min_time,
+            max_time,
+            last_msg_key: None,
+        }
+    }
+
+    /// answer at most `max_cnt` messages a page, 100 by default.
+    pub fn with_max_cnt(mut self, max_cnt: u32) -> Self {
+        self.max_cnt = max_cnt;
+        self
+    }
+
+    /// the query of the page after `page`, if it is not the last one.
+    pub fn next_page(&self, page: &RoamMsgPage) -> Option<Self> {
+        if page.is_complete() || page.msg_list.is_empty() {
+            return None;
+        }
+
+        let mut next = self.clone();
+        next.max_time = page.last_msg_time;
+        next.last_msg_key = Some(page.last_msg_key.clone());
+        Some(next)
+    }
+}
+
+/// A page of messages answered by `get_roam_msg`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RoamMsgPage {
+    /// 1 if it is the last page, 0 otherwise.
+    pub complete: u8,
+    #[serde(default)]
+    pub last_msg_time: u64,
+    #[serde(default)]
+    pub last_msg_key: String,
+    #[serde(default)]
+    pub msg_list: Vec<RoamMsg>,
+}
+
+impl RoamMsgPage {
+    pub fn is_complete(&self) -> bool {
+        self.complete == 1
+    }
+}
+
+/// A message answered by `get_roam_msg`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RoamMsg {
+    #[serde(rename = "From_Account")]
+    pub from_account: String,
+    #[serde(rename = "To_Account")]
+    pub to_account: String,
+    #[serde(rename = "MsgSeq", default)]
+    pub msg_seq: u64,
+    #[serde(rename = "MsgRandom", default)]
+    pub msg_random: u32,
+    #[serde(rename = "MsgTimeStamp")]
+    pub msg_time_stamp: u64,
+    /// 0 for a normal message, 1 if it has been deleted, 2 if withdrawn.
+    #[serde(rename = "MsgFlagBits", default)]
+    pub msg_flag_bits: u32,
+    #[serde(rename = "MsgKey", default)]
+    pub msg_key: String,
+    #[serde(rename = "MsgBody", default)]
+    pub msg_body: Vec<MsgElement>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BatchSendMsg, ImportMsg, MsgElement, OfflinePushInfo, RoamMsgPage, RoamMsgQuery};
+    use super::{ImportGroupMsg, ImportGroupMsgs, MsgPriority, SendGroupMsg, SendMsg};
+    use serde_json::json;
+
+    #[test]
+    fn test_send_msg_body() {
+        let msg = SendMsg::new("10086", vec![MsgElement::text("hi")]).with_msg_random(42);
+        assert_eq!(
+            serde_json::to_value(&msg).unwrap(),
+            json!({
+                "To_Account": "10086",
+                "MsgRandom": 42,
+                "MsgBody": [{ "MsgType": "TIMTextElem", "MsgContent": { "Text": "hi" } }],
+            })
+        );
+
+        let msg = msg
+            .with_from_account("shop")
+            .with_sync_other_machine(false)
+            .with_offline_push_info(OfflinePushInfo::default().with_title("Shop").without_push());
+        let body = serde_json::to_value(&msg).unwrap();
+        assert_eq!(body["From_Account"], "shop");
+        assert_eq!(body["SyncOtherMachine"], 2);
+        assert_eq!(
+            body["OfflinePushInfo"],
+            json!({ "PushFlag": 1, "Title": "Shop" })
+        );
+    }
+
+    #[test]
+    fn test_send_group_msg_body() {
+        let msg = SendGroupMsg::new("rust", vec![MsgElement::text("hi")]).with_random(42);
+        assert_eq!(
+            serde_json::to_value(&msg).unwrap(),
+            json!({
+                "GroupId": "rust",
+                "Random": 42,
+                "MsgBody": [{ "MsgType": "TIMTextElem", "MsgContent": { "Text": "hi" } }],
+            })
+        );
+
+        let msg = msg
+            .with_from_account("alice")
+            .with_msg_priority(MsgPriority::Lowest)
+            .with_online_only(true);
+        let body = serde_json::to_value(&msg).unwrap();
+        assert_eq!(body["From_Account"], "alice");
+        assert_eq!(body["MsgPriority"], "Lowest");
+        assert_eq!(body["OnlineOnlyFlag"], 1);
+    }
+
+    #[test]
+    fn test_import_group_msg_body() {
+        let msgs = [
+            ImportGroupMsg::new("alice", 1569910200, vec![MsgElement::text("hi")]).with_random(42),
+        ];
+        let body = ImportGroupMsgs {
+            group_id: "rust",
+            msg_list: &msgs,
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({
+                "GroupId": "rust",
+                "MsgList": [{
+                    "From_Account": "alice",
+                    "SendTime": 1569910200,
+                    "Random": 42,
+                    "MsgBody": [{ "MsgType": "TIMTextElem", "MsgContent": { "Text": "hi" } }],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_import_msg_body() {
+        let msg = ImportMsg::new("alice", "bob", 1569910200, vec![MsgElement::text("hi")])
+            .with_msg_random(42);
+        assert_eq!(
+            serde_json::to_value(&msg).unwrap(),
+            json!({
+                "SyncFromOldSystem": 2,
+                "From_Account": "alice",
+                "To_Account": "bob",
+                "MsgRandom": 42,
+                "MsgTimeStamp": 1569910200,
+                "MsgBody": [{ "MsgType": "TIMTextElem", "MsgContent": { "Text": "hi" } }],
+            })
+        );
+        let msg = msg.with_unread(true);
+        assert_eq!(serde_json::to_value(&msg).unwrap()["SyncFromOldSystem"], 1);
+    }
+
+    #[test]
+    fn test_roam_msg_next_page() {
+        let query = RoamMsgQuery::new("alice", "bob", 100, 200).with_max_cnt(2);
+        assert_eq!(
+            serde_json::to_value(&query).unwrap(),
+            json!({
+                "Operator_Account": "alice",
+                "Peer_Account": "bob",
+                "MaxCnt": 2,
+                "MinTime": 100,
+                "MaxTime": 200,
+            })
+        );
+
+        let page: RoamMsgPage = serde_json::from_value(json!({
+            "Complete": 0,
+            "LastMsgTime": 150,
+            "LastMsgKey": "k",
+            "MsgList": [{
+                "From_Account": "alice",
+                "To_Account": "bob",
+                "MsgTimeStamp": 150,
+                "MsgKey": "k",
+                "MsgBody": [{ "MsgType": "TIMTextElem", "MsgContent": { "Text": "hi" } }],
+            }],
+        }))
+        .unwrap();
+        assert_eq!(page.msg_list[0].msg_body[0], MsgElement::text("hi"));
+        let next = query.next_page(&page).unwrap();
+        let next = serde_json::to_value(&next).unwrap();
+        assert_eq!(next["MaxTime"], 150);
+        assert_eq!(next["LastMsgKey"], "k");
+
+        let last = RoamMsgPage {
+            complete: 1,
+            ..page
+        };
+        assert!(query.next_page(&last).is_none());
+    }
+
+    #[test]
+    fn test_batch_send_msg_chunks() {
+        let recipients: Vec<String> = (0..1200).map(|id| id.to_string()).collect();
+        let msg = BatchSendMsg::new(recipients, vec![MsgElement::text("hi")]).with_msg_random(42);
+        let chunks: Vec<serde_json::Value> = msg
+            .chunks()
+            .map(|chunk| serde_json::to_value(&chunk).unwrap())
+            .collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0]["To_Account"].as_array().unwrap().len(), 500);
+        assert_eq!(chunks[1]["To_Account"][0], "500");
+        assert_eq!(chunks[2]["To_Account"].as_array().unwrap().len(), 200);
+        assert_eq!(chunks[2]["MsgRandom"], 42);
+        assert_eq!(chunks[2]["MsgBody"][0]["MsgType"], "TIMTextElem");
+    }
+}
@@ -0,0 +1,57 @@
+use std::fmt;
+use std::time::Duration;
+
+use super::{HttpRequest, HttpResponse};
+use crate::Result;
+
+/// Observes or changes the requests of a REST client before they are posted,
+/// and their responses before they are parsed, e.g. to add trace headers or
+/// record latency.
+///
+/// Middlewares are called in the order they are added to the client, on
+/// every attempt of a call.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{HttpRequest, HttpResponse, Middleware, Result};
+/// use std::time::Duration;
+///
+/// #[derive(Debug)]
+/// struct Traced;
+///
+/// impl Middleware for Traced {
+///     fn on_request(&self, _: &str, _: &str, request: &mut HttpRequest) {
+///         request.headers.push(("x-trace-id".to_string(), "42".to_string()));
+///         println!("posting {}", request.redacted_url());
+///     }
+///
+///     fn on_response(
+///         &self,
+///         service: &str,
+///         command: &str,
+///         response: &mut Result<HttpResponse>,
+///         elapsed: Duration,
+///     ) {
+///         println!("{}/{} answered in {:?}", service, command, elapsed);
+///     }
+/// }
+/// ```
+pub trait Middleware: fmt::Debug + Send + Sync {
+    /// called before `request` of `service/command` is posted.
+    fn on_request(&self, service: &str, command: &str, request: &mut HttpRequest) {
+        let _ = (service, command, request);
+    }
+
+    /// called with the `response` of `service/command`, or the failure to
+    /// receive one, `elapsed` after the request was posted.
+    fn on_response(
+        &self,
+        service: &str,
+        command: &str,
+        response: &mut Result<HttpResponse>,
+        elapsed: Duration,
+    ) {
+        let _ = (service, command, response, elapsed);
+    }
+}
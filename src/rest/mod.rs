@@ -0,0 +1,320 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use self::response::parse_response;
+use crate::{Result, SharedSigner, SigInfo, TlsSigError, UserSig};
+
+mod account;
+#[cfg(feature = "rest-blocking")]
+mod blocking;
+#[cfg(feature = "rest")]
+mod client;
+mod elem;
+mod group;
+mod message;
+mod middleware;
+mod openconfig;
+mod openim;
+mod options;
+mod profile;
+mod push;
+mod recentcontact;
+mod response;
+mod retry;
+mod sns;
+mod stats;
+mod transport;
+
+#[cfg(feature = "rest-blocking")]
+pub use self::blocking::{GroupMembers, RecentContacts, RoamMsgs, TimClientBlocking};
+#[cfg(feature = "rest")]
+pub use self::client::{GroupMemberStream, RecentContactStream, RoamMsgStream, TimClient};
+pub use self::elem::{CustomElem, FaceElem, FileElem, ImageElem, ImageInfo, LocationElem};
+pub use self::elem::{SoundElem, TextElem, VideoFileElem};
+pub use self::group::{AddGroupMemberItem, AddGroupMemberStatus, ApplyJoinOption, CreateGroup};
+pub use self::group::{CreateTopic, DestroyTopicItem, ModifyTopic, TopicInfo};
+pub use self::group::{GroupAttr, ImportGroupMember};
+pub use self::group::{GroupInfo, GroupMember, GroupMemberPage, GroupMemberQuery, GroupRole};
+pub use self::group::{GroupType, ModifyGroupBaseInfo, ModifyGroupMemberInfo, ShuttedMember};
+pub use self::message::{BatchSendMsg, ImportMsg, MsgElement, OfflinePushInfo, SendMsg};
+pub use self::message::{ImportGroupMsg, MsgPriority, SendGroupMsg};
+pub use self::message::{RoamMsg, RoamMsgPage, RoamMsgQuery};
+pub use self::middleware::Middleware;
+pub use self::openconfig::NoSpeaking;
+pub use self::options::HttpOptions;
+pub use self::profile::{ProfileItem, ProfileTag, UserProfile};
+pub use self::push::{ImPush, PushAttrs, PushCondition, PushReport, PushTags};
+pub use self::recentcontact::{RecentContact, RecentContactPage, RecentContactQuery, Session};
+pub use self::response::{
+    AccountCheckItem, AccountDeleteItem, AccountError, AccountImportReport, AccountStatus,
+    BatchSendMsgResult, ImportGroupMsgItem, MultiAccountImportResult, OnlineState, OnlineStatus,
+    OnlineStatusResult, PlatformStatus, SendGroupMsgResult, SendMsgResult, TimResponse,
+};
+pub use self::retry::RetryPolicy;
+pub use self::sns::{AddFriendItem, Friend, FriendCheckItem, FriendPage, FriendQuery};
+pub use self::sns::{BlacklistCheckItem, BlacklistItem, BlacklistPage};
+pub use self::sns::{BlacklistQuery, BlacklistRelation};
+pub use self::sns::{FriendRelation, FriendResultItem, FriendType, FriendUpdate, SnsItem};
+pub use self::stats::{AppInfo, ChatType, HistoryFile};
+#[cfg(feature = "rest-blocking")]
+pub use self::transport::BlockingHttpTransport;
+pub use self::transport::{HttpRequest, HttpResponse};
+#[cfg(feature = "rest")]
+pub use self::transport::{HttpTransport, TransportFuture};
+
+/// The endpoint of the Tencent IM REST API in mainland China.
+pub const DEFAULT_ENDPOINT: &str = "https://console.tim.qq.com";
+
+/// how requests are signed, shared by the async and blocking clients.
+///
+/// The admin sig is reused by clones until `renew_before` is left, a fifth
+/// of its expire unless set.
+#[derive(Debug, Clone)]
+struct AdminSigner {
+    signer: SharedSigner,
+    admin: String,
+    endpoint: String,
+    sig_expire: Duration,
+    renew_before: Option<Duration>,
+    sig: Arc<Mutex<Option<SigInfo>>>,
+}
+
+impl AdminSigner {
+    fn new(signer: SharedSigner, admin: &str) -> Self {
+        AdminSigner {
+            signer,
+            admin: admin.to_string(),
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            sig_expire: Duration::from_secs(3600),
+            renew_before: None,
+            sig: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn set_endpoint(&mut self, endpoint: &str) {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+    }
+
+    /// the url of `v4/{service}/{command}` signed by the admin sig, with a
+    /// new random.
+    fn url(&self, service: &str, command: &str) -> Result<Url> {
+        let usersig = self.admin_sig()?;
+        let random = random().to_string();
+
+        Url::parse_with_params(
+            &format!("{}/v4/{}/{}", self.endpoint, service, command),
+            &[
+                ("sdkappid", self.signer.appid().to_string().as_str()),
+                ("identifier", &self.admin),
+                ("usersig", &usersig),
+                ("random", &random),
+                ("contenttype", "json"),
+            ],
+        )
+        .map_err(|e| TlsSigError::RestUnavailable(format!("invalid endpoint: {}", e)))
+    }
+
+    /// the cached admin sig, signed anew if it is about to expire.
+    fn admin_sig(&self) -> Result<UserSig> {
+        let now = self.signer.clock.now();
+        let mut sig = self.sig.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(info) = &*sig {
+            let remaining = info.expires_at.duration_since(now).unwrap_or_default();
+            if remaining > self.renew_before() {
+                return Ok(info.sig.clone());
+            }
+        }
+
+        let info = self
+            .signer
+            .gen_sign_info(&self.admin, self.sig_expire, None)?;
+        let usersig = info.sig.clone();
+        *sig = Some(info);
+        Ok(usersig)
+    }
+
+    fn renew_before(&self) -> Duration {
+        self.renew_before.unwrap_or(self.sig_expire / 5)
+    }
+
+    /// drop the cached admin sig after the API rejects it.
+    fn forget_sig(&self) {
+        *self.sig.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// whether a call failed with `result` should be retried once with a new
+/// admin sig.
+fn should_resign<T>(result: &Result<T>) -> bool {
+    matches!(result, Err(TlsSigError::RestFailed { code, .. }) if code.is_sig_error())
+}
+
+/// a new random 32-bit number, for the `random` parameter telling requests
+/// apart.
+fn random() -> u32 {
+    RandomState::new().hash_one(SystemTime::now()) as u32
+}
+
+/// the request posting `body` as JSON to `url`.
+fn json_request<B: Serialize + ?Sized>(url: Url, body: &B) -> Result<HttpRequest> {
+    let body = serde_json::to_vec(body)
+        .map_err(|e| TlsSigError::RestUnavailable(format!("invalid request body: {}", e)))?;
+    Ok(HttpRequest {
+        url,
+        headers: vec![("content-type".to_string(), "application/json".to_string())],
+        body,
+    })
+}
+
+/// parse the `response` of `service/command`, failing with
+/// [`RestHttpStatus`] if its status is an error.
+///
+/// [`RestHttpStatus`]: enum.TlsSigError.html#variant.RestHttpStatus
+fn parse_reply<T: DeserializeOwned>(
+    service: &str,
+    command: &str,
+    response: HttpResponse,
+) -> Result<T> {
+    if response.status >= 400 {
+        return Err(TlsSigError::RestHttpStatus(response.status));
+    }
+
+    parse_response(service, command, &response.body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{random, AdminSigner};
+    use crate::clock::ManualClock;
+    use crate::TlsSigApiVer2;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::thread::{self, JoinHandle};
+    use std::time::Duration;
+
+    pub(super) const MOCK_KEY: &str =
+        "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+
+    /// serve `body` once, returning the request line and body received.
+    pub(super) fn serve(body: &'static str) -> (String, JoinHandle<(String, String)>) {
+        let (addr, server) = serve_all(vec![body]);
+        (
+            addr,
+            thread::spawn(move || server.join().unwrap().remove(0)),
+        )
+    }
+
+    /// serve `bodies` one connection each, returning the request lines and
+    /// bodies received.
+    pub(super) fn serve_all(
+        bodies: Vec<&'static str>,
+    ) -> (String, JoinHandle<Vec<(String, String)>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            bodies
+                .into_iter()
+                .map(|body| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = Vec::new();
+                    let mut buf = [0; 4096];
+                    // read until the whole body announced by content-length is in
+                    let (head, sent) = loop {
+                        let n = stream.read(&mut buf).unwrap();
+                        request.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&request).to_string();
+                        if let Some(end) = text.find("\r\n\r\n") {
+                            let head = text[..end].to_lowercase();
+                            let length: usize = head
+                                .lines()
+                                .find_map(|line| line.strip_prefix("content-length: "))
+                                .map_or(0, |length| length.parse().unwrap());
+                            if text.len() >= end + 4 + length {
+                                break (text[..end].to_string(), text[end + 4..].to_string());
+                            }
+                        }
+                    };
+
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                    .unwrap();
+                    (head.lines().next().unwrap().to_string(), sent)
+                })
+                .collect()
+        });
+
+        (addr, server)
+    }
+
+    #[test]
+    fn test_admin_signer_url() {
+        let mut auth = AdminSigner::new(TlsSigApiVer2::new(1400000000, MOCK_KEY).into(), "admin");
+        auth.set_endpoint("https://console.tim.qq.com/");
+        let url = auth.url("im_open_login_svc", "account_import").unwrap();
+        assert_eq!(url.host_str(), Some("console.tim.qq.com"));
+        assert_eq!(url.path(), "/v4/im_open_login_svc/account_import");
+
+        let params: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        let names: Vec<&str> = params.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["sdkappid", "identifier", "usersig", "random", "contenttype"]
+        );
+        assert_eq!(params[0].1, "1400000000");
+        assert_eq!(params[1].1, "admin");
+        assert!(auth.signer.verify_sign("admin", &params[2].1).is_ok());
+        assert!(params[3].1.parse::<u32>().is_ok());
+        assert_eq!(params[4].1, "json");
+
+        assert_ne!(random(), random());
+    }
+
+    #[test]
+    fn test_admin_signer_caches_sig() {
+        let clock = ManualClock(Arc::new(AtomicU64::new(1569910200)));
+        let signer = TlsSigApiVer2::new(1400000000, MOCK_KEY).with_clock(clock.clone());
+        let auth = AdminSigner::new(signer.into(), "admin");
+
+        let sig = auth.admin_sig().unwrap();
+        clock.0.fetch_add(2879, Ordering::SeqCst);
+        assert_eq!(auth.clone().admin_sig().unwrap(), sig);
+
+        // a fifth of the expire left
+        clock.0.fetch_add(1, Ordering::SeqCst);
+        let renewed = auth.admin_sig().unwrap();
+        assert_ne!(renewed, sig);
+
+        clock.0.fetch_add(1, Ordering::SeqCst);
+        auth.forget_sig();
+        assert_ne!(auth.admin_sig().unwrap(), renewed);
+    }
+
+    #[test]
+    fn test_admin_signer_renew_before() {
+        let clock = ManualClock(Arc::new(AtomicU64::new(1569910200)));
+        let signer = TlsSigApiVer2::new(1400000000, MOCK_KEY).with_clock(clock.clone());
+        let mut auth = AdminSigner::new(signer.into(), "admin");
+        auth.sig_expire = Duration::from_secs(36000);
+        auth.renew_before = Some(Duration::from_secs(600));
+
+        let sig = auth.admin_sig().unwrap();
+        clock.0.fetch_add(35399, Ordering::SeqCst);
+        assert_eq!(auth.admin_sig().unwrap(), sig);
+        clock.0.fetch_add(1, Ordering::SeqCst);
+        assert_ne!(auth.admin_sig().unwrap(), sig);
+    }
+}
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+/// How long an account is muted globally, set by
+/// `openconfigsvr/setnospeaking` and answered by `getnospeaking`.
+///
+/// Each time is in seconds, 0 if the account is not muted and
+/// [`u32::MAX`] if it is muted forever.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::NoSpeaking;
+///
+/// let muted = NoSpeaking::forever().with_group_msg_nospeaking_time(0);
+/// assert!(muted.is_c2c_muted());
+/// assert!(!muted.is_group_muted());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoSpeaking {
+    /// how long the account can not send one-to-one messages.
+    #[serde(rename = "C2CmsgNospeakingTime", default)]
+    pub c2c_msg_nospeaking_time: u32,
+    /// how long the account can not send messages in any group.
+    #[serde(rename = "GroupmsgNospeakingTime", default)]
+    pub group_msg_nospeaking_time: u32,
+}
+
+impl NoSpeaking {
+    /// muted for `seconds`, one-to-one and in groups.
+    pub fn new(seconds: u32) -> Self {
+        NoSpeaking {
+            c2c_msg_nospeaking_time: seconds,
+            group_msg_nospeaking_time: seconds,
+        }
+    }
+
+    /// muted forever, one-to-one and in groups.
+    pub fn forever() -> Self {
+        NoSpeaking::new(u32::MAX)
+    }
+
+    /// not muted at all, e.g. to unmute an account.
+    pub fn unmuted() -> Self {
+        NoSpeaking::new(0)
+    }
+
+    pub fn with_c2c_msg_nospeaking_time(mut self, seconds: u32) -> Self {
+        self.c2c_msg_nospeaking_time = seconds;
+        self
+    }
+
+    pub fn with_group_msg_nospeaking_time(mut self, seconds: u32) -> Self {
+        self.group_msg_nospeaking_time = seconds;
+        self
+    }
+
+    pub fn is_c2c_muted(&self) -> bool {
+        self.c2c_msg_nospeaking_time != 0
+    }
+
+    pub fn is_group_muted(&self) -> bool {
+        self.group_msg_nospeaking_time != 0
+    }
+}
+
+/// the body of `openconfigsvr/setnospeaking`.
+#[derive(Debug, Serialize)]
+pub(crate) struct SetNoSpeaking<'a> {
+    #[serde(rename = "Set_Account")]
+    pub(crate) set_account: &'a str,
+    #[serde(flatten)]
+    pub(crate) no_speaking: NoSpeaking,
+}
+
+/// the body of `openconfigsvr/getnospeaking`.
+#[derive(Debug, Serialize)]
+pub(crate) struct GetNoSpeaking<'a> {
+    #[serde(rename = "Get_Account")]
+    pub(crate) get_account: &'a str,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GetNoSpeaking, NoSpeaking, SetNoSpeaking};
+    use serde_json::json;
+
+    #[test]
+    fn test_no_speaking_bodies() {
+        let body = SetNoSpeaking {
+            set_account: "alice",
+            no_speaking: NoSpeaking::forever().with_group_msg_nospeaking_time(7200),
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({
+                "Set_Account": "alice",
+                "C2CmsgNospeakingTime": 4294967295u32,
+                "GroupmsgNospeakingTime": 7200,
+            })
+        );
+
+        let body = GetNoSpeaking {
+            get_account: "alice",
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "Get_Account": "alice" })
+        );
+    }
+
+    #[test]
+    fn test_no_speaking() {
+        let muted: NoSpeaking =
+            serde_json::from_value(json!({ "GroupmsgNospeakingTime": 7200 })).unwrap();
+        assert!(!muted.is_c2c_muted());
+        assert!(muted.is_group_muted());
+        assert_eq!(NoSpeaking::unmuted(), NoSpeaking::default());
+    }
+}
@@ -0,0 +1,70 @@
+use serde::Serialize;
+
+/// the body of `openim/query_online_status`.
+#[derive(Debug, Serialize)]
+pub(crate) struct QueryOnlineStatus<'a> {
+    #[serde(rename = "IsNeedDetail")]
+    pub(crate) is_need_detail: u8,
+    #[serde(rename = "To_Account")]
+    pub(crate) to_account: &'a [&'a str],
+}
+
+/// the body of `openim/admin_msgwithdraw`.
+#[derive(Debug, Serialize)]
+pub(crate) struct MsgWithdraw<'a> {
+    #[serde(rename = "From_Account")]
+    pub(crate) from_account: &'a str,
+    #[serde(rename = "To_Account")]
+    pub(crate) to_account: &'a str,
+    #[serde(rename = "MsgKey")]
+    pub(crate) msg_key: &'a str,
+}
+
+/// the body of `openim/admin_set_msg_read`.
+#[derive(Debug, Serialize)]
+pub(crate) struct SetMsgRead<'a> {
+    #[serde(rename = "Report_Account")]
+    pub(crate) report_account: &'a str,
+    #[serde(rename = "Peer_Account")]
+    pub(crate) peer_account: &'a str,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MsgWithdraw, QueryOnlineStatus, SetMsgRead};
+    use serde_json::json;
+
+    #[test]
+    fn test_query_online_status_body() {
+        let body = QueryOnlineStatus {
+            is_need_detail: 1,
+            to_account: &["a", "b"],
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "IsNeedDetail": 1, "To_Account": ["a", "b"] })
+        );
+    }
+
+    #[test]
+    fn test_msg_withdraw_body() {
+        let body = MsgWithdraw {
+            from_account: "a",
+            to_account: "b",
+            msg_key: "k",
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "From_Account": "a", "To_Account": "b", "MsgKey": "k" })
+        );
+
+        let body = SetMsgRead {
+            report_account: "b",
+            peer_account: "a",
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "Report_Account": "b", "Peer_Account": "a" })
+        );
+    }
+}
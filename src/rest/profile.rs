@@ -0,0 +1,246 @@
+use std::fmt;
+
+use serde::de::{Deserializer, Error as _};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A field of the profile of an account, see
+/// [the profile fields](https://cloud.tencent.com/document/product/269/1500).
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::ProfileTag;
+///
+/// assert_eq!(ProfileTag::Nick.to_string(), "Tag_Profile_IM_Nick");
+/// assert_eq!(ProfileTag::custom("Hobby").to_string(), "Tag_Profile_Custom_Hobby");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProfileTag {
+    Nick,
+    /// `Gender_Type_Unknown`, `Gender_Type_Female` or `Gender_Type_Male`.
+    Gender,
+    /// e.g. 19800101.
+    BirthDay,
+    Location,
+    SelfSignature,
+    /// `AllowType_Type_NeedConfirm`, `AllowType_Type_AllowAny` or
+    /// `AllowType_Type_DenyAny`.
+    AllowType,
+    Language,
+    /// the url of the avatar.
+    Image,
+    MsgSettings,
+    /// `AdminForbid_Type_None` or `AdminForbid_Type_SendOut`.
+    AdminForbidType,
+    Level,
+    Role,
+    /// a custom field, named without the `Tag_Profile_Custom_` prefix.
+    Custom(String),
+}
+
+const CUSTOM_PREFIX: &str = "Tag_Profile_Custom_";
+
+const STANDARD_TAGS: [(ProfileTag, &str); 12] = [
+    (ProfileTag::Nick, "Tag_Profile_IM_Nick"),
+    (ProfileTag::Gender, "Tag_Profile_IM_Gender"),
+    (ProfileTag::BirthDay, "Tag_Profile_IM_BirthDay"),
+    (ProfileTag::Location, "Tag_Profile_IM_Location"),
+    (ProfileTag::SelfSignature, "Tag_Profile_IM_SelfSignature"),
+    (ProfileTag::AllowType, "Tag_Profile_IM_AllowType"),
+    (ProfileTag::Language, "Tag_Profile_IM_Language"),
+    (ProfileTag::Image, "Tag_Profile_IM_Image"),
+    (ProfileTag::MsgSettings, "Tag_Profile_IM_MsgSettings"),
+    (
+        ProfileTag::AdminForbidType,
+        "Tag_Profile_IM_AdminForbidType",
+    ),
+    (ProfileTag::Level, "Tag_Profile_IM_Level"),
+    (ProfileTag::Role, "Tag_Profile_IM_Role"),
+];
+
+impl ProfileTag {
+    /// the custom field `name`, e.g. `Hobby` for `Tag_Profile_Custom_Hobby`.
+    pub fn custom(name: &str) -> Self {
+        ProfileTag::Custom(name.to_string())
+    }
+
+    /// the tag named `tag`, if it is a standard or custom one.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        if let Some(name) = tag.strip_prefix(CUSTOM_PREFIX) {
+            return Some(ProfileTag::custom(name));
+        }
+
+        STANDARD_TAGS
+            .iter()
+            .find(|(_, standard)| *standard == tag)
+            .map(|(tag, _)| tag.clone())
+    }
+}
+
+impl fmt::Display for ProfileTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileTag::Custom(name) => write!(f, "{}{}", CUSTOM_PREFIX, name),
+            tag => {
+                let (_, name) = STANDARD_TAGS.iter().find(|(t, _)| t == tag).unwrap();
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+impl Serialize for ProfileTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ProfileTag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tag = String::deserialize(deserializer)?;
+        ProfileTag::from_tag(&tag)
+            .ok_or_else(|| D::Error::custom(format!("unknown profile tag {}", tag)))
+    }
+}
+
+/// A field of a profile with its value, set by `portrait_set` or answered by
+/// `portrait_get`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{ProfileItem, ProfileTag};
+///
+/// let nick = ProfileItem::new(ProfileTag::Nick, "alice");
+/// let level = ProfileItem::new(ProfileTag::Level, 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ProfileItem {
+    pub tag: ProfileTag,
+    /// a string or an integer, depending on the field.
+    pub value: Value,
+}
+
+impl ProfileItem {
+    pub fn new<V: Into<Value>>(tag: ProfileTag, value: V) -> Self {
+        ProfileItem {
+            tag,
+            value: value.into(),
+        }
+    }
+}
+
+/// The profile of an account answered by `portrait_get`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UserProfile {
+    #[serde(rename = "To_Account")]
+    pub to_account: String,
+    #[serde(rename = "ProfileItem", default)]
+    pub profile_item: Vec<ProfileItem>,
+    /// not 0 if the profile could not be got.
+    #[serde(rename = "ResultCode", default)]
+    pub result_code: u32,
+    #[serde(rename = "ResultInfo", default)]
+    pub result_info: String,
+}
+
+impl UserProfile {
+    /// the value of the field `tag`, if it is answered.
+    pub fn get(&self, tag: &ProfileTag) -> Option<&Value> {
+        self.profile_item
+            .iter()
+            .find(|item| item.tag == *tag)
+            .map(|item| &item.value)
+    }
+}
+
+/// the body of `profile/portrait_get`.
+#[derive(Debug, Serialize)]
+pub(crate) struct PortraitGet<'a> {
+    #[serde(rename = "To_Account")]
+    pub(crate) to_account: &'a [&'a str],
+    #[serde(rename = "TagList")]
+    pub(crate) tag_list: &'a [ProfileTag],
+}
+
+/// the result of `profile/portrait_get`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct PortraitGetReply {
+    #[serde(rename = "UserProfileItem", default)]
+    pub(crate) user_profile_item: Vec<UserProfile>,
+}
+
+/// the body of `profile/portrait_set`.
+#[derive(Debug, Serialize)]
+pub(crate) struct PortraitSet<'a> {
+    #[serde(rename = "From_Account")]
+    pub(crate) from_account: &'a str,
+    #[serde(rename = "ProfileItem")]
+    pub(crate) profile_item: &'a [ProfileItem],
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PortraitGet, PortraitGetReply, PortraitSet, ProfileItem, ProfileTag};
+    use serde_json::json;
+
+    #[test]
+    fn test_profile_tag() {
+        for (tag, name) in super::STANDARD_TAGS.iter() {
+            assert_eq!(tag.to_string(), *name);
+            assert_eq!(ProfileTag::from_tag(name).as_ref(), Some(tag));
+        }
+        assert_eq!(
+            ProfileTag::from_tag("Tag_Profile_Custom_Hobby"),
+            Some(ProfileTag::custom("Hobby"))
+        );
+        assert_eq!(ProfileTag::from_tag("Tag_Profile_IM_Unknown"), None);
+        assert!(serde_json::from_value::<ProfileTag>(json!("Tag_Profile_IM_Unknown")).is_err());
+    }
+
+    #[test]
+    fn test_portrait_bodies() {
+        let body = PortraitGet {
+            to_account: &["alice"],
+            tag_list: &[ProfileTag::Nick, ProfileTag::custom("Hobby")],
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({
+                "To_Account": ["alice"],
+                "TagList": ["Tag_Profile_IM_Nick", "Tag_Profile_Custom_Hobby"],
+            })
+        );
+
+        let body = PortraitSet {
+            from_account: "alice",
+            profile_item: &[ProfileItem::new(ProfileTag::Level, 3)],
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({
+                "From_Account": "alice",
+                "ProfileItem": [{ "Tag": "Tag_Profile_IM_Level", "Value": 3 }],
+            })
+        );
+
+        let reply: PortraitGetReply = serde_json::from_value(json!({
+            "UserProfileItem": [
+                {
+                    "To_Account": "alice",
+                    "ProfileItem": [{ "Tag": "Tag_Profile_IM_Nick", "Value": "Alice" }],
+                    "ResultCode": 0,
+                },
+                { "To_Account": "nobody", "ResultCode": 40006, "ResultInfo": "not found" },
+            ]
+        }))
+        .unwrap();
+        let profiles = reply.user_profile_item;
+        assert_eq!(profiles[0].get(&ProfileTag::Nick), Some(&json!("Alice")));
+        assert_eq!(profiles[0].get(&ProfileTag::Image), None);
+        assert_eq!(profiles[1].result_code, 40006);
+    }
+}
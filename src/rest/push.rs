@@ -0,0 +1,369 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::random;
+use super::{MsgElement, OfflinePushInfo};
+
+/// Which accounts a message is pushed to by `all_member_push`, by the push
+/// tags or attributes set on them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum PushCondition {
+    /// the accounts having all the tags.
+    TagsAnd(Vec<String>),
+    /// the accounts having any of the tags.
+    TagsOr(Vec<String>),
+    /// the accounts having all the attribute values.
+    AttrsAnd(BTreeMap<String, String>),
+    /// the accounts having any of the attribute values.
+    AttrsOr(BTreeMap<String, String>),
+}
+
+/// A message pushed to all the accounts of the app, or to those matching a
+/// [`PushCondition`], by `all_member_push`.
+///
+/// [`PushCondition`]: enum.PushCondition.html
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{ImPush, MsgElement, PushCondition};
+///
+/// let push = ImPush::new(vec![MsgElement::text("new season is live")])
+///     .with_condition(PushCondition::TagsOr(vec!["vip".to_string()]))
+///     .with_msg_life_time(86400);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImPush {
+    #[serde(rename = "From_Account", skip_serializing_if = "Option::is_none")]
+    from_account: Option<String>,
+    #[serde(rename = "MsgRandom")]
+    msg_random: u32,
+    #[serde(rename = "MsgLifeTime", skip_serializing_if = "Option::is_none")]
+    msg_life_time: Option<u32>,
+    #[serde(rename = "Condition", skip_serializing_if = "Option::is_none")]
+    condition: Option<PushCondition>,
+    #[serde(rename = "MsgBody")]
+    msg_body: Vec<MsgElement>,
+    #[serde(rename = "OfflinePushInfo", skip_serializing_if = "Option::is_none")]
+    offline_push_info: Option<OfflinePushInfo>,
+}
+
+impl ImPush {
+    /// a message of `msg_body` to all the accounts, sent by the admin.
+    pub fn new(msg_body: Vec<MsgElement>) -> Self {
+        ImPush {
+            from_account: None,
+            msg_random: random(),
+            msg_life_time: None,
+            condition: None,
+            msg_body,
+            offline_push_info: None,
+        }
+    }
+
+    /// send the message as `from_account` instead of the admin.
+    pub fn with_from_account(mut self, from_account: &str) -> Self {
+        self.from_account = Some(from_account.to_string());
+        self
+    }
+
+    pub fn with_msg_random(mut self, msg_random: u32) -> Self {
+        self.msg_random = msg_random;
+        self
+    }
+
+    /// keep the message for the accounts offline for `seconds`, at most 7
+    /// days. It is not kept by default.
+    pub fn with_msg_life_time(mut self, seconds: u32) -> Self {
+        self.msg_life_time = Some(seconds);
+        self
+    }
+
+    /// only push the message to the accounts matching `condition`.
+    pub fn with_condition(mut self, condition: PushCondition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    pub fn with_offline_push_info(mut self, info: OfflinePushInfo) -> Self {
+        self.offline_push_info = Some(info);
+        self
+    }
+
+    pub fn msg_random(&self) -> u32 {
+        self.msg_random
+    }
+}
+
+/// The delivery of a push created by `all_member_push`, answered by
+/// `im_get_push_report`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PushReport {
+    pub task_id: String,
+    /// the unix time the push starts at.
+    #[serde(default)]
+    pub start_time: u64,
+    /// the unix time the push finishes at, 0 if it is not finished.
+    #[serde(default)]
+    pub finish_time: u64,
+    /// 0 if the push is waiting, 1 if it is being sent, 2 if it is done.
+    #[serde(default)]
+    pub state: u8,
+    /// the number of the accounts the message is delivered to.
+    #[serde(default)]
+    pub total: u64,
+}
+
+impl PushReport {
+    pub fn is_finished(&self) -> bool {
+        self.state == 2
+    }
+}
+
+/// The push attributes of an account, matched by
+/// [`PushCondition::AttrsAnd`] and [`PushCondition::AttrsOr`].
+///
+/// [`PushCondition::AttrsAnd`]: enum.PushCondition.html#variant.AttrsAnd
+/// [`PushCondition::AttrsOr`]: enum.PushCondition.html#variant.AttrsOr
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::PushAttrs;
+///
+/// let attrs = PushAttrs::new("alice").with_attr("sex", "female").with_attr("city", "shenzhen");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushAttrs {
+    #[serde(rename = "To_Account")]
+    pub to_account: String,
+    /// the values of the attributes by their names.
+    #[serde(rename = "Attrs", default)]
+    pub attrs: BTreeMap<String, String>,
+}
+
+impl PushAttrs {
+    /// no attributes of `to_account` yet.
+    pub fn new(to_account: &str) -> Self {
+        PushAttrs {
+            to_account: to_account.to_string(),
+            attrs: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_attr(mut self, name: &str, value: &str) -> Self {
+        self.attrs.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+/// The push tags of an account, matched by [`PushCondition::TagsAnd`] and
+/// [`PushCondition::TagsOr`].
+///
+/// [`PushCondition::TagsAnd`]: enum.PushCondition.html#variant.TagsAnd
+/// [`PushCondition::TagsOr`]: enum.PushCondition.html#variant.TagsOr
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::PushTags;
+///
+/// let tags = PushTags::new("alice", &["vip", "beta"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushTags {
+    #[serde(rename = "To_Account")]
+    pub to_account: String,
+    #[serde(rename = "Tags", default)]
+    pub tags: Vec<String>,
+}
+
+impl PushTags {
+    pub fn new(to_account: &str, tags: &[&str]) -> Self {
+        PushTags {
+            to_account: to_account.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+}
+
+/// the body of `all_member_push/im_set_attr_name` and the result of
+/// `im_get_attr_name`.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct AttrNames {
+    /// the names of the attributes by their indexes, from "0" to "9", kept as
+    /// strings since the response is flattened into a [`TimResponse`].
+    ///
+    /// [`TimResponse`]: struct.TimResponse.html
+    #[serde(rename = "AttrNames", default)]
+    attr_names: BTreeMap<String, String>,
+}
+
+impl AttrNames {
+    pub(crate) fn new(attr_names: &BTreeMap<u8, String>) -> Self {
+        AttrNames {
+            attr_names: attr_names
+                .iter()
+                .map(|(index, name)| (index.to_string(), name.clone()))
+                .collect(),
+        }
+    }
+
+    /// the names by their indexes, skipping any index not a number.
+    pub(crate) fn into_names(self) -> BTreeMap<u8, String> {
+        self.attr_names
+            .into_iter()
+            .filter_map(|(index, name)| Some((index.parse().ok()?, name)))
+            .collect()
+    }
+}
+
+/// the body of the endpoints taking nothing but accounts, e.g.
+/// `all_member_push/im_get_tag`.
+#[derive(Debug, Serialize)]
+pub(crate) struct PushAccounts<'a> {
+    #[serde(rename = "To_Account")]
+    pub(crate) to_account: &'a [&'a str],
+}
+
+/// the body of `all_member_push/im_set_attr` and the result of `im_get_attr`.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct UserAttrs<T> {
+    #[serde(rename = "UserAttrs", default)]
+    pub(crate) user_attrs: T,
+}
+
+/// the attributes of an account removed by `all_member_push/im_remove_attr`.
+#[derive(Debug, Serialize)]
+pub(crate) struct RemovedAttrs<'a> {
+    #[serde(rename = "To_Account")]
+    pub(crate) to_account: &'a str,
+    #[serde(rename = "Attrs")]
+    pub(crate) attrs: &'a [&'a str],
+}
+
+/// the body of `all_member_push/im_add_tag` and `im_remove_tag`, and the
+/// result of `im_get_tag`.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct UserTags<T> {
+    #[serde(rename = "UserTags", default)]
+    pub(crate) user_tags: T,
+}
+
+/// the result of `all_member_push/im_push`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct PushTask {
+    pub(crate) task_id: String,
+}
+
+/// the body of `all_member_push/im_get_push_report`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct PushReportQuery<'a> {
+    pub(crate) task_ids: &'a [&'a str],
+}
+
+/// the result of `all_member_push/im_get_push_report`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct PushReports {
+    #[serde(default)]
+    pub(crate) reports: Vec<PushReport>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AttrNames, ImPush, PushAttrs, PushCondition, PushReports, PushTags};
+    use super::{RemovedAttrs, UserAttrs, UserTags};
+    use crate::MsgElement;
+    use serde_json::json;
+
+    #[test]
+    fn test_im_push_body() {
+        let push = ImPush::new(vec![MsgElement::text("hi")]).with_msg_random(42);
+        assert_eq!(
+            serde_json::to_value(&push).unwrap(),
+            json!({
+                "MsgRandom": 42,
+                "MsgBody": [{ "MsgType": "TIMTextElem", "MsgContent": { "Text": "hi" } }],
+            })
+        );
+
+        let attrs = vec![("sex".to_string(), "female".to_string())];
+        let push = push
+            .with_msg_life_time(3600)
+            .with_condition(PushCondition::AttrsAnd(attrs.into_iter().collect()));
+        let body = serde_json::to_value(&push).unwrap();
+        assert_eq!(body["MsgLifeTime"], 3600);
+        assert_eq!(
+            body["Condition"],
+            json!({ "AttrsAnd": { "sex": "female" } })
+        );
+
+        let push = push.with_condition(PushCondition::TagsOr(vec!["vip".to_string()]));
+        let body = serde_json::to_value(&push).unwrap();
+        assert_eq!(body["Condition"], json!({ "TagsOr": ["vip"] }));
+    }
+
+    #[test]
+    fn test_push_attr_bodies() {
+        let names = vec![(0, "sex".to_string()), (1, "city".to_string())];
+        let names = AttrNames::new(&names.into_iter().collect());
+        assert_eq!(
+            serde_json::to_value(&names).unwrap(),
+            json!({ "AttrNames": { "0": "sex", "1": "city" } })
+        );
+        let names: AttrNames =
+            serde_json::from_value(json!({ "AttrNames": { "2": "age" } })).unwrap();
+        assert_eq!(names.into_names()[&2], "age");
+
+        let body = UserAttrs {
+            user_attrs: vec![PushAttrs::new("alice").with_attr("sex", "female")],
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "UserAttrs": [{ "To_Account": "alice", "Attrs": { "sex": "female" } }] })
+        );
+        let body = UserAttrs {
+            user_attrs: vec![RemovedAttrs {
+                to_account: "alice",
+                attrs: &["sex"],
+            }],
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "UserAttrs": [{ "To_Account": "alice", "Attrs": ["sex"] }] })
+        );
+
+        let body = UserTags {
+            user_tags: &[PushTags::new("alice", &["vip"])],
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "UserTags": [{ "To_Account": "alice", "Tags": ["vip"] }] })
+        );
+    }
+
+    #[test]
+    fn test_push_reports() {
+        let reports: PushReports = serde_json::from_value(json!({
+            "Reports": [
+                {
+                    "TaskId": "53D6D8C0",
+                    "StartTime": 1569910200,
+                    "FinishTime": 1569910260,
+                    "State": 2,
+                    "Total": 50,
+                },
+                { "TaskId": "53D6D8C1", "State": 1 },
+            ]
+        }))
+        .unwrap();
+        assert!(reports.reports[0].is_finished());
+        assert_eq!(reports.reports[0].total, 50);
+        assert!(!reports.reports[1].is_finished());
+    }
+}
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+
+/// A conversation in the recent contacts of an account.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Session {
+    /// the one-to-one conversation with an account.
+    C2c(String),
+    /// the conversation of a group.
+    Group(String),
+}
+
+/// A query of the recent contacts of an account, answered a page at a time
+/// by `get_recent_contact_list`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::RecentContactQuery;
+///
+/// let query = RecentContactQuery::new("alice");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RecentContactQuery {
+    #[serde(rename = "From_Account")]
+    from_account: String,
+    time_stamp: u64,
+    start_index: u32,
+    top_time_stamp: u64,
+    top_start_index: u32,
+}
+
+impl RecentContactQuery {
+    /// all the recent contacts of `from_account`.
+    pub fn new(from_account: &str) -> Self {
+        RecentContactQuery {
+            from_account: from_account.to_string(),
+            time_stamp: 0,
+            start_index: 0,
+            top_time_stamp: 0,
+            top_start_index: 0,
+        }
+    }
+
+    /// the query of the page after `page`, if it is not the last one.
+    pub fn next_page(&self, page: &RecentContactPage) -> Option<Self> {
+        if page.is_complete() || page.session_item.is_empty() {
+            return None;
+        }
+
+        let mut next = self.clone();
+        next.time_stamp = page.time_stamp;
+        next.start_index = page.start_index;
+        next.top_time_stamp = page.top_time_stamp;
+        next.top_start_index = page.top_start_index;
+        Some(next)
+    }
+}
+
+/// A page of recent contacts answered by `get_recent_contact_list`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RecentContactPage {
+    /// 1 if it is the last page, 0 otherwise.
+    #[serde(default)]
+    pub complete_flag: u8,
+    #[serde(default)]
+    pub session_item: Vec<RecentContact>,
+    #[serde(default)]
+    time_stamp: u64,
+    #[serde(default)]
+    start_index: u32,
+    #[serde(default)]
+    top_time_stamp: u64,
+    #[serde(default)]
+    top_start_index: u32,
+}
+
+impl RecentContactPage {
+    pub fn is_complete(&self) -> bool {
+        self.complete_flag == 1
+    }
+}
+
+/// A recent contact answered by `get_recent_contact_list`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RecentContact {
+    /// 1 for a one-to-one conversation, 2 for a group.
+    #[serde(rename = "Type")]
+    pub session_type: u8,
+    #[serde(rename = "To_Account", default)]
+    pub to_account: String,
+    #[serde(rename = "GroupId", default)]
+    pub group_id: String,
+    /// the unix time of the last message of the conversation.
+    #[serde(rename = "MsgTime", default)]
+    pub msg_time: u64,
+    /// 1 if the conversation is pinned, 0 otherwise.
+    #[serde(rename = "TopFlag", default)]
+    pub top_flag: u8,
+}
+
+impl RecentContact {
+    pub fn session(&self) -> Session {
+        match self.session_type {
+            2 => Session::Group(self.group_id.clone()),
+            _ => Session::C2c(self.to_account.clone()),
+        }
+    }
+
+    pub fn is_top(&self) -> bool {
+        self.top_flag == 1
+    }
+}
+
+/// the body of `recentcontact/delete`.
+#[derive(Debug, Serialize)]
+pub(crate) struct DeleteRecentContact<'a> {
+    #[serde(rename = "From_Account")]
+    from_account: &'a str,
+    #[serde(rename = "Type")]
+    session_type: u8,
+    #[serde(rename = "To_Account", skip_serializing_if = "Option::is_none")]
+    to_account: Option<&'a str>,
+    #[serde(rename = "ToGroupid", skip_serializing_if = "Option::is_none")]
+    to_group_id: Option<&'a str>,
+    /// 1 to delete the roaming messages of the conversation too.
+    #[serde(rename = "ClearRamble")]
+    clear_ramble: u8,
+}
+
+impl<'a> DeleteRecentContact<'a> {
+    pub(crate) fn new(from_account: &'a str, session: &'a Session, clear_ramble: bool) -> Self {
+        let (session_type, to_account, to_group_id) = match session {
+            Session::C2c(account) => (1, Some(account.as_str()), None),
+            Session::Group(group_id) => (2, None, Some(group_id.as_str())),
+        };
+        DeleteRecentContact {
+            from_account,
+            session_type,
+            to_account,
+            to_group_id,
+            clear_ramble: if clear_ramble { 1 } else { 0 },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DeleteRecentContact, RecentContactPage, RecentContactQuery, Session};
+    use serde_json::json;
+
+    #[test]
+    fn test_delete_recent_contact_body() {
+        let session = Session::C2c("bob".to_string());
+        assert_eq!(
+            serde_json::to_value(DeleteRecentContact::new("alice", &session, true)).unwrap(),
+            json!({ "From_Account": "alice", "Type": 1, "To_Account": "bob", "ClearRamble": 1 })
+        );
+
+        let session = Session::Group("rust".to_string());
+        assert_eq!(
+            serde_json::to_value(DeleteRecentContact::new("alice", &session, false)).unwrap(),
+            json!({ "From_Account": "alice", "Type": 2, "ToGroupid": "rust", "ClearRamble": 0 })
+        );
+    }
+
+    #[test]
+    fn test_recent_contact_next_page() {
+        let query = RecentContactQuery::new("alice");
+        let page: RecentContactPage = serde_json::from_value(json!({
+            "CompleteFlag": 0,
+            "TimeStamp": 1569910200,
+            "StartIndex": 1,
+            "TopTimeStamp": 1569900000,
+            "TopStartIndex": 0,
+            "SessionItem": [
+                { "Type": 1, "To_Account": "bob", "MsgTime": 1569910200, "TopFlag": 1 },
+                { "Type": 2, "GroupId": "rust", "MsgTime": 1569910100, "TopFlag": 0 },
+            ],
+        }))
+        .unwrap();
+        let sessions: Vec<_> = page
+            .session_item
+            .iter()
+            .map(|item| item.session())
+            .collect();
+        assert_eq!(
+            sessions,
+            [
+                Session::C2c("bob".to_string()),
+                Session::Group("rust".to_string())
+            ]
+        );
+        assert!(page.session_item[0].is_top());
+
+        let next = query.next_page(&page).unwrap();
+        assert_eq!(
+            serde_json::to_value(&next).unwrap(),
+            json!({
+                "From_Account": "alice",
+                "TimeStamp": 1569910200,
+                "StartIndex": 1,
+                "TopTimeStamp": 1569900000,
+                "TopStartIndex": 0,
+            })
+        );
+
+        let page: RecentContactPage =
+            serde_json::from_value(json!({ "CompleteFlag": 1, "SessionItem": [] })).unwrap();
+        assert_eq!(next.next_page(&page), None);
+    }
+}
@@ -0,0 +1,410 @@
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::Deserialize;
+
+use crate::{Result, TimErrorCode, TlsSigError};
+
+/// A response of the REST API: the status every endpoint answers with, next
+/// to the payload `T` of the endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TimResponse<T> {
+    /// `OK` if the request succeeded, `FAIL` otherwise.
+    pub action_status: String,
+    #[serde(default)]
+    pub error_code: u32,
+    #[serde(default)]
+    pub error_info: String,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T> TimResponse<T> {
+    pub fn is_ok(&self) -> bool {
+        self.action_status == "OK"
+    }
+
+    /// the payload, or [`RestFailed`] if the request failed.
+    ///
+    /// [`RestFailed`]: enum.TlsSigError.html#variant.RestFailed
+    pub fn into_result(self) -> Result<T> {
+        if !self.is_ok() {
+            return Err(TlsSigError::RestFailed {
+                code: self.error_code.into(),
+                info: self.error_info,
+            });
+        }
+
+        Ok(self.data)
+    }
+}
+
+/// The result of checking an account with `account_check`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AccountCheckItem {
+    #[serde(rename = "UserID")]
+    pub user_id: String,
+    pub result_code: u32,
+    #[serde(default)]
+    pub result_info: String,
+    /// `Imported` or `NotImported`.
+    #[serde(default)]
+    pub account_status: String,
+}
+
+impl AccountCheckItem {
+    pub fn is_imported(&self) -> bool {
+        self.status() == AccountStatus::Imported
+    }
+
+    pub fn status(&self) -> AccountStatus {
+        match (self.result_code, self.account_status.as_str()) {
+            (0, "Imported") => AccountStatus::Imported,
+            (0, _) => AccountStatus::NotImported,
+            (code, _) => AccountStatus::Failed(code.into()),
+        }
+    }
+}
+
+/// Whether an account checked with `account_check` is imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccountStatus {
+    Imported,
+    NotImported,
+    /// the account could not be checked.
+    Failed(TimErrorCode),
+}
+
+/// The result of deleting an account with `account_delete`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AccountDeleteItem {
+    #[serde(rename = "UserID")]
+    pub user_id: String,
+    pub result_code: u32,
+    #[serde(default)]
+    pub result_info: String,
+}
+
+impl AccountDeleteItem {
+    pub fn is_deleted(&self) -> bool {
+        self.result_code == 0
+    }
+}
+
+/// The result of importing accounts with `multiaccount_import`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MultiAccountImportResult {
+    /// the accounts failed to be imported, which may be retried.
+    #[serde(default)]
+    pub fail_accounts: Vec<String>,
+}
+
+impl MultiAccountImportResult {
+    /// whether every account has been imported.
+    pub fn is_complete(&self) -> bool {
+        self.fail_accounts.is_empty()
+    }
+}
+
+/// The result of importing accounts with `import_accounts_from`.
+#[derive(Debug, Default)]
+pub struct AccountImportReport {
+    pub succeeded: Vec<String>,
+    /// the accounts answered failed by every attempt to import them.
+    pub failed: Vec<String>,
+    /// the batches of accounts whose requests failed, e.g. after the
+    /// [`RetryPolicy`] gave up, with why.
+    ///
+    /// [`RetryPolicy`]: struct.RetryPolicy.html
+    pub failed_batches: Vec<(Vec<String>, TlsSigError)>,
+}
+
+impl AccountImportReport {
+    /// whether every account has been imported.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.failed_batches.is_empty()
+    }
+
+    /// the accounts not imported, failed alone or with their batch.
+    pub fn not_imported(&self) -> impl Iterator<Item = &str> {
+        self.failed
+            .iter()
+            .chain(self.failed_batches.iter().flat_map(|(batch, _)| batch))
+            .map(String::as_str)
+    }
+
+    /// record the accounts of `batch` imported as answered by `result`,
+    /// answering those failed to be retried.
+    pub(crate) fn imported(
+        &mut self,
+        batch: Vec<String>,
+        result: &MultiAccountImportResult,
+    ) -> Vec<String> {
+        let (failed, imported) = batch
+            .into_iter()
+            .partition(|account| result.fail_accounts.contains(account));
+        self.succeeded.extend::<Vec<_>>(imported);
+        failed
+    }
+}
+
+/// The result of querying the online status of accounts with
+/// `query_online_status`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct OnlineStatusResult {
+    #[serde(rename = "QueryResult", default)]
+    pub query_result: Vec<OnlineStatus>,
+    /// the accounts whose status could not be queried, e.g. as they are not
+    /// imported.
+    #[serde(rename = "ErrorList", default)]
+    pub error_list: Vec<AccountError>,
+}
+
+/// The online status of an account.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct OnlineStatus {
+    #[serde(rename = "To_Account")]
+    pub account: String,
+    #[serde(rename = "Status")]
+    pub status: OnlineState,
+    /// the status on each platform logged in, if queried with the detail.
+    #[serde(rename = "Detail", default)]
+    pub detail: Vec<PlatformStatus>,
+}
+
+/// The online status of an account on a platform, e.g. `iPhone` or `Web`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlatformStatus {
+    pub platform: String,
+    pub status: OnlineState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum OnlineState {
+    /// connected to the server.
+    Online,
+    /// in the background, reachable by offline pushes.
+    PushOnline,
+    Offline,
+    /// a state unknown to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// An account a batch request failed for, e.g. as it is not imported.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AccountError {
+    #[serde(rename = "To_Account")]
+    pub account: String,
+    #[serde(rename = "ErrorCode")]
+    pub error_code: u32,
+}
+
+/// The result of sending a message with `send_msg`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SendMsgResult {
+    /// the unix time the message is sent at.
+    pub msg_time: u64,
+    /// the key identifying the message, e.g. to withdraw it.
+    #[serde(default)]
+    pub msg_key: String,
+}
+
+/// The result of sending a message with `send_group_msg`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SendGroupMsgResult {
+    /// the unix time the message is sent at.
+    pub msg_time: u64,
+    /// the sequence of the message in the group.
+    #[serde(default)]
+    pub msg_seq: u64,
+}
+
+/// The result of importing a message with `import_group_msg`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ImportGroupMsgItem {
+    /// 0 if imported, 1 if failed, 2 if imported before.
+    pub result: u8,
+    #[serde(default)]
+    pub msg_seq: u64,
+    #[serde(default)]
+    pub msg_time: u64,
+}
+
+impl ImportGroupMsgItem {
+    /// whether the message is in the group now, imported by this call or a
+    /// previous one.
+    pub fn is_imported(&self) -> bool {
+        self.result != 1
+    }
+}
+
+/// the result of `group_open_http_svc/import_group_msg`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct ImportGroupMsgReply {
+    #[serde(default)]
+    pub(crate) import_msg_result: Vec<ImportGroupMsgItem>,
+}
+
+/// The result of sending a message with `batch_send_msg`, merged across the
+/// requests it is sent in.
+#[derive(Debug, Default)]
+pub struct BatchSendMsgResult {
+    /// the key of the message sent by each request succeeded.
+    pub msg_keys: Vec<String>,
+    /// the accounts the message could not be sent to.
+    pub error_list: Vec<AccountError>,
+    /// the recipients of the requests failed, with why.
+    pub failed_batches: Vec<(Vec<String>, TlsSigError)>,
+}
+
+impl BatchSendMsgResult {
+    /// whether the message has been sent to every recipient.
+    pub fn is_complete(&self) -> bool {
+        self.error_list.is_empty() && self.failed_batches.is_empty()
+    }
+
+    /// the recipients of the requests failed, which may be sent to again.
+    pub fn unsent(&self) -> impl Iterator<Item = &str> {
+        self.failed_batches
+            .iter()
+            .flat_map(|(batch, _)| batch)
+            .map(String::as_str)
+    }
+}
+
+/// the result of a request of `openim/batchsendmsg`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct BatchSendMsgReply {
+    #[serde(default)]
+    pub(crate) msg_key: String,
+    #[serde(default)]
+    pub(crate) error_list: Vec<AccountError>,
+}
+
+/// the `ResultItem` list answered by batch account endpoints.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ResultItems<T> {
+    #[serde(rename = "ResultItem", default = "Vec::new")]
+    pub(crate) items: Vec<T>,
+}
+
+/// the payload of endpoints answering nothing but their status.
+pub(crate) type Empty = IgnoredAny;
+
+/// parse the `body` answered by `service/command`.
+pub(crate) fn parse_response<T: DeserializeOwned>(
+    service: &str,
+    command: &str,
+    body: &str,
+) -> Result<T> {
+    serde_json::from_str::<TimResponse<T>>(body)
+        .map_err(|e| {
+            TlsSigError::RestUnavailable(format!(
+                "invalid response of {}/{}: {}",
+                service, command, e
+            ))
+        })?
+        .into_result()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_response, AccountCheckItem, AccountImportReport, AccountStatus, Empty};
+    use super::{MultiAccountImportResult, OnlineState, OnlineStatusResult, ResultItems};
+    use crate::{TimErrorCode, TlsSigError};
+    use serde_json::Value;
+
+    #[test]
+    fn test_parse_response() {
+        let body = r#"{"ActionStatus":"OK","ErrorCode":0,"ErrorInfo":"","ResultItem":[
+            {"UserID":"a","ResultCode":0,"ResultInfo":"","AccountStatus":"Imported"},
+            {"UserID":"b","ResultCode":0,"ResultInfo":"","AccountStatus":"NotImported"}
+        ]}"#;
+        let checked: ResultItems<AccountCheckItem> =
+            parse_response("im_open_login_svc", "account_check", body).unwrap();
+        assert_eq!(checked.items.len(), 2);
+        assert_eq!(checked.items[0].user_id, "a");
+        assert!(checked.items[0].is_imported());
+        assert!(!checked.items[1].is_imported());
+        assert_eq!(checked.items[1].status(), AccountStatus::NotImported);
+
+        let raw: Value = parse_response("im_open_login_svc", "account_check", body).unwrap();
+        assert_eq!(raw["ResultItem"][1]["UserID"], "b");
+
+        let body = r#"{"ActionStatus":"OK"}"#;
+        assert!(parse_response::<Empty>("im_open_login_svc", "kick", body).is_ok());
+        let imported: MultiAccountImportResult =
+            parse_response("im_open_login_svc", "multiaccount_import", body).unwrap();
+        assert!(imported.is_complete());
+    }
+
+    #[test]
+    fn test_parse_online_status() {
+        let body = r#"{"ActionStatus":"OK","ErrorInfo":"","ErrorCode":0,
+            "QueryResult":[
+                {"To_Account":"a","Status":"Offline"},
+                {"To_Account":"b","Status":"Online","Detail":[
+                    {"Platform":"iPhone","Status":"PushOnline"},
+                    {"Platform":"Web","Status":"Busy"}
+                ]}
+            ],
+            "ErrorList":[{"To_Account":"c","ErrorCode":70107}]}"#;
+        let result: OnlineStatusResult =
+            parse_response("openim", "query_online_status", body).unwrap();
+        assert_eq!(result.query_result[0].status, OnlineState::Offline);
+        assert!(result.query_result[0].detail.is_empty());
+        let detail = &result.query_result[1].detail;
+        assert_eq!(detail[0].platform, "iPhone");
+        assert_eq!(detail[0].status, OnlineState::PushOnline);
+        assert_eq!(detail[1].status, OnlineState::Unknown);
+        assert_eq!(result.error_list[0].account, "c");
+        assert_eq!(result.error_list[0].error_code, 70107);
+    }
+
+    #[test]
+    fn test_parse_response_failed() {
+        let body = r#"{"ActionStatus":"FAIL","ErrorCode":70001,"ErrorInfo":"usersig expired"}"#;
+        match parse_response::<Empty>("im_open_login_svc", "kick", body) {
+            Err(TlsSigError::RestFailed { code, info }) => {
+                assert_eq!(code, TimErrorCode::SigExpired);
+                assert_eq!(info, "usersig expired");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        assert!(matches!(
+            parse_response::<Empty>("im_open_login_svc", "kick", "<html>"),
+            Err(TlsSigError::RestUnavailable(_))
+        ));
+    }
+
+    #[test]
+    fn test_account_import_report() {
+        let mut report = AccountImportReport::default();
+        let result = MultiAccountImportResult {
+            fail_accounts: vec!["b".to_string()],
+        };
+        let batch = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let failed = report.imported(batch, &result);
+        assert_eq!(failed, ["b"]);
+        assert_eq!(report.succeeded, ["a", "c"]);
+        assert!(report.is_complete());
+
+        report.failed.extend(failed);
+        report.failed_batches.push((
+            vec!["d".to_string()],
+            TlsSigError::RestUnavailable("timed out".to_string()),
+        ));
+        assert!(!report.is_complete());
+        assert_eq!(report.not_imported().collect::<Vec<_>>(), ["b", "d"]);
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use super::random;
+use crate::{TimErrorCode, TlsSigError};
+
+/// When and how long a REST client waits to retry a failed call.
+///
+/// By default a call is tried up to 3 times, backing off exponentially from
+/// 200 milliseconds up to 5 seconds with a random jitter, when the server
+/// can not be reached, answers a 5xx status, or fails with an error code
+/// that is [`retryable`]. Rejected admin sigs are retried once with a new sig
+/// regardless of the policy.
+///
+/// [`retryable`]: enum.TimErrorCode.html#method.is_retryable
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{RetryPolicy, TimErrorCode};
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::default()
+///     .with_max_attempts(5)
+///     .with_backoff(Duration::from_millis(100), Duration::from_secs(2))
+///     .with_retry_code(TimErrorCode::AccountNotFound);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    server_errors: bool,
+    codes: HashSet<TimErrorCode>,
+    transient_codes: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+            server_errors: true,
+            codes: HashSet::new(),
+            transient_codes: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// try every call once.
+    pub fn never() -> Self {
+        RetryPolicy::default().with_max_attempts(1)
+    }
+
+    /// try every call up to `attempts` times, at least once.
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// wait `base` before the first retry, doubled for each next one up to
+    /// `max`.
+    pub fn with_backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.base_delay = base;
+        self.max_delay = max.max(base);
+        self
+    }
+
+    /// wait a random half to all of the backoff, so that clients failing
+    /// together do not retry together. On by default.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// retry when the server can not be reached or answers a 5xx status. On
+    /// by default.
+    pub fn with_server_errors(mut self, retry: bool) -> Self {
+        self.server_errors = retry;
+        self
+    }
+
+    /// retry the codes that are [`retryable`]. On by default.
+    ///
+    /// [`retryable`]: enum.TimErrorCode.html#method.is_retryable
+    pub fn with_transient_codes(mut self, retry: bool) -> Self {
+        self.transient_codes = retry;
+        self
+    }
+
+    /// retry `code` too.
+    pub fn with_retry_code(mut self, code: TimErrorCode) -> Self {
+        self.codes.insert(code);
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// whether a call failed with `e` is worth retrying.
+    pub fn should_retry(&self, e: &TlsSigError) -> bool {
+        match e {
+            TlsSigError::RestUnavailable(_) => self.server_errors,
+            TlsSigError::RestHttpStatus(status) => self.server_errors && *status >= 500,
+            TlsSigError::RestFailed { code, .. } => {
+                self.codes.contains(code) || (self.transient_codes && code.is_retryable())
+            }
+            _ => false,
+        }
+    }
+
+    /// how long to wait before the `retry`th retry, from 1.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let doubling = 2u32.saturating_pow(retry.saturating_sub(1));
+        let backoff = self.base_delay.saturating_mul(doubling).min(self.max_delay);
+        if !self.jitter {
+            return backoff;
+        }
+
+        let half = backoff / 2;
+        let jitter = match half.as_millis() as u64 {
+            0 => Duration::from_millis(0),
+            millis => Duration::from_millis(u64::from(random()) % (millis + 1)),
+        };
+        half + jitter
+    }
+
+    /// the delay after the `attempts`th attempt failed with `result`, if it
+    /// is to be retried.
+    pub(crate) fn retry_after<T>(
+        &self,
+        result: &crate::Result<T>,
+        attempts: u32,
+    ) -> Option<Duration> {
+        match result {
+            Err(e) if attempts < self.max_attempts && self.should_retry(e) => {
+                Some(self.delay(attempts))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RetryPolicy;
+    use crate::{TimErrorCode, TlsSigError};
+    use std::time::Duration;
+
+    fn failed(code: u32) -> TlsSigError {
+        TlsSigError::RestFailed {
+            code: code.into(),
+            info: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_should_retry() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry(&TlsSigError::RestUnavailable("timeout".to_string())));
+        assert!(policy.should_retry(&TlsSigError::RestHttpStatus(502)));
+        assert!(!policy.should_retry(&TlsSigError::RestHttpStatus(404)));
+        assert!(policy.should_retry(&failed(70500)));
+        assert!(!policy.should_retry(&failed(70107)));
+        assert!(!policy.should_retry(&failed(70001)));
+        assert!(!policy.should_retry(&TlsSigError::InvalidKey));
+
+        let policy = RetryPolicy::default()
+            .with_server_errors(false)
+            .with_transient_codes(false)
+            .with_retry_code(TimErrorCode::AccountNotFound);
+        assert!(!policy.should_retry(&TlsSigError::RestHttpStatus(502)));
+        assert!(!policy.should_retry(&failed(70500)));
+        assert!(policy.should_retry(&failed(70107)));
+    }
+
+    #[test]
+    fn test_retry_policy_delay() {
+        let policy = RetryPolicy::default()
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(500))
+            .with_jitter(false);
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+        assert_eq!(policy.delay(4), Duration::from_millis(500));
+        assert_eq!(policy.delay(100), Duration::from_millis(500));
+
+        let policy = policy.with_jitter(true);
+        for retry in 1..10 {
+            let delay = policy.delay(retry);
+            let backoff = policy.clone().with_jitter(false).delay(retry);
+            assert!(delay >= backoff / 2 && delay <= backoff, "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_attempts() {
+        let policy = RetryPolicy::default().with_backoff(Duration::ZERO, Duration::ZERO);
+        let result: crate::Result<()> = Err(failed(70500));
+        assert!(policy.retry_after(&result, 1).is_some());
+        assert!(policy.retry_after(&result, 3).is_none());
+        assert!(policy.retry_after(&Ok(()), 1).is_none());
+
+        assert_eq!(RetryPolicy::never().max_attempts(), 1);
+        assert!(RetryPolicy::never().retry_after(&result, 1).is_none());
+    }
+}
@@ -0,0 +1,702 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Whether a friendship is changed or checked both ways or one way only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FriendType {
+    /// both accounts are each other's friend.
+    Both,
+    /// only the other account is a friend of the account.
+    Single,
+}
+
+impl FriendType {
+    /// the value of `kind`, e.g. `Add_Type_Both` for `Add`.
+    fn tag(self, kind: &str) -> String {
+        match self {
+            FriendType::Both => format!("{}_Type_Both", kind),
+            FriendType::Single => format!("{}_Type_Single", kind),
+        }
+    }
+}
+
+/// An account added as a friend by `friend_add`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::AddFriendItem;
+///
+/// let friend = AddFriendItem::new("bob", "Android")
+///     .with_remark("Bob")
+///     .with_group_name("colleagues")
+///     .with_add_wording("I'm Alice");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddFriendItem {
+    #[serde(rename = "To_Account")]
+    to_account: String,
+    #[serde(rename = "Remark", skip_serializing_if = "Option::is_none")]
+    remark: Option<String>,
+    #[serde(rename = "GroupName", skip_serializing_if = "Option::is_none")]
+    group_name: Option<String>,
+    #[serde(rename = "AddSource")]
+    add_source: String,
+    #[serde(rename = "AddWording", skip_serializing_if = "Option::is_none")]
+    add_wording: Option<String>,
+}
+
+impl AddFriendItem {
+    /// add `to_account`, found by the `add_source` of the app, e.g. `Android`
+    /// for `AddSource_Type_Android`.
+    pub fn new(to_account: &str, add_source: &str) -> Self {
+        AddFriendItem {
+            to_account: to_account.to_string(),
+            remark: None,
+            group_name: None,
+            add_source: format!("AddSource_Type_{}", add_source),
+            add_wording: None,
+        }
+    }
+
+    pub fn with_remark(mut self, remark: &str) -> Self {
+        self.remark = Some(remark.to_string());
+        self
+    }
+
+    /// put the friend in the friend group `group_name`, which is created if
+    /// it does not exist.
+    pub fn with_group_name(mut self, group_name: &str) -> Self {
+        self.group_name = Some(group_name.to_string());
+        self
+    }
+
+    /// the words sent with the request.
+    pub fn with_add_wording(mut self, add_wording: &str) -> Self {
+        self.add_wording = Some(add_wording.to_string());
+        self
+    }
+}
+
+/// A relationship field of a friend, e.g. `Tag_SNS_IM_Remark`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SnsItem {
+    pub tag: String,
+    pub value: Value,
+}
+
+impl SnsItem {
+    pub fn new<V: Into<Value>>(tag: &str, value: V) -> Self {
+        SnsItem {
+            tag: tag.to_string(),
+            value: value.into(),
+        }
+    }
+}
+
+/// The changes of a friend made by `friend_update`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::FriendUpdate;
+///
+/// let changes = FriendUpdate::new("bob")
+///     .with_remark("Bobby")
+///     .with_groups(&["colleagues", "climbing"])
+///     .with_custom("Nickname", "bb");
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FriendUpdate {
+    #[serde(rename = "To_Account")]
+    to_account: String,
+    #[serde(rename = "SnsItem")]
+    sns_item: Vec<SnsItem>,
+}
+
+impl FriendUpdate {
+    /// change nothing of the friend `to_account` yet.
+    pub fn new(to_account: &str) -> Self {
+        FriendUpdate {
+            to_account: to_account.to_string(),
+            sns_item: Vec::new(),
+        }
+    }
+
+    pub fn with_remark(self, remark: &str) -> Self {
+        self.with_item(SnsItem::new("Tag_SNS_IM_Remark", remark))
+    }
+
+    /// put the friend in the friend groups `groups` only.
+    pub fn with_groups(self, groups: &[&str]) -> Self {
+        self.with_item(SnsItem::new("Tag_SNS_IM_Group", groups.to_vec()))
+    }
+
+    /// set the custom field `name`, e.g. `Nickname` for
+    /// `Tag_SNS_Custom_Nickname`.
+    pub fn with_custom<V: Into<Value>>(self, name: &str, value: V) -> Self {
+        self.with_item(SnsItem::new(&format!("Tag_SNS_Custom_{}", name), value))
+    }
+
+    pub fn with_item(mut self, item: SnsItem) -> Self {
+        self.sns_item.push(item);
+        self
+    }
+}
+
+/// The result of adding, deleting or updating a friend, or of blacklisting
+/// an account or not.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FriendResultItem {
+    #[serde(rename = "To_Account")]
+    pub to_account: String,
+    pub result_code: u32,
+    #[serde(default)]
+    pub result_info: String,
+}
+
+impl FriendResultItem {
+    pub fn is_ok(&self) -> bool {
+        self.result_code == 0
+    }
+}
+
+/// A query of the friends of an account, answered a page at a time by
+/// `friend_get`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::FriendQuery;
+///
+/// let query = FriendQuery::new("alice");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FriendQuery {
+    #[serde(rename = "From_Account")]
+    from_account: String,
+    #[serde(rename = "StartIndex")]
+    start_index: u32,
+    #[serde(rename = "StandardSequence", skip_serializing_if = "Option::is_none")]
+    standard_sequence: Option<u64>,
+    #[serde(rename = "CustomSequence", skip_serializing_if = "Option::is_none")]
+    custom_sequence: Option<u64>,
+}
+
+impl FriendQuery {
+    /// all the friends of `from_account`.
+    pub fn new(from_account: &str) -> Self {
+        FriendQuery {
+            from_account: from_account.to_string(),
+            start_index: 0,
+            standard_sequence: None,
+            custom_sequence: None,
+        }
+    }
+
+    /// the query of the page after `page`, if it is not the last one. It
+    /// answers the same version of the friends as `page` does.
+    pub fn next_page(&self, page: &FriendPage) -> Option<Self> {
+        if page.is_complete() || page.user_data_item.is_empty() {
+            return None;
+        }
+
+        let mut next = self.clone();
+        next.start_index = page.next_start_index;
+        next.standard_sequence = Some(page.standard_sequence);
+        next.custom_sequence = Some(page.custom_sequence);
+        Some(next)
+    }
+}
+
+/// A page of friends answered by `friend_get`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FriendPage {
+    #[serde(default)]
+    pub user_data_item: Vec<Friend>,
+    /// the number of all the friends of the account.
+    #[serde(default)]
+    pub friend_num: u32,
+    /// 1 if it is the last page, 0 otherwise.
+    #[serde(default)]
+    pub complete_flag: u8,
+    #[serde(default)]
+    pub next_start_index: u32,
+    /// the version of the standard fields of the friends.
+    #[serde(default)]
+    pub standard_sequence: u64,
+    /// the version of the custom fields of the friends.
+    #[serde(default)]
+    pub custom_sequence: u64,
+}
+
+impl FriendPage {
+    pub fn is_complete(&self) -> bool {
+        self.complete_flag == 1
+    }
+}
+
+/// A friend answered by `friend_get`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Friend {
+    #[serde(rename = "To_Account")]
+    pub to_account: String,
+    /// the relationship and profile fields of the friend.
+    #[serde(rename = "ValueItem", default)]
+    pub value_item: Vec<SnsItem>,
+}
+
+impl Friend {
+    /// the value of the field `tag`, if it is answered.
+    pub fn get(&self, tag: &str) -> Option<&Value> {
+        self.value_item
+            .iter()
+            .find(|item| item.tag == tag)
+            .map(|item| &item.value)
+    }
+}
+
+/// The relationship between two accounts checked by `friend_check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum FriendRelation {
+    /// each is a friend of the other.
+    #[serde(rename = "CheckResult_Type_BothWay")]
+    BothWay,
+    /// the other account is a friend of the account only.
+    #[serde(rename = "CheckResult_Type_AWithB")]
+    AWithB,
+    /// the account is a friend of the other account only.
+    #[serde(rename = "CheckResult_Type_BWithA")]
+    BWithA,
+    #[serde(rename = "CheckResult_Type_NoRelation")]
+    NoRelation,
+    #[serde(other)]
+    Unknown,
+}
+
+/// The result of checking a friend with `friend_check`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FriendCheckItem {
+    #[serde(rename = "To_Account")]
+    pub to_account: String,
+    pub relation: FriendRelation,
+    #[serde(default)]
+    pub result_code: u32,
+    #[serde(default)]
+    pub result_info: String,
+}
+
+/// A query of the blacklist of an account, answered a page at a time by
+/// `black_list_get`.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::BlacklistQuery;
+///
+/// let query = BlacklistQuery::new("alice").with_max_limited(50);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlacklistQuery {
+    #[serde(rename = "From_Account")]
+    from_account: String,
+    #[serde(rename = "StartIndex")]
+    start_index: u32,
+    #[serde(rename = "MaxLimited")]
+    max_limited: u32,
+    #[serde(rename = "LastSequence")]
+    last_sequence: u64,
+}
+
+impl BlacklistQuery {
+    /// all the accounts blacklisted by `from_account`.
+    pub fn new(from_account: &str) -> Self {
+        BlacklistQuery {
+            from_account: from_account.to_string(),
+            start_index: 0,
+            max_limited: 100,
+            last_sequence: 0,
+        }
+    }
+
+    /// answer at most `max_limited` accounts a page, 100 by default.
+    pub fn with_max_limited(mut self, max_limited: u32) -> Self {
+        self.max_limited = max_limited;
+        self
+    }
+
+    /// the query of the page after `page`, if it is not the last one.
+    pub fn next_page(&self, page: &BlacklistPage) -> Option<Self> {
+        if page.start_index == 0 || page.black_list_item.is_empty() {
+            return None;
+        }
+
+        let mut next = self.clone();
+        next.start_index = page.start_index;
+        next.last_sequence = page.current_sequence;
+        Some(next)
+    }
+}
+
+/// A page of blacklisted accounts answered by `black_list_get`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BlacklistPage {
+    #[serde(default)]
+    pub black_list_item: Vec<BlacklistItem>,
+    /// where the next page starts, 0 on the last page.
+    #[serde(default)]
+    pub start_index: u32,
+    /// the version of the blacklist.
+    #[serde(rename = "CurruentSequence", default)]
+    pub current_sequence: u64,
+}
+
+/// An account blacklisted, answered by `black_list_get`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BlacklistItem {
+    #[serde(rename = "To_Account")]
+    pub to_account: String,
+    /// the unix time the account is blacklisted at.
+    #[serde(default)]
+    pub add_black_time_stamp: u64,
+}
+
+/// The blacklisting between two accounts checked by `black_list_check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum BlacklistRelation {
+    /// each has blacklisted the other.
+    #[serde(rename = "BlackCheckResult_Type_BothWay")]
+    BothWay,
+    /// the account has blacklisted the other account only.
+    #[serde(rename = "BlackCheckResult_Type_AWithB")]
+    AWithB,
+    /// the other account has blacklisted the account only.
+    #[serde(rename = "BlackCheckResult_Type_BWithA")]
+    BWithA,
+    #[serde(rename = "BlackCheckResult_Type_NO")]
+    NoRelation,
+    #[serde(other)]
+    Unknown,
+}
+
+/// The result of checking an account with `black_list_check`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BlacklistCheckItem {
+    #[serde(rename = "To_Account")]
+    pub to_account: String,
+    pub relation: BlacklistRelation,
+    #[serde(default)]
+    pub result_code: u32,
+    #[serde(default)]
+    pub result_info: String,
+}
+
+/// the body of `sns/black_list_add` and `black_list_delete`.
+#[derive(Debug, Serialize)]
+pub(crate) struct Blacklist<'a> {
+    #[serde(rename = "From_Account")]
+    pub(crate) from_account: &'a str,
+    #[serde(rename = "To_Account")]
+    pub(crate) to_account: &'a [&'a str],
+}
+
+/// the body of `sns/black_list_check`.
+#[derive(Debug, Serialize)]
+pub(crate) struct BlacklistCheck<'a> {
+    #[serde(rename = "From_Account")]
+    from_account: &'a str,
+    #[serde(rename = "To_Account")]
+    to_account: &'a [&'a str],
+    #[serde(rename = "CheckType")]
+    check_type: String,
+}
+
+impl<'a> BlacklistCheck<'a> {
+    pub(crate) fn new(
+        from_account: &'a str,
+        accounts: &'a [&'a str],
+        check_type: FriendType,
+    ) -> Self {
+        BlacklistCheck {
+            from_account,
+            to_account: accounts,
+            check_type: check_type.tag("BlackCheckResult"),
+        }
+    }
+}
+
+/// the result of `sns/black_list_check`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct BlacklistCheckReply {
+    #[serde(rename = "BlackListCheckItem", default)]
+    pub(crate) black_list_check_item: Vec<BlacklistCheckItem>,
+}
+
+/// the body of `sns/friend_add`.
+#[derive(Debug, Serialize)]
+pub(crate) struct FriendAdd<'a> {
+    #[serde(rename = "From_Account")]
+    from_account: &'a str,
+    #[serde(rename = "AddFriendItem")]
+    add_friend_item: &'a [AddFriendItem],
+    #[serde(rename = "AddType")]
+    add_type: String,
+    /// 1 to add the friends without their confirmation.
+    #[serde(rename = "ForceAddFlags")]
+    force_add_flags: u8,
+}
+
+impl<'a> FriendAdd<'a> {
+    pub(crate) fn new(
+        from_account: &'a str,
+        items: &'a [AddFriendItem],
+        add_type: FriendType,
+        force: bool,
+    ) -> Self {
+        FriendAdd {
+            from_account,
+            add_friend_item: items,
+            add_type: add_type.tag("Add"),
+            force_add_flags: if force { 1 } else { 0 },
+        }
+    }
+}
+
+/// the body of `sns/friend_delete`.
+#[derive(Debug, Serialize)]
+pub(crate) struct FriendDelete<'a> {
+    #[serde(rename = "From_Account")]
+    from_account: &'a str,
+    #[serde(rename = "To_Account")]
+    to_account: &'a [&'a str],
+    #[serde(rename = "DeleteType")]
+    delete_type: String,
+}
+
+impl<'a> FriendDelete<'a> {
+    pub(crate) fn new(
+        from_account: &'a str,
+        accounts: &'a [&'a str],
+        delete_type: FriendType,
+    ) -> Self {
+        FriendDelete {
+            from_account,
+            to_account: accounts,
+            delete_type: delete_type.tag("Delete"),
+        }
+    }
+}
+
+/// the body of `sns/friend_update`.
+#[derive(Debug, Serialize)]
+pub(crate) struct FriendUpdates<'a> {
+    #[serde(rename = "From_Account")]
+    pub(crate) from_account: &'a str,
+    #[serde(rename = "UpdateItem")]
+    pub(crate) update_item: &'a [FriendUpdate],
+}
+
+/// the body of `sns/friend_check`.
+#[derive(Debug, Serialize)]
+pub(crate) struct FriendCheck<'a> {
+    #[serde(rename = "From_Account")]
+    from_account: &'a str,
+    #[serde(rename = "To_Account")]
+    to_account: &'a [&'a str],
+    #[serde(rename = "CheckType")]
+    check_type: String,
+}
+
+impl<'a> FriendCheck<'a> {
+    pub(crate) fn new(
+        from_account: &'a str,
+        accounts: &'a [&'a str],
+        check_type: FriendType,
+    ) -> Self {
+        FriendCheck {
+            from_account,
+            to_account: accounts,
+            check_type: check_type.tag("CheckResult"),
+        }
+    }
+}
+
+/// the result of `sns/friend_add`, `friend_delete` and `friend_update`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct FriendResults {
+    #[serde(default)]
+    pub(crate) result_item: Vec<FriendResultItem>,
+}
+
+/// the result of `sns/friend_check`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct FriendCheckReply {
+    #[serde(default)]
+    pub(crate) info_item: Vec<FriendCheckItem>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AddFriendItem, FriendAdd, FriendCheck, FriendCheckReply, FriendDelete};
+    use super::{BlacklistCheck, BlacklistCheckReply, BlacklistPage};
+    use super::{BlacklistQuery, BlacklistRelation};
+    use super::{FriendPage, FriendQuery, FriendRelation, FriendType, FriendUpdate, FriendUpdates};
+    use serde_json::json;
+
+    #[test]
+    fn test_friend_bodies() {
+        let items = [AddFriendItem::new("bob", "Web").with_group_name("climbing")];
+        let body = FriendAdd::new("alice", &items, FriendType::Both, true);
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({
+                "From_Account": "alice",
+                "AddFriendItem": [{
+                    "To_Account": "bob",
+                    "GroupName": "climbing",
+                    "AddSource": "AddSource_Type_Web",
+                }],
+                "AddType": "Add_Type_Both",
+                "ForceAddFlags": 1,
+            })
+        );
+
+        let body = FriendDelete::new("alice", &["bob"], FriendType::Single);
+        assert_eq!(
+            serde_json::to_value(&body).unwrap()["DeleteType"],
+            "Delete_Type_Single"
+        );
+        let body = FriendCheck::new("alice", &["bob"], FriendType::Both);
+        assert_eq!(
+            serde_json::to_value(&body).unwrap()["CheckType"],
+            "CheckResult_Type_Both"
+        );
+
+        let items = [FriendUpdate::new("bob")
+            .with_groups(&["climbing"])
+            .with_custom("Nickname", "bb")];
+        let body = FriendUpdates {
+            from_account: "alice",
+            update_item: &items,
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({
+                "From_Account": "alice",
+                "UpdateItem": [{
+                    "To_Account": "bob",
+                    "SnsItem": [
+                        { "Tag": "Tag_SNS_IM_Group", "Value": ["climbing"] },
+                        { "Tag": "Tag_SNS_Custom_Nickname", "Value": "bb" },
+                    ],
+                }],
+            })
+        );
+
+        let reply: FriendCheckReply = serde_json::from_value(json!({
+            "InfoItem": [
+                { "To_Account": "bob", "Relation": "CheckResult_Type_AWithB", "ResultCode": 0 },
+                { "To_Account": "carol", "Relation": "CheckResult_Type_Other" },
+            ]
+        }))
+        .unwrap();
+        assert_eq!(reply.info_item[0].relation, FriendRelation::AWithB);
+        assert_eq!(reply.info_item[1].relation, FriendRelation::Unknown);
+    }
+
+    #[test]
+    fn test_friend_next_page() {
+        let query = FriendQuery::new("alice");
+        let page: FriendPage = serde_json::from_value(json!({
+            "UserDataItem": [{
+                "To_Account": "bob",
+                "ValueItem": [{ "Tag": "Tag_SNS_IM_Remark", "Value": "Bob" }],
+            }],
+            "StandardSequence": 3,
+            "CustomSequence": 1,
+            "FriendNum": 2,
+            "CompleteFlag": 0,
+            "NextStartIndex": 1,
+        }))
+        .unwrap();
+        assert_eq!(
+            page.user_data_item[0].get("Tag_SNS_IM_Remark"),
+            Some(&json!("Bob"))
+        );
+
+        let next = query.next_page(&page).unwrap();
+        assert_eq!(
+            serde_json::to_value(&next).unwrap(),
+            json!({
+                "From_Account": "alice",
+                "StartIndex": 1,
+                "StandardSequence": 3,
+                "CustomSequence": 1,
+            })
+        );
+
+        let page: FriendPage = serde_json::from_value(json!({ "CompleteFlag": 1 })).unwrap();
+        assert_eq!(next.next_page(&page), None);
+    }
+    #[test]
+    fn test_blacklist_bodies() {
+        let body = BlacklistCheck::new("alice", &["bob"], FriendType::Single);
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({
+                "From_Account": "alice",
+                "To_Account": ["bob"],
+                "CheckType": "BlackCheckResult_Type_Single",
+            })
+        );
+
+        let reply: BlacklistCheckReply = serde_json::from_value(json!({
+            "BlackListCheckItem": [
+                { "To_Account": "bob", "Relation": "BlackCheckResult_Type_NO", "ResultCode": 0 },
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            reply.black_list_check_item[0].relation,
+            BlacklistRelation::NoRelation
+        );
+    }
+
+    #[test]
+    fn test_blacklist_next_page() {
+        let query = BlacklistQuery::new("alice").with_max_limited(1);
+        let page: BlacklistPage = serde_json::from_value(json!({
+            "BlackListItem": [{ "To_Account": "bob", "AddBlackTimeStamp": 1569910200 }],
+            "StartIndex": 1,
+            "CurruentSequence": 7,
+        }))
+        .unwrap();
+        let next = query.next_page(&page).unwrap();
+        assert_eq!(
+            serde_json::to_value(&next).unwrap(),
+            json!({
+                "From_Account": "alice",
+                "StartIndex": 1,
+                "MaxLimited": 1,
+                "LastSequence": 7,
+            })
+        );
+
+        let page: BlacklistPage = serde_json::from_value(json!({
+            "BlackListItem": [{ "To_Account": "carol" }],
+            "StartIndex": 0,
+            "CurruentSequence": 7,
+        }))
+        .unwrap();
+        assert_eq!(next.next_page(&page), None);
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt;
+
+use serde::de::{Deserializer, Visitor};
+use serde::{Deserialize, Serialize};
+
+/// The operational data of the app on a day, answered by
+/// `openconfigsvr/getappinfo` for each of the last 30 days, see
+/// [the fields](https://cloud.tencent.com/document/product/269/4193).
+///
+/// The numbers are answered as strings, and are 0 if a field is not asked
+/// for.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct AppInfo {
+    /// the day, e.g. `20160607`.
+    pub date: String,
+    pub app_name: String,
+    #[serde(deserialize_with = "number")]
+    pub app_id: u64,
+    pub company: String,
+    /// the daily active accounts.
+    #[serde(deserialize_with = "number")]
+    pub active_user_num: u64,
+    #[serde(deserialize_with = "number")]
+    pub regist_user_num_one_day: u64,
+    #[serde(deserialize_with = "number")]
+    pub regist_user_num_total: u64,
+    #[serde(deserialize_with = "number")]
+    pub login_times: u64,
+    #[serde(deserialize_with = "number")]
+    pub login_user_num: u64,
+    #[serde(deserialize_with = "number")]
+    pub max_online_num: u64,
+    /// the number of friendships added.
+    #[serde(deserialize_with = "number")]
+    pub chain_increase: u64,
+    /// the number of friendships deleted.
+    #[serde(deserialize_with = "number")]
+    pub chain_decrease: u64,
+    /// the number of messages sent, one-to-one and in groups.
+    #[serde(deserialize_with = "number")]
+    pub up_msg_num: u64,
+    #[serde(deserialize_with = "number")]
+    pub send_msg_user_num: u64,
+    #[serde(rename = "APNSMsgNum", deserialize_with = "number")]
+    pub apns_msg_num: u64,
+    #[serde(rename = "C2CUpMsgNum", deserialize_with = "number")]
+    pub c2c_up_msg_num: u64,
+    #[serde(rename = "C2CSendMsgUserNum", deserialize_with = "number")]
+    pub c2c_send_msg_user_num: u64,
+    #[serde(rename = "C2CAPNSMsgNum", deserialize_with = "number")]
+    pub c2c_apns_msg_num: u64,
+    #[serde(deserialize_with = "number")]
+    pub group_up_msg_num: u64,
+    #[serde(deserialize_with = "number")]
+    pub group_send_msg_user_num: u64,
+    #[serde(rename = "GroupAPNSMsgNum", deserialize_with = "number")]
+    pub group_apns_msg_num: u64,
+    #[serde(deserialize_with = "number")]
+    pub group_send_msg_group_num: u64,
+    #[serde(deserialize_with = "number")]
+    pub group_join_group_times: u64,
+    #[serde(deserialize_with = "number")]
+    pub group_quit_group_times: u64,
+    #[serde(deserialize_with = "number")]
+    pub group_new_group_num: u64,
+    #[serde(deserialize_with = "number")]
+    pub group_all_group_num: u64,
+    #[serde(deserialize_with = "number")]
+    pub group_destroy_group_num: u64,
+    /// the number of callbacks requested to the app server.
+    #[serde(rename = "CallBackReq", deserialize_with = "number")]
+    pub callback_req: u64,
+    /// the number of callbacks answered by the app server.
+    #[serde(rename = "CallBackRsp", deserialize_with = "number")]
+    pub callback_rsp: u64,
+}
+
+/// a number answered as a string, or as a number.
+fn number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    struct NumberVisitor;
+
+    impl<'de> Visitor<'de> for NumberVisitor {
+        type Value = u64;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a number or a string of it")
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<u64, E> {
+            Ok(v)
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<u64, E> {
+            if v.is_empty() {
+                return Ok(0);
+            }
+            v.parse()
+                .map_err(|_| E::custom(format!("invalid number {}", v)))
+        }
+    }
+
+    deserializer.deserialize_any(NumberVisitor)
+}
+
+/// the body of `openconfigsvr/getappinfo`, asking for all the fields if
+/// `request_field` is empty.
+#[derive(Debug, Serialize)]
+pub(crate) struct GetAppInfo<'a> {
+    #[serde(rename = "RequestField", skip_serializing_if = "<[_]>::is_empty")]
+    pub(crate) request_field: &'a [&'a str],
+}
+
+/// the result of `openconfigsvr/getappinfo`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct AppInfos {
+    #[serde(rename = "Result", default)]
+    pub(crate) result: Vec<AppInfo>,
+}
+
+/// Which messages are downloaded by `open_msg_svc/get_history`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ChatType {
+    /// the one-to-one messages.
+    C2C,
+    /// the group messages.
+    Group,
+}
+
+/// A file of the messages sent within an hour, answered by
+/// `open_msg_svc/get_history`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct HistoryFile {
+    /// where the gzipped file is downloaded from.
+    #[serde(rename = "URL")]
+    pub url: String,
+    /// when the url expires, e.g. `2015-12-02 14:40:33`.
+    #[serde(rename = "ExpireTime")]
+    pub expire_time: String,
+    /// the size of the file unzipped.
+    #[serde(rename = "FileSize")]
+    pub file_size: u64,
+    #[serde(rename = "FileMD5")]
+    pub file_md5: String,
+    #[serde(rename = "GZipSize")]
+    pub gzip_size: u64,
+    #[serde(rename = "GZipMD5")]
+    pub gzip_md5: String,
+}
+
+/// the body of `open_msg_svc/get_history`.
+#[derive(Debug, Serialize)]
+pub(crate) struct GetHistory<'a> {
+    #[serde(rename = "ChatType")]
+    pub(crate) chat_type: ChatType,
+    /// the hour, e.g. `2015120121`.
+    #[serde(rename = "MsgTime")]
+    pub(crate) msg_time: &'a str,
+}
+
+/// the result of `open_msg_svc/get_history`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct HistoryFiles {
+    #[serde(rename = "File", default)]
+    pub(crate) file: Vec<HistoryFile>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AppInfos, ChatType, GetAppInfo, GetHistory, HistoryFiles};
+    use serde_json::json;
+
+    #[test]
+    fn test_get_app_info_body() {
+        let body = GetAppInfo { request_field: &[] };
+        assert_eq!(serde_json::to_value(&body).unwrap(), json!({}));
+
+        let body = GetAppInfo {
+            request_field: &["ActiveUserNum", "CallBackReq"],
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "RequestField": ["ActiveUserNum", "CallBackReq"] })
+        );
+    }
+
+    #[test]
+    fn test_app_infos() {
+        let infos: AppInfos = serde_json::from_value(json!({
+            "Result": [
+                {
+                    "Date": "20160607",
+                    "AppId": "1400000000",
+                    "ActiveUserNum": "120",
+                    "C2CUpMsgNum": "35",
+                    "APNSMsgNum": "",
+                    "CallBackReq": 7,
+                    "CallBackRsp": "7",
+                },
+                { "Date": "20160606" },
+            ]
+        }))
+        .unwrap();
+        let info = &infos.result[0];
+        assert_eq!(info.date, "20160607");
+        assert_eq!(info.app_id, 1400000000);
+        assert_eq!(info.active_user_num, 120);
+        assert_eq!(info.c2c_up_msg_num, 35);
+        assert_eq!(info.apns_msg_num, 0);
+        assert_eq!((info.callback_req, info.callback_rsp), (7, 7));
+        assert_eq!(infos.result[1].active_user_num, 0);
+
+        let failed = serde_json::from_value::<AppInfos>(json!({
+            "Result": [{ "ActiveUserNum": "many" }]
+        }));
+        assert!(failed.is_err());
+    }
+
+    #[test]
+    fn test_get_history() {
+        let body = GetHistory {
+            chat_type: ChatType::C2C,
+            msg_time: "2015120121",
+        };
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "ChatType": "C2C", "MsgTime": "2015120121" })
+        );
+
+        let files: HistoryFiles = serde_json::from_value(json!({
+            "File": [{
+                "URL": "https://download.tim.qq.com/msg/1400000000/2015120121.gz",
+                "ExpireTime": "2015-12-02 14:40:33",
+                "FileSize": 4127,
+                "FileMD5": "4a8b8d5b7c5d5f8e",
+                "GZipSize": 1024,
+                "GZipMD5": "7d7d6c8f1e8a2b3c",
+            }]
+        }))
+        .unwrap();
+        assert_eq!(files.file[0].file_size, 4127);
+        assert_eq!(files.file[0].gzip_size, 1024);
+    }
+}
@@ -0,0 +1,178 @@
+#[cfg(feature = "rest")]
+use std::future::Future;
+#[cfg(feature = "rest")]
+use std::pin::Pin;
+
+use reqwest::Url;
+
+use crate::{Result, TlsSigError};
+
+/// A signed request of a REST client, to be posted by a transport.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// the url, including the admin sig in its query.
+    pub url: Url,
+    pub headers: Vec<(String, String)>,
+    /// the JSON body.
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// the url with the admin sig masked, to be logged.
+    pub fn redacted_url(&self) -> String {
+        let mut url = self.url.clone();
+        let pairs: Vec<(String, String)> = self
+            .url
+            .query_pairs()
+            .map(|(name, value)| match &*name {
+                "usersig" => (name.to_string(), "***".to_string()),
+                _ => (name.to_string(), value.to_string()),
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+        url.to_string()
+    }
+}
+
+/// The response to an [`HttpRequest`], of any status.
+///
+/// [`HttpRequest`]: struct.HttpRequest.html
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// The future returned by [`HttpTransport::post`].
+///
+/// [`HttpTransport::post`]: trait.HttpTransport.html#tymethod.post
+#[cfg(feature = "rest")]
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<HttpResponse>> + Send + 'a>>;
+
+/// Posts the requests of a [`TimClient`].
+///
+/// It is implemented for `reqwest::Client`, which is used by default.
+/// Implement it to use another HTTP client, e.g. a pooled or instrumented
+/// one, or to answer requests without network access in tests.
+///
+/// [`TimClient`]: struct.TimClient.html
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{HttpRequest, HttpResponse, HttpTransport, TimClient, TransportFuture};
+///
+/// #[derive(Debug)]
+/// struct AlwaysOk;
+///
+/// impl HttpTransport for AlwaysOk {
+///     fn post(&self, _: HttpRequest) -> TransportFuture<'_> {
+///         Box::pin(async {
+///             Ok(HttpResponse {
+///                 status: 200,
+///                 body: r#"{"ActionStatus":"OK"}"#.to_string(),
+///             })
+///         })
+///     }
+/// }
+///
+/// # use tls_sig_api::TlsSigApiVer2;
+/// # let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let client = TimClient::new(TlsSigApiVer2::new(1400000000, mock_key), "administrator")
+///     .with_transport(AlwaysOk);
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// runtime.block_on(async { client.kick("10086").await.unwrap() });
+/// ```
+#[cfg(feature = "rest")]
+pub trait HttpTransport: std::fmt::Debug + Send + Sync {
+    /// post `request`, failing with [`RestUnavailable`] if no response is
+    /// received.
+    ///
+    /// [`RestUnavailable`]: enum.TlsSigError.html#variant.RestUnavailable
+    fn post(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+/// Posts the requests of a [`TimClientBlocking`], like [`HttpTransport`]
+/// does for the async client.
+///
+/// It is implemented for `reqwest::blocking::Client`, which is used by
+/// default.
+///
+/// [`TimClientBlocking`]: struct.TimClientBlocking.html
+/// [`HttpTransport`]: trait.HttpTransport.html
+#[cfg(feature = "rest-blocking")]
+pub trait BlockingHttpTransport: std::fmt::Debug + Send + Sync {
+    /// post `request`, failing with [`RestUnavailable`] if no response is
+    /// received.
+    ///
+    /// [`RestUnavailable`]: enum.TlsSigError.html#variant.RestUnavailable
+    fn post(&self, request: HttpRequest) -> Result<HttpResponse>;
+}
+
+#[cfg(feature = "rest")]
+impl HttpTransport for reqwest::Client {
+    fn post(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let path = request.url.path().to_string();
+            let mut builder = reqwest::Client::post(self, request.url);
+            for (name, value) in &request.headers {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+
+            let resp = builder
+                .body(request.body)
+                .send()
+                .await
+                .map_err(|e| unreachable(&path, e))?;
+            let status = resp.status().as_u16();
+            let body = resp.text().await.map_err(|e| unreachable(&path, e))?;
+            Ok(HttpResponse { status, body })
+        })
+    }
+}
+
+#[cfg(feature = "rest-blocking")]
+impl BlockingHttpTransport for reqwest::blocking::Client {
+    fn post(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let path = request.url.path().to_string();
+        let mut builder = reqwest::blocking::Client::post(self, request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        let resp = builder
+            .body(request.body)
+            .send()
+            .map_err(|e| unreachable(&path, e))?;
+        let status = resp.status().as_u16();
+        let body = resp.text().map_err(|e| unreachable(&path, e))?;
+        Ok(HttpResponse { status, body })
+    }
+}
+
+/// the error of failing to post to `path`, which unlike the url does not
+/// include the admin sig.
+fn unreachable(path: &str, e: reqwest::Error) -> TlsSigError {
+    TlsSigError::RestUnavailable(format!("failed to post {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::HttpRequest;
+
+    #[test]
+    fn test_redacted_url() {
+        let request = HttpRequest {
+            url: "https://console.tim.qq.com/v4/a/b?sdkappid=1&usersig=secret&random=2"
+                .parse()
+                .unwrap(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        };
+        assert_eq!(
+            request.redacted_url(),
+            "https://console.tim.qq.com/v4/a/b?sdkappid=1&usersig=***&random=2"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::TlsSigApiVer2;
+#[cfg(feature = "async")]
+use crate::{ExpireDuration, Result, SigInfo, TlsSigError, UserSig};
+
+/// A cheaply cloneable handle to a signer, to be kept in the state of web
+/// frameworks like axum or actix-web and cloned per request.
+///
+/// All clones share the same signer, so a key rotated through any of them,
+/// see [`update_key`], is used by all of them right away. It derefs to the
+/// signer for signing and verifying.
+///
+/// [`update_key`]: struct.TlsSigApiVer2.html#method.update_key
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{SharedSigner, TlsSigApiVer2};
+/// use std::time::Duration;
+///
+/// let signer = SharedSigner::new(TlsSigApiVer2::new(0, "leaked key"));
+/// let handle = signer.clone();
+///
+/// signer.update_key("5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e");
+///
+/// let sig = handle.gen_sign("10086", Duration::from_secs(7200), None);
+/// assert!(signer.verify_sign("10086", &sig).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedSigner(Arc<TlsSigApiVer2>);
+
+impl SharedSigner {
+    pub fn new(signer: TlsSigApiVer2) -> Self {
+        SharedSigner(Arc::new(signer))
+    }
+
+    /// generate user sign like [`TlsSigApiVer2::try_gen_sign`] on the
+    /// blocking thread pool of tokio, so that async handlers do not block the
+    /// reactor on the compression and HMAC under heavy load.
+    ///
+    /// Requires the `async` feature and a running tokio runtime.
+    ///
+    /// [`TlsSigApiVer2::try_gen_sign`]: struct.TlsSigApiVer2.html#method.try_gen_sign
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tls_sig_api::{SharedSigner, TlsSigApiVer2};
+    /// use std::time::Duration;
+    ///
+    /// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    /// let signer = SharedSigner::new(TlsSigApiVer2::new(0, mock_key));
+    ///
+    /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// runtime.block_on(async {
+    ///     let sig = signer
+    ///         .gen_sign_async("10086", Duration::from_secs(7200), None)
+    ///         .await
+    ///         .unwrap();
+    ///     assert!(signer.verify_sign("10086", &sig).is_ok());
+    /// });
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn gen_sign_async<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<UserSig> {
+        self.sign_info_blocking(
+            identifier.to_string(),
+            expire.expire_secs(),
+            userbuf.map(str::to_string),
+        )
+        .await
+        .map(|info| info.sig)
+    }
+
+    /// sign on the blocking thread pool of tokio.
+    #[cfg(feature = "async")]
+    pub(crate) async fn sign_info_blocking(
+        &self,
+        identifier: String,
+        expire: i64,
+        userbuf: Option<String>,
+    ) -> Result<SigInfo> {
+        let signer = Arc::clone(&self.0);
+        let task = tokio::task::spawn_blocking(move || {
+            signer.sign_info(
+                &identifier,
+                signer.curr_timestamp(),
+                expire,
+                userbuf.as_deref().map(str::as_bytes),
+            )
+        });
+        match task.await {
+            Ok(sig) => sig,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => Err(TlsSigError::MacUnavailable(format!(
+                "signing task cancelled: {}",
+                e
+            ))),
+        }
+    }
+}
+
+impl Deref for SharedSigner {
+    type Target = TlsSigApiVer2;
+
+    fn deref(&self) -> &TlsSigApiVer2 {
+        &self.0
+    }
+}
+
+impl AsRef<TlsSigApiVer2> for SharedSigner {
+    fn as_ref(&self) -> &TlsSigApiVer2 {
+        &self.0
+    }
+}
+
+impl From<TlsSigApiVer2> for SharedSigner {
+    fn from(signer: TlsSigApiVer2) -> Self {
+        SharedSigner::new(signer)
+    }
+}
+
+impl From<Arc<TlsSigApiVer2>> for SharedSigner {
+    fn from(signer: Arc<TlsSigApiVer2>) -> Self {
+        SharedSigner(signer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SharedSigner;
+    use crate::TlsSigApiVer2;
+    use std::time::Duration;
+
+    const MOCK_KEY: &str = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+
+    #[test]
+    fn test_shared_signer_is_send_sync() {
+        fn assert_send_sync_clone<T: Send + Sync + Clone + 'static>() {}
+        assert_send_sync_clone::<SharedSigner>();
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_gen_sign_async() {
+        let signer = SharedSigner::new(TlsSigApiVer2::new(1400000000, MOCK_KEY));
+
+        let sig = signer
+            .gen_sign_async("10086", Duration::from_secs(7200), Some("abc"))
+            .await
+            .unwrap();
+        assert!(signer.verify_sign("10086", &sig).is_ok());
+        assert!(signer
+            .gen_sign_async("10086", Duration::from_secs(0), None)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_rotation_propagates_to_clones() {
+        let signer: SharedSigner = TlsSigApiVer2::new(1400000000, "old key").into();
+        let clones: Vec<SharedSigner> = (0..4).map(|_| signer.clone()).collect();
+
+        std::thread::spawn({
+            let signer = signer.clone();
+            move || signer.update_key(MOCK_KEY)
+        })
+        .join()
+        .unwrap();
+
+        let sig =
+            TlsSigApiVer2::new(1400000000, MOCK_KEY).gen_sign("0", Duration::from_secs(7200), None);
+        for clone in clones {
+            assert!(clone.verify_sign("0", &sig).is_ok());
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, SystemTime};
+
+use log::*;
+use tokio::task::JoinHandle;
+
+use crate::{ExpireDuration, Result, SharedSigner, SigInfo, UserSig};
+
+/// the current sig of each identifier.
+type SigMap = RwLock<HashMap<String, SigInfo>>;
+
+/// how long to wait before retrying a failed renewal.
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Keeps the sigs of a set of identifiers fresh in a tokio task, e.g. admin
+/// sigs used to call the REST API, or sigs pushed to clients before they
+/// expire.
+///
+/// Each sig is renewed [`with_renew_before`] it expires, a fifth of the
+/// expire by default, minus a random jitter up to [`with_jitter`], so that
+/// sigs issued together are not all renewed at once. Failed renewals are
+/// logged and retried, keeping the previous sig meanwhile.
+///
+/// Requires the `async` feature.
+///
+/// [`with_renew_before`]: #method.with_renew_before
+/// [`with_jitter`]: #method.with_jitter
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{SigRefresher, TlsSigApiVer2};
+/// use std::time::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = TlsSigApiVer2::new(1400000000, mock_key);
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread()
+///     .enable_time()
+///     .build()
+///     .unwrap();
+/// runtime.block_on(async {
+///     let sigs = SigRefresher::new(signer, Duration::from_secs(24 * 3600))
+///         .with_jitter(Duration::from_secs(600))
+///         .spawn(vec!["administrator", "bot"])
+///         .await
+///         .unwrap();
+///
+///     let admin_sig = sigs.get("administrator").unwrap();
+///     assert!(sigs.signer().verify_sign("administrator", &admin_sig).is_ok());
+/// });
+/// ```
+#[derive(Debug)]
+pub struct SigRefresher {
+    signer: SharedSigner,
+    expire: i64,
+    renew_before: Duration,
+    jitter: Duration,
+}
+
+impl SigRefresher {
+    /// keep sigs of `expire` from `signer` fresh.
+    pub fn new<S: Into<SharedSigner>, E: ExpireDuration>(signer: S, expire: E) -> Self {
+        let expire = expire.expire_secs();
+        let secs = expire.max(0) as u64;
+
+        SigRefresher {
+            signer: signer.into(),
+            expire,
+            renew_before: Duration::from_secs(secs / 5),
+            jitter: Duration::from_secs(secs / 50),
+        }
+    }
+
+    /// renew sigs once at most `before` is left until they expire.
+    pub fn with_renew_before(mut self, before: Duration) -> Self {
+        self.renew_before = before;
+        self
+    }
+
+    /// renew each sig up to `jitter` earlier, at random, a fiftieth of the
+    /// expire by default.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// sign all `identifiers`, then spawn the task keeping them fresh until
+    /// the returned sigs are dropped. Fails if any identifier can not be
+    /// signed at first.
+    ///
+    /// It must be called within a tokio runtime with time enabled.
+    pub async fn spawn<I, S>(self, identifiers: I) -> Result<RefreshedSigs>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut sigs = HashMap::new();
+        for identifier in identifiers {
+            let identifier = identifier.into();
+            let info = self
+                .signer
+                .sign_info_blocking(identifier.clone(), self.expire, None)
+                .await?;
+            sigs.insert(identifier, info);
+        }
+
+        let signer = self.signer.clone();
+        let sigs = Arc::new(RwLock::new(sigs));
+        let task = tokio::spawn(self.run(Arc::clone(&sigs)));
+
+        Ok(RefreshedSigs { signer, sigs, task })
+    }
+
+    async fn run(self, sigs: Arc<SigMap>) {
+        let jitter_seed = RandomState::new();
+        let mut schedule: HashMap<String, SystemTime> = read(&sigs)
+            .iter()
+            .map(|(identifier, info)| {
+                let at = self.renew_at(&jitter_seed, identifier, info);
+                (identifier.clone(), at)
+            })
+            .collect();
+
+        loop {
+            let now = self.signer.clock.now();
+            let due: Vec<String> = schedule
+                .iter()
+                .filter(|(_, at)| **at <= now)
+                .map(|(identifier, _)| identifier.clone())
+                .collect();
+
+            for identifier in due {
+                let renewed = self
+                    .signer
+                    .sign_info_blocking(identifier.clone(), self.expire, None)
+                    .await;
+                let at = match renewed {
+                    Ok(info) => {
+                        debug!("renewed the sig of {}", identifier);
+                        let at = self.renew_at(&jitter_seed, &identifier, &info);
+                        write(&sigs).insert(identifier.clone(), info);
+                        at
+                    }
+                    Err(e) => {
+                        warn!("failed to renew the sig of {}: {}", identifier, e);
+                        now + RETRY_INTERVAL
+                    }
+                };
+                schedule.insert(identifier, at);
+            }
+
+            let next = match schedule.values().min() {
+                Some(next) => *next,
+                None => return,
+            };
+            // at least a second apart, as sigs are issued in seconds
+            let wait = next
+                .duration_since(self.signer.clock.now())
+                .unwrap_or_default()
+                .max(Duration::from_secs(1));
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// when to renew the sig `info` of `identifier`.
+    fn renew_at(&self, jitter_seed: &RandomState, identifier: &str, info: &SigInfo) -> SystemTime {
+        let jitter = match self.jitter.as_millis() as u64 {
+            0 => Duration::from_secs(0),
+            millis => {
+                Duration::from_millis(jitter_seed.hash_one((identifier, info.issued_at)) % millis)
+            }
+        };
+
+        info.expires_at
+            .checked_sub(self.renew_before + jitter)
+            .map_or(info.issued_at, |at| at.max(info.issued_at))
+    }
+}
+
+/// The sigs kept fresh by a [`SigRefresher`], until dropped.
+///
+/// [`SigRefresher`]: struct.SigRefresher.html
+#[derive(Debug)]
+pub struct RefreshedSigs {
+    signer: SharedSigner,
+    sigs: Arc<SigMap>,
+    task: JoinHandle<()>,
+}
+
+impl RefreshedSigs {
+    /// the current sig of `identifier`, if it is one of those refreshed.
+    pub fn get(&self, identifier: &str) -> Option<UserSig> {
+        self.get_info(identifier).map(|info| info.sig)
+    }
+
+    /// the current sig of `identifier` with its issuance metadata.
+    pub fn get_info(&self, identifier: &str) -> Option<SigInfo> {
+        read(&self.sigs).get(identifier).cloned()
+    }
+
+    /// the signer, e.g. for verifying.
+    pub fn signer(&self) -> &SharedSigner {
+        &self.signer
+    }
+}
+
+impl Drop for RefreshedSigs {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn read(sigs: &SigMap) -> RwLockReadGuard<'_, HashMap<String, SigInfo>> {
+    sigs.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write(sigs: &SigMap) -> RwLockWriteGuard<'_, HashMap<String, SigInfo>> {
+    sigs.write().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+    use super::SigRefresher;
+    use crate::{TlsSigApiVer2, TlsSigError};
+    use std::time::Duration;
+
+    const MOCK_KEY: &str = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+
+    #[tokio::test]
+    async fn test_sig_refresher() {
+        let signer = TlsSigApiVer2::new(1400000000, MOCK_KEY);
+        let sigs = SigRefresher::new(signer, Duration::from_secs(3))
+            .with_renew_before(Duration::from_secs(2))
+            .with_jitter(Duration::from_millis(100))
+            .spawn(vec!["admin"])
+            .await
+            .unwrap();
+
+        let first = sigs.get_info("admin").unwrap();
+        assert!(sigs.get("other").is_none());
+
+        // renewed a second after issued
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+        let renewed = sigs.get_info("admin").unwrap();
+        assert!(renewed.issued_at > first.issued_at);
+        assert!(sigs.signer().verify_sign("admin", &renewed.sig).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_sig_refresher_invalid_identifier() {
+        let signer = TlsSigApiVer2::new(1400000000, MOCK_KEY);
+        let spawned = SigRefresher::new(signer, Duration::from_secs(3))
+            .spawn(vec![""])
+            .await;
+        assert!(matches!(spawned, Err(TlsSigError::InvalidIdentifier(_))));
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use log::*;
+use sha2::{Digest, Sha256};
+
+use crate::cache::fresh_until;
+use crate::{ExpireDuration, Result, TlsSigApiVer2, TlsSigError, UserSig};
+
+#[cfg(feature = "redis")]
+mod redis;
+
+#[cfg(feature = "redis")]
+pub use self::redis::RedisSigStore;
+
+/// Where a [`StoredSigner`] keeps the sigs it issues, so that a fleet of
+/// stateless servers can share them instead of each minting its own.
+///
+/// Stores only have to keep strings until their TTL runs out. What they
+/// return is verified before it is reused, so a store does not have to be
+/// trusted to hand out valid sigs.
+///
+/// [`StoredSigner`]: struct.StoredSigner.html
+pub trait SigStore: fmt::Debug + Send + Sync {
+    /// the sig stored under `key`, if any and its TTL has not run out.
+    fn load(&self, key: &str) -> Result<Option<String>>;
+
+    /// store `sig` under `key` for `ttl`, replacing any previous one.
+    fn store(&self, key: &str, sig: &str, ttl: Duration) -> Result<()>;
+
+    /// remove the sig under `key`, if any.
+    fn remove(&self, key: &str) -> Result<()>;
+}
+
+/// A sig store in process memory, for tests and single servers.
+#[derive(Debug, Default)]
+pub struct MemorySigStore {
+    sigs: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl MemorySigStore {
+    pub fn new() -> Self {
+        MemorySigStore::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, (String, Instant)>> {
+        self.sigs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl SigStore for MemorySigStore {
+    fn load(&self, key: &str) -> Result<Option<String>> {
+        let mut sigs = self.lock();
+        match sigs.get(key) {
+            Some((sig, until)) if *until > Instant::now() => Ok(Some(sig.clone())),
+            Some(_) => {
+                sigs.remove(key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn store(&self, key: &str, sig: &str, ttl: Duration) -> Result<()> {
+        let until = Instant::now() + ttl;
+        self.lock()
+            .insert(key.to_string(), (sig.to_string(), until));
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        self.lock().remove(key);
+        Ok(())
+    }
+}
+
+/// A signer sharing the sigs it issues through a [`SigStore`], e.g. the admin
+/// sigs of a fleet of API servers.
+///
+/// A sig is reused while at least 20% of its lifetime remains by default, see
+/// [`with_min_remaining`], and stored with a TTL running out then. Sigs are
+/// verified after loaded, and the store failing is logged and signed around,
+/// so a store outage never stops issuance. [`revoke`] removes a sig from the
+/// store so that the next call mints a new one, it stays valid until it
+/// expires though.
+///
+/// [`SigStore`]: trait.SigStore.html
+/// [`with_min_remaining`]: #method.with_min_remaining
+/// [`revoke`]: #method.revoke
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{MemorySigStore, StoredSigner, TlsSigApiVer2};
+/// use std::time::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = StoredSigner::new(TlsSigApiVer2::new(0, mock_key), MemorySigStore::new());
+///
+/// let expire = Duration::from_secs(7200);
+/// let sig = signer.try_gen_sign("administrator", expire, None).unwrap();
+/// assert_eq!(signer.try_gen_sign("administrator", expire, None).unwrap(), sig);
+///
+/// // signed anew next time
+/// signer.revoke("administrator", expire, None).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct StoredSigner {
+    signer: TlsSigApiVer2,
+    store: Box<dyn SigStore>,
+    prefix: String,
+    min_remaining: f64,
+}
+
+impl StoredSigner {
+    pub fn new<S: SigStore + 'static>(signer: TlsSigApiVer2, store: S) -> Self {
+        StoredSigner {
+            signer,
+            store: Box::new(store),
+            prefix: "tls-sig".to_string(),
+            min_remaining: 0.2,
+        }
+    }
+
+    /// prefix the keys of the store with `prefix`, `tls-sig` by default.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// reuse sigs only while at least `fraction` of their lifetime remains,
+    /// 0.2 by default. It is clamped between 0 and 1.
+    pub fn with_min_remaining(mut self, fraction: f64) -> Self {
+        self.min_remaining = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// the wrapped signer, e.g. for verifying. Sigs issued through it
+    /// directly are not stored.
+    pub fn signer(&self) -> &TlsSigApiVer2 {
+        &self.signer
+    }
+
+    /// generate user sign like [`TlsSigApiVer2::try_gen_sign`], or return the
+    /// one in the store if it is still fresh.
+    ///
+    /// [`TlsSigApiVer2::try_gen_sign`]: struct.TlsSigApiVer2.html#method.try_gen_sign
+    pub fn try_gen_sign<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<UserSig> {
+        let expire = expire.expire_secs();
+        let key = self.key(identifier, expire, userbuf);
+
+        match self.store.load(&key) {
+            Ok(Some(sig)) => match self.check(identifier, &sig, userbuf) {
+                Ok(sig) => return Ok(sig),
+                Err(e) => warn!("ignored the stored sig of {}: {}", identifier, e),
+            },
+            Ok(None) => {}
+            Err(e) => warn!("failed to load the sig of {}: {}", identifier, e),
+        }
+
+        let info = self.signer.sign_info(
+            identifier,
+            self.signer.curr_timestamp(),
+            expire,
+            userbuf.map(str::as_bytes),
+        )?;
+        let ttl = fresh_until(&info, self.min_remaining)
+            .duration_since(self.signer.clock.now())
+            .unwrap_or_default();
+        if !ttl.is_zero() {
+            if let Err(e) = self.store.store(&key, &info.sig, ttl) {
+                warn!("failed to store the sig of {}: {}", identifier, e);
+            }
+        }
+
+        Ok(info.sig)
+    }
+
+    /// remove the sig of `identifier` from the store, so that servers sign a
+    /// new one next time.
+    pub fn revoke<E: ExpireDuration>(
+        &self,
+        identifier: &str,
+        expire: E,
+        userbuf: Option<&str>,
+    ) -> Result<()> {
+        let key = self.key(identifier, expire.expire_secs(), userbuf);
+        self.store.remove(&key)
+    }
+
+    /// `{prefix}:{sdkappid}:{expire}:{sha256 of userbuf or -}:{identifier}`,
+    /// the identifier last as it may contain colons.
+    fn key(&self, identifier: &str, expire: i64, userbuf: Option<&str>) -> String {
+        let userbuf = match userbuf {
+            Some(buf) => Sha256::digest(buf.as_bytes())
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{}:{}:{}:{}:{}",
+            self.prefix, self.signer.sdkappid, expire, userbuf, identifier
+        )
+    }
+
+    /// the stored `sig` if it is valid and fresh for `identifier` and
+    /// `userbuf`.
+    fn check(&self, identifier: &str, sig: &str, userbuf: Option<&str>) -> Result<UserSig> {
+        self.signer.verify_sign(identifier, sig)?;
+        let sig: UserSig = sig.parse()?;
+        let payload = sig.payload()?;
+
+        if payload.userbuf_bytes()?.as_deref() != userbuf.map(str::as_bytes) {
+            return Err(TlsSigError::MalformedSig("userbuf mismatch".to_string()));
+        }
+        let now: SystemTime = self.signer.clock.now();
+        let lifetime = payload
+            .expires_at()
+            .duration_since(payload.issued_at())
+            .unwrap_or_default();
+        if payload.remaining_validity(now) < lifetime.mul_f64(self.min_remaining) {
+            return Err(TlsSigError::Expired);
+        }
+
+        Ok(sig)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MemorySigStore, SigStore, StoredSigner};
+    use crate::clock::ManualClock;
+    use crate::{Result, TlsSigApiVer2, TlsSigError};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const MOCK_KEY: &str = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    const EXPIRE: Duration = Duration::from_secs(1000);
+
+    /// a store shared by several signers, ignoring TTLs.
+    #[derive(Debug, Default, Clone)]
+    struct SharedStore(Arc<std::sync::Mutex<Vec<(String, String, Duration)>>>);
+
+    impl SigStore for SharedStore {
+        fn load(&self, key: &str) -> Result<Option<String>> {
+            let sigs = self.0.lock().unwrap();
+            Ok(sigs
+                .iter()
+                .rev()
+                .find(|(k, _, _)| k == key)
+                .map(|(_, sig, _)| sig.clone()))
+        }
+
+        fn store(&self, key: &str, sig: &str, ttl: Duration) -> Result<()> {
+            let mut sigs = self.0.lock().unwrap();
+            sigs.push((key.to_string(), sig.to_string(), ttl));
+            Ok(())
+        }
+
+        fn remove(&self, key: &str) -> Result<()> {
+            self.0.lock().unwrap().retain(|(k, _, _)| k != key);
+            Ok(())
+        }
+    }
+
+    /// a store that is always down.
+    #[derive(Debug)]
+    struct DownStore;
+
+    impl SigStore for DownStore {
+        fn load(&self, _: &str) -> Result<Option<String>> {
+            Err(TlsSigError::StoreUnavailable("down".to_string()))
+        }
+
+        fn store(&self, _: &str, _: &str, _: Duration) -> Result<()> {
+            Err(TlsSigError::StoreUnavailable("down".to_string()))
+        }
+
+        fn remove(&self, _: &str) -> Result<()> {
+            Err(TlsSigError::StoreUnavailable("down".to_string()))
+        }
+    }
+
+    fn signer(clock: &ManualClock) -> TlsSigApiVer2 {
+        TlsSigApiVer2::new(1400000000, MOCK_KEY).with_clock(clock.clone())
+    }
+
+    #[test]
+    fn test_stored_signer_shares_sigs() {
+        let clock = ManualClock(Arc::new(AtomicU64::new(1569910200)));
+        let store = SharedStore::default();
+        let pod1 = StoredSigner::new(signer(&clock), store.clone());
+        let pod2 = StoredSigner::new(signer(&clock), store.clone());
+
+        let sig = pod1.try_gen_sign("admin", EXPIRE, Some("abc")).unwrap();
+        assert_eq!(
+            pod2.try_gen_sign("admin", EXPIRE, Some("abc")).unwrap(),
+            sig
+        );
+        assert_ne!(pod2.try_gen_sign("admin", EXPIRE, None).unwrap(), sig);
+        {
+            let sigs = store.0.lock().unwrap();
+            assert_eq!(sigs.len(), 2);
+            assert_eq!(sigs[0].2, Duration::from_secs(800));
+            assert!(sigs[0].0.starts_with("tls-sig:1400000000:1000:ba7816bf"));
+            assert_eq!(sigs[1].0, "tls-sig:1400000000:1000:-:admin");
+        }
+
+        // stale sigs are not reused even if the store keeps them
+        clock.0.fetch_add(801, Ordering::SeqCst);
+        let renewed = pod2.try_gen_sign("admin", EXPIRE, Some("abc")).unwrap();
+        assert_ne!(renewed, sig);
+        assert_eq!(
+            pod1.try_gen_sign("admin", EXPIRE, Some("abc")).unwrap(),
+            renewed
+        );
+
+        pod1.revoke("admin", EXPIRE, Some("abc")).unwrap();
+        clock.0.fetch_add(1, Ordering::SeqCst);
+        assert_ne!(
+            pod2.try_gen_sign("admin", EXPIRE, Some("abc")).unwrap(),
+            renewed
+        );
+    }
+
+    #[test]
+    fn test_stored_signer_ignores_forged_sigs() {
+        let clock = ManualClock(Arc::new(AtomicU64::new(1569910200)));
+        let store = SharedStore::default();
+        let other =
+            TlsSigApiVer2::new(1400000000, &MOCK_KEY.replace('5', "6")).with_clock(clock.clone());
+        let forged = other.try_gen_sign("admin", EXPIRE, None).unwrap();
+        store
+            .store("tls-sig:1400000000:1000:-:admin", &forged, EXPIRE)
+            .unwrap();
+
+        let signer = StoredSigner::new(signer(&clock), store);
+        let sig = signer.try_gen_sign("admin", EXPIRE, None).unwrap();
+        assert_ne!(sig, forged);
+        assert!(signer.signer().verify_sign("admin", &sig).is_ok());
+    }
+
+    #[test]
+    fn test_stored_signer_store_down() {
+        let clock = ManualClock(Arc::new(AtomicU64::new(1569910200)));
+        let signer = StoredSigner::new(signer(&clock), DownStore);
+        let sig = signer.try_gen_sign("admin", EXPIRE, None).unwrap();
+        assert!(signer.signer().verify_sign("admin", &sig).is_ok());
+        assert!(matches!(
+            signer.revoke("admin", EXPIRE, None),
+            Err(TlsSigError::StoreUnavailable(_))
+        ));
+    }
+
+    #[test]
+    fn test_memory_sig_store() {
+        let store = MemorySigStore::new();
+        store.store("a", "sig", Duration::from_secs(60)).unwrap();
+        store.store("b", "sig", Duration::from_secs(0)).unwrap();
+        assert_eq!(store.load("a").unwrap().as_deref(), Some("sig"));
+        assert_eq!(store.load("b").unwrap(), None);
+
+        store.remove("a").unwrap();
+        assert_eq!(store.load("a").unwrap(), None);
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use redis::{Client, Commands, Connection, RedisError};
+
+use crate::{Result, SigStore, TlsSigError};
+
+/// A sig store in Redis, shared by every server connected to it.
+///
+/// One connection is kept and reopened after failures. Sigs are stored with
+/// `SET EX`, so Redis drops them once their TTL runs out.
+///
+/// Requires the `redis` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tls_sig_api::{RedisSigStore, StoredSigner, TlsSigApiVer2};
+/// use std::time::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let store = RedisSigStore::new("redis://127.0.0.1/").unwrap();
+/// let signer = StoredSigner::new(TlsSigApiVer2::new(0, mock_key), store);
+///
+/// let sig = signer.try_gen_sign("administrator", Duration::from_secs(7200), None);
+/// ```
+pub struct RedisSigStore {
+    client: Client,
+    timeout: Duration,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl RedisSigStore {
+    /// connect lazily to the Redis server at `url`, e.g. `redis://127.0.0.1/`.
+    pub fn new(url: &str) -> Result<Self> {
+        let client = Client::open(url).map_err(unavailable)?;
+        Ok(RedisSigStore::from_client(client))
+    }
+
+    pub fn from_client(client: Client) -> Self {
+        RedisSigStore {
+            client,
+            timeout: Duration::from_secs(1),
+            connection: Mutex::new(None),
+        }
+    }
+
+    /// timeout of connecting and of each command, a second by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// run `command` on the connection, dropping it if the command fails.
+    fn with_connection<T, F>(&self, command: F) -> Result<T>
+    where
+        F: FnOnce(&mut Connection) -> redis::RedisResult<T>,
+    {
+        let mut connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        if connection.is_none() {
+            let opened = self
+                .client
+                .get_connection_with_timeout(self.timeout)
+                .map_err(unavailable)?;
+            opened
+                .set_read_timeout(Some(self.timeout))
+                .and_then(|_| opened.set_write_timeout(Some(self.timeout)))
+                .map_err(unavailable)?;
+            *connection = Some(opened);
+        }
+
+        let result = command(connection.as_mut().expect("connection is opened above"));
+        if result.is_err() {
+            *connection = None;
+        }
+        result.map_err(unavailable)
+    }
+}
+
+impl std::fmt::Debug for RedisSigStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // the client may hold the password
+        f.debug_struct("RedisSigStore")
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SigStore for RedisSigStore {
+    fn load(&self, key: &str) -> Result<Option<String>> {
+        self.with_connection(|connection| connection.get(key))
+    }
+
+    fn store(&self, key: &str, sig: &str, ttl: Duration) -> Result<()> {
+        // EX needs whole seconds, rounded down to never outlive the TTL
+        match ttl.as_secs() {
+            0 => Ok(()),
+            secs => self.with_connection(|connection| connection.set_ex(key, sig, secs)),
+        }
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        self.with_connection(|connection| connection.del(key))
+    }
+}
+
+fn unavailable(e: RedisError) -> TlsSigError {
+    TlsSigError::StoreUnavailable(e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::RedisSigStore;
+    use crate::{SigStore, TlsSigError};
+    use std::time::Duration;
+
+    #[test]
+    fn test_redis_sig_store_unavailable() {
+        assert!(matches!(
+            RedisSigStore::new("not a url"),
+            Err(TlsSigError::StoreUnavailable(_))
+        ));
+
+        // nothing listens on the discard port
+        let store = RedisSigStore::new("redis://127.0.0.1:9/")
+            .unwrap()
+            .with_timeout(Duration::from_millis(100));
+        assert!(matches!(
+            store.load("tls-sig:0:1000:-:admin"),
+            Err(TlsSigError::StoreUnavailable(_))
+        ));
+    }
+}
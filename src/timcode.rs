@@ -0,0 +1,229 @@
+use std::fmt;
+
+/// The `ErrorCode` of a failed Tencent IM REST API call, see
+/// [the error code list](https://cloud.tencent.com/document/product/269/1671).
+///
+/// Only the codes commonly hit by admin tooling are named, the others are
+/// kept as [`Unknown`]. Use [`is_retryable`] and [`is_sig_error`] to decide
+/// whether a call is worth retrying.
+///
+/// [`Unknown`]: #variant.Unknown
+/// [`is_retryable`]: #method.is_retryable
+/// [`is_sig_error`]: #method.is_sig_error
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::TimErrorCode;
+///
+/// let code = TimErrorCode::from(70001);
+/// assert_eq!(code, TimErrorCode::SigExpired);
+/// assert!(code.is_sig_error());
+/// assert_eq!(TimErrorCode::from(12345), TimErrorCode::Unknown(12345));
+/// assert!(TimErrorCode::from(12345).is_permanent());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimErrorCode {
+    /// 70001, the sig has been expired.
+    SigExpired,
+    /// 70002, the sig is empty.
+    SigMissing,
+    /// 70003, the sig can not be decoded.
+    SigInvalid,
+    /// 70005, the sig can not be decompressed.
+    SigMalformed,
+    /// 70009, the sig is not signed by the key of the app.
+    SigCheckFailed,
+    /// 70013, the sig is not issued to the calling identifier.
+    SigIdentifierMismatch,
+    /// 70014, the sig is not issued by the calling sdkappid.
+    SigSdkAppIdMismatch,
+    /// 70016, the app has no key to verify sigs with.
+    KeyNotFound,
+    /// 70020, the sdkappid is not found.
+    SdkAppIdNotFound,
+    /// 70052, the sig has been revoked.
+    SigRevoked,
+    /// 70107, the account is not imported.
+    AccountNotFound,
+    /// 70169, the server times out, retry later.
+    ServerTimeout,
+    /// 70398, the app has too many accounts.
+    AccountLimitExceeded,
+    /// 70402, the request has invalid parameters.
+    InvalidParameter,
+    /// 70403, the caller is not an app administrator.
+    NotAdmin,
+    /// 70500, the server fails internally, retry later.
+    ServerError,
+    /// 10002, the group service fails internally, retry later.
+    GroupServerError,
+    /// 10003, the group command is invalid.
+    InvalidGroupCommand,
+    /// 10004, the group request has invalid parameters.
+    InvalidGroup,
+    /// 10007, the caller lacks the permission on the group.
+    GroupPermissionDenied,
+    /// 10010, the group has been dismissed.
+    GroupDismissed,
+    /// 10015, the group id is invalid.
+    InvalidGroupId,
+    /// 20001, the request packet is invalid.
+    InvalidRequest,
+    /// 20003, the identifier of the message is invalid.
+    InvalidMessageIdentifier,
+    /// 20004, the network fails, retry later.
+    NetworkError,
+    /// 20005, the message service fails internally, retry later.
+    MessageServerError,
+    /// 90001, the request is not valid JSON.
+    InvalidJson,
+    /// 90009, the caller is not an app administrator.
+    NotAppAdmin,
+    Unknown(u32),
+}
+
+impl TimErrorCode {
+    pub fn code(self) -> u32 {
+        match self {
+            TimErrorCode::SigExpired => 70001,
+            TimErrorCode::SigMissing => 70002,
+            TimErrorCode::SigInvalid => 70003,
+            TimErrorCode::SigMalformed => 70005,
+            TimErrorCode::SigCheckFailed => 70009,
+            TimErrorCode::SigIdentifierMismatch => 70013,
+            TimErrorCode::SigSdkAppIdMismatch => 70014,
+            TimErrorCode::KeyNotFound => 70016,
+            TimErrorCode::SdkAppIdNotFound => 70020,
+            TimErrorCode::SigRevoked => 70052,
+            TimErrorCode::AccountNotFound => 70107,
+            TimErrorCode::ServerTimeout => 70169,
+            TimErrorCode::AccountLimitExceeded => 70398,
+            TimErrorCode::InvalidParameter => 70402,
+            TimErrorCode::NotAdmin => 70403,
+            TimErrorCode::ServerError => 70500,
+            TimErrorCode::GroupServerError => 10002,
+            TimErrorCode::InvalidGroupCommand => 10003,
+            TimErrorCode::InvalidGroup => 10004,
+            TimErrorCode::GroupPermissionDenied => 10007,
+            TimErrorCode::GroupDismissed => 10010,
+            TimErrorCode::InvalidGroupId => 10015,
+            TimErrorCode::InvalidRequest => 20001,
+            TimErrorCode::InvalidMessageIdentifier => 20003,
+            TimErrorCode::NetworkError => 20004,
+            TimErrorCode::MessageServerError => 20005,
+            TimErrorCode::InvalidJson => 90001,
+            TimErrorCode::NotAppAdmin => 90009,
+            TimErrorCode::Unknown(code) => code,
+        }
+    }
+
+    /// whether the server fails transiently, so that the same call may
+    /// succeed later.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            TimErrorCode::ServerTimeout
+                | TimErrorCode::ServerError
+                | TimErrorCode::GroupServerError
+                | TimErrorCode::NetworkError
+                | TimErrorCode::MessageServerError
+        )
+    }
+
+    /// whether the admin sig is rejected, so that the call may succeed with a
+    /// new one.
+    pub fn is_sig_error(self) -> bool {
+        matches!(
+            self,
+            TimErrorCode::SigExpired
+                | TimErrorCode::SigMissing
+                | TimErrorCode::SigInvalid
+                | TimErrorCode::SigMalformed
+                | TimErrorCode::SigCheckFailed
+                | TimErrorCode::SigRevoked
+        )
+    }
+
+    /// whether retrying the call can not help.
+    pub fn is_permanent(self) -> bool {
+        !self.is_retryable() && !self.is_sig_error()
+    }
+}
+
+impl From<u32> for TimErrorCode {
+    fn from(code: u32) -> Self {
+        match code {
+            70001 => TimErrorCode::SigExpired,
+            70002 => TimErrorCode::SigMissing,
+            70003 => TimErrorCode::SigInvalid,
+            70005 => TimErrorCode::SigMalformed,
+            70009 => TimErrorCode::SigCheckFailed,
+            70013 => TimErrorCode::SigIdentifierMismatch,
+            70014 => TimErrorCode::SigSdkAppIdMismatch,
+            70016 => TimErrorCode::KeyNotFound,
+            70020 => TimErrorCode::SdkAppIdNotFound,
+            70052 => TimErrorCode::SigRevoked,
+            70107 => TimErrorCode::AccountNotFound,
+            70169 => TimErrorCode::ServerTimeout,
+            70398 => TimErrorCode::AccountLimitExceeded,
+            70402 => TimErrorCode::InvalidParameter,
+            70403 => TimErrorCode::NotAdmin,
+            70500 => TimErrorCode::ServerError,
+            10002 => TimErrorCode::GroupServerError,
+            10003 => TimErrorCode::InvalidGroupCommand,
+            10004 => TimErrorCode::InvalidGroup,
+            10007 => TimErrorCode::GroupPermissionDenied,
+            10010 => TimErrorCode::GroupDismissed,
+            10015 => TimErrorCode::InvalidGroupId,
+            20001 => TimErrorCode::InvalidRequest,
+            20003 => TimErrorCode::InvalidMessageIdentifier,
+            20004 => TimErrorCode::NetworkError,
+            20005 => TimErrorCode::MessageServerError,
+            90001 => TimErrorCode::InvalidJson,
+            90009 => TimErrorCode::NotAppAdmin,
+            code => TimErrorCode::Unknown(code),
+        }
+    }
+}
+
+impl From<TimErrorCode> for u32 {
+    fn from(code: TimErrorCode) -> u32 {
+        code.code()
+    }
+}
+
+impl fmt::Display for TimErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TimErrorCode;
+
+    #[test]
+    fn test_tim_error_code_round_trip() {
+        for code in [
+            70001, 70002, 70003, 70005, 70009, 70013, 70014, 70016, 70020, 70052, 70107, 70169,
+            70398, 70402, 70403, 70500, 10002, 10003, 10004, 10007, 10010, 10015, 20001, 20003,
+            20004, 20005, 90001, 90009,
+        ] {
+            let named = TimErrorCode::from(code);
+            assert!(!matches!(named, TimErrorCode::Unknown(_)), "{}", code);
+            assert_eq!(named.code(), code);
+        }
+        assert_eq!(TimErrorCode::from(1).code(), 1);
+    }
+
+    #[test]
+    fn test_tim_error_code_classes() {
+        assert!(TimErrorCode::ServerTimeout.is_retryable());
+        assert!(!TimErrorCode::ServerTimeout.is_permanent());
+        assert!(TimErrorCode::SigCheckFailed.is_sig_error());
+        assert!(!TimErrorCode::SigCheckFailed.is_retryable());
+        assert!(TimErrorCode::InvalidGroup.is_permanent());
+        assert!(TimErrorCode::Unknown(1).is_permanent());
+    }
+}
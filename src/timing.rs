@@ -0,0 +1,181 @@
+//! Conversions from the time types of `std`, `chrono` (default feature) and
+//! `time` (optional feature) into the seconds carried by a sig, so that
+//! callers can use whichever they already have.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone};
+
+/// Types that can be used as the validity period of a sig.
+pub trait ExpireDuration {
+    /// The validity period in seconds, saturated into `i64`.
+    fn expire_secs(&self) -> i64;
+}
+
+/// Types that can be used as a point of time when signing or verifying.
+pub trait UnixTime {
+    /// Seconds since the unix epoch, negative for times before it.
+    fn unix_timestamp(&self) -> i64;
+}
+
+impl ExpireDuration for Duration {
+    fn expire_secs(&self) -> i64 {
+        saturating_secs(*self)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ExpireDuration for chrono::Duration {
+    fn expire_secs(&self) -> i64 {
+        self.num_seconds()
+    }
+}
+
+#[cfg(feature = "time")]
+impl ExpireDuration for time::Duration {
+    fn expire_secs(&self) -> i64 {
+        self.whole_seconds()
+    }
+}
+
+impl<T: ExpireDuration + ?Sized> ExpireDuration for &T {
+    fn expire_secs(&self) -> i64 {
+        (**self).expire_secs()
+    }
+}
+
+impl UnixTime for SystemTime {
+    fn unix_timestamp(&self) -> i64 {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(since) => saturating_secs(since),
+            Err(e) => -saturating_secs(e.duration()),
+        }
+    }
+}
+
+/// Plain unix timestamps in seconds.
+impl UnixTime for i64 {
+    fn unix_timestamp(&self) -> i64 {
+        *self
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: TimeZone> UnixTime for DateTime<Tz> {
+    fn unix_timestamp(&self) -> i64 {
+        self.timestamp()
+    }
+}
+
+#[cfg(feature = "time")]
+impl UnixTime for time::OffsetDateTime {
+    fn unix_timestamp(&self) -> i64 {
+        time::OffsetDateTime::unix_timestamp(*self)
+    }
+}
+
+impl<T: UnixTime + ?Sized> UnixTime for &T {
+    fn unix_timestamp(&self) -> i64 {
+        (**self).unix_timestamp()
+    }
+}
+
+/// convert unix timestamp in seconds back into `SystemTime`.
+pub(crate) fn system_time(timestamp: i64) -> SystemTime {
+    if timestamp >= 0 {
+        UNIX_EPOCH + Duration::from_secs(timestamp as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(timestamp.unsigned_abs())
+    }
+}
+
+/// format unix timestamp in seconds as RFC3339 in UTC, independent of the
+/// time crates enabled.
+pub(crate) fn rfc3339(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86400);
+    let secs_of_day = timestamp.rem_euclid(86400);
+
+    // civil_from_days by Howard Hinnant, proleptic Gregorian calendar
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+fn saturating_secs(d: Duration) -> i64 {
+    if d.as_secs() > i64::MAX as u64 {
+        i64::MAX
+    } else {
+        d.as_secs() as i64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{rfc3339, system_time, ExpireDuration, UnixTime};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_expire_secs() {
+        assert_eq!(Duration::from_secs(7200).expire_secs(), 7200);
+        assert_eq!(Duration::from_millis(1999).expire_secs(), 1);
+        assert_eq!(Duration::from_secs(u64::MAX).expire_secs(), i64::MAX);
+    }
+
+    #[test]
+    fn test_unix_timestamp() {
+        let st = UNIX_EPOCH + Duration::from_secs(1569910200);
+
+        assert_eq!(st.unix_timestamp(), 1569910200);
+        assert_eq!(1569910200.unix_timestamp(), 1569910200);
+        assert_eq!((UNIX_EPOCH - Duration::from_secs(1)).unix_timestamp(), -1);
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(1569910200), "2019-10-01T06:10:00Z");
+        assert_eq!(rfc3339(951782400), "2000-02-29T00:00:00Z");
+        assert_eq!(rfc3339(-1), "1969-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn test_system_time() {
+        assert_eq!(system_time(1569910200).unix_timestamp(), 1569910200);
+        assert_eq!(system_time(-1), UNIX_EPOCH - Duration::from_secs(1));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_conversions() {
+        use chrono::{TimeZone, Utc};
+
+        let dt = Utc.with_ymd_and_hms(2019, 10, 1, 6, 10, 0).unwrap();
+        assert_eq!(dt.unix_timestamp(), 1569910200);
+        assert_eq!(chrono::Duration::hours(2).expire_secs(), 7200);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_conversions() {
+        let dt = time::OffsetDateTime::from_unix_timestamp(1569910200).unwrap();
+        assert_eq!(UnixTime::unix_timestamp(&dt), 1569910200);
+        assert_eq!(time::Duration::hours(2).expire_secs(), 7200);
+    }
+}
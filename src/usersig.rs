@@ -0,0 +1,149 @@
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use serde::{Serialize, Serializer};
+
+use crate::{decode_sign, Result, SigPayload, TlsSigError};
+
+/// A user sign, as generated by the signer or parsed from a client.
+///
+/// It derefs to `str` and serializes as a plain string, so it can be put into
+/// responses as is, while keeping sigs apart from other tokens in the type
+/// system.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{TlsSigApiVer2, UserSig};
+/// use std::time::Duration;
+///
+/// let mock_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let signer = TlsSigApiVer2::new(0, mock_key);
+///
+/// let sig: UserSig = signer.gen_sign("10086", Duration::from_secs(7200), None);
+/// let parsed: UserSig = sig.to_string().parse().unwrap();
+/// assert_eq!(sig, parsed);
+///
+/// assert!("not a sig".parse::<UserSig>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UserSig(String);
+
+impl UserSig {
+    /// wrap a sig that is known to be well-formed, e.g. freshly generated.
+    pub(crate) fn new_unchecked(sig: String) -> Self {
+        UserSig(sig)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// decode the payload carried by the sig **without** verifying it.
+    pub fn payload(&self) -> Result<SigPayload> {
+        decode_sign(&self.0)
+    }
+}
+
+impl Deref for UserSig {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for UserSig {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for UserSig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// parse a sig, checking that it decodes into a valid payload. The HMAC and
+/// expiry are **not** verified.
+impl FromStr for UserSig {
+    type Err = TlsSigError;
+
+    fn from_str(sig: &str) -> Result<Self> {
+        decode_sign(sig)?;
+        Ok(UserSig(sig.to_string()))
+    }
+}
+
+impl Serialize for UserSig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl From<UserSig> for String {
+    fn from(sig: UserSig) -> String {
+        sig.0
+    }
+}
+
+impl PartialEq<str> for UserSig {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for UserSig {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+/// A freshly issued user sign with its issuance metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigInfo {
+    pub sig: UserSig,
+    pub issued_at: SystemTime,
+    pub expires_at: SystemTime,
+    pub identifier: String,
+    pub sdkappid: u64,
+}
+
+#[cfg(test)]
+mod test {
+    use super::UserSig;
+    use crate::TlsSigError;
+
+    // url-safe base64 sig produced by the python version
+    const MOCK_SIG: &str = "eJyrVgrxCdYrSy1SslIy0jNQ0gHzM1NS80oy0zLBwjDB4pTsxIKCzBQlK0MTAyiAyKRWFGQWpQLFTU1NjeCiJZm5YDEzS0tDAyOYaHFmOtBM54KsUqeUwGLLqPK8UndHryCL-Lx8-1RPy7Ty1AijMk9f30hLk0x-r3CvSFulWgAPYy*9";
+
+    #[test]
+    fn test_parse_user_sig() {
+        let sig: UserSig = MOCK_SIG.parse().unwrap();
+
+        assert_eq!(sig, MOCK_SIG);
+        assert_eq!(sig.to_string(), MOCK_SIG);
+        assert_eq!(sig.payload().unwrap().identifier, "0");
+        assert!(matches!(
+            "not a sig".parse::<UserSig>(),
+            Err(TlsSigError::MalformedSig(_))
+        ));
+    }
+
+    #[test]
+    fn test_serialize_user_sig() {
+        let sig: UserSig = MOCK_SIG.parse().unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&sig).unwrap(),
+            format!("\"{}\"", MOCK_SIG)
+        );
+    }
+}
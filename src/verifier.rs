@@ -0,0 +1,137 @@
+use crate::{HmacBackend, MacBackend, Result, TlsSigApiVer2, TlsSigError};
+
+/// A verifier accepting sigs by the signer's own key plus a set of secondary
+/// keys, so that sigs issued before a key rotation stay valid until they
+/// expire.
+///
+/// The HMAC is checked against every key in constant time, so that the
+/// timing does not reveal which key, if any, matches.
+///
+/// # Examples
+///
+/// ```
+/// use tls_sig_api::{MultiKeyVerifier, TlsSigApiVer2};
+/// use std::time::Duration;
+///
+/// let old_key = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+/// let new_key = "0f4c78e5bb2c8ab578ba8a8617a95e0ff0d4d8f1e5c9ebb1a73d0a51d1f6b4ea";
+///
+/// let old_sig = TlsSigApiVer2::new(0, old_key).gen_sign("10086", Duration::from_secs(7200), None);
+///
+/// let verifier = MultiKeyVerifier::new(TlsSigApiVer2::new(0, new_key)).with_secondary_key(old_key);
+/// assert!(verifier.verify_sign("10086", &old_sig).is_ok());
+///
+/// // new sigs are signed with the primary key
+/// let new_sig = verifier.signer().gen_sign("10086", Duration::from_secs(7200), None);
+/// assert!(verifier.verify_sign("10086", &new_sig).is_ok());
+/// ```
+pub struct MultiKeyVerifier {
+    primary: TlsSigApiVer2,
+    secondaries: Vec<Box<dyn MacBackend>>,
+}
+
+impl MultiKeyVerifier {
+    /// verify with the key and settings of `primary`, e.g. its clock and
+    /// leeway.
+    pub fn new(primary: TlsSigApiVer2) -> Self {
+        MultiKeyVerifier {
+            primary,
+            secondaries: Vec::new(),
+        }
+    }
+
+    /// also accept sigs signed by `key`.
+    pub fn with_secondary_key(self, key: &str) -> Self {
+        self.with_secondary_backend(HmacBackend::new(key))
+    }
+
+    /// also accept sigs whose HMAC is computed by `backend`.
+    pub fn with_secondary_backend<B: MacBackend + 'static>(mut self, backend: B) -> Self {
+        self.secondaries.push(Box::new(backend));
+        self
+    }
+
+    /// the signer of the primary key, for issuing new sigs.
+    pub fn signer(&self) -> &TlsSigApiVer2 {
+        &self.primary
+    }
+
+    /// verify a user sign like [`TlsSigApiVer2::verify_sign`], accepting the
+    /// primary key and any secondary key.
+    ///
+    /// [`TlsSigApiVer2::verify_sign`]: struct.TlsSigApiVer2.html#method.verify_sign
+    pub fn verify_sign(&self, identifier: &str, sig: &str) -> Result<()> {
+        let payload = self.primary.decode_claims(identifier, sig)?;
+        let content = self.primary.signing_content(
+            &payload.identifier,
+            payload.time,
+            payload.expire,
+            payload.userbuf.as_deref(),
+        );
+        let digest = payload.digest()?;
+        let digest = digest.as_bytes();
+
+        // no short-circuit, every key is tried
+        let mut matched = false;
+        let primary = self.primary.mac();
+        for backend in std::iter::once(&**primary).chain(&self.secondaries) {
+            match backend.verify_hmac_sha256(content.as_bytes(), digest) {
+                Ok(()) => matched = true,
+                Err(TlsSigError::SignatureMismatch) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if !matched {
+            return Err(TlsSigError::SignatureMismatch);
+        }
+
+        self.primary
+            .check_validity(&payload, self.primary.clock.now())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MultiKeyVerifier;
+    use crate::{TlsSigApiVer2, TlsSigError};
+    use std::time::Duration;
+
+    const OLD_KEY: &str = "5bd2850fff3ecb11d7c805251c51ee463a25727bddc2385f3fa8bfee1bb93b5e";
+    const NEW_KEY: &str = "new key";
+    const EXPIRE: Duration = Duration::from_secs(7200);
+
+    #[test]
+    fn test_multi_key_verifier() {
+        let verifier = MultiKeyVerifier::new(TlsSigApiVer2::new(1400000000, NEW_KEY))
+            .with_secondary_key(OLD_KEY);
+
+        let old_sig = TlsSigApiVer2::new(1400000000, OLD_KEY).gen_sign("0", EXPIRE, None);
+        let new_sig = TlsSigApiVer2::new(1400000000, NEW_KEY).gen_sign("0", EXPIRE, None);
+        let other_sig = TlsSigApiVer2::new(1400000000, "other key").gen_sign("0", EXPIRE, None);
+
+        assert!(verifier.verify_sign("0", &old_sig).is_ok());
+        assert!(verifier.verify_sign("0", &new_sig).is_ok());
+        assert!(matches!(
+            verifier.verify_sign("0", &other_sig),
+            Err(TlsSigError::SignatureMismatch)
+        ));
+        assert!(matches!(
+            verifier.verify_sign("1", &old_sig),
+            Err(TlsSigError::IdentifierMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_multi_key_verifier_checks_expiry() {
+        let verifier = MultiKeyVerifier::new(TlsSigApiVer2::new(1400000000, NEW_KEY))
+            .with_secondary_key(OLD_KEY);
+        let expired = TlsSigApiVer2::new(1400000000, OLD_KEY)
+            .gen_sign_at("0", 1569910200, EXPIRE, None)
+            .unwrap();
+
+        assert!(matches!(
+            verifier.verify_sign("0", &expired),
+            Err(TlsSigError::Expired)
+        ));
+    }
+}
@@ -1,10 +1,6 @@
-use chrono::Duration;
 use dotenv::{dotenv, var};
 use log::*;
-use rand::prelude::*;
-use reqwest::Client;
-use std::collections::HashMap;
-use tls_sig_api::TlsSigApiVer2;
+use tls_sig_api::{TimClientBlocking, TlsSigApiVer2};
 
 #[test]
 fn check_generated_sig_correctness() {
@@ -25,28 +21,9 @@ fn check_generated_sig_correctness() {
         key,
         admin
     );
-    let sig_api = TlsSigApiVer2::new(appid, &key);
+    let client = TimClientBlocking::new(TlsSigApiVer2::new(appid, &key), &admin);
 
-    let admin_sig = sig_api.gen_sign(&admin, Duration::hours(10), None);
-    trace!("generated admin_sig: {}", admin_sig);
-
-    let r = random::<u32>();
-
-    let url = format!("https://console.tim.qq.com/v4/im_open_login_svc/account_import?sdkappid={}&identifier={}&usersig={}&random={}&contenttype=json", appid, admin, admin_sig, r).to_string();
-    trace!("concated url: {}", url);
-
-    let mut map = HashMap::new();
-    map.insert("Identifier", "test");
-
-    let client = Client::new();
-    let res = client
-        .post(&url)
-        .json(&map)
-        .send()
-        .expect("Sending Request failed!")
-        .text()
-        .expect("Reading Content failed!");
-    trace!("Get respone: {}", res);
-
-    assert!(res.contains(r#""ActionStatus":"OK""#));
+    client
+        .account_import("test", None, None)
+        .expect("Importing account failed!");
 }